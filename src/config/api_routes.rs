@@ -11,6 +11,10 @@ pub fn api_routes(state: Arc<AppState>) -> Router<Arc<AppState>, Body> {
         .route("/ping", get(ping_handler))
         .route("/tempApiGetToken", get(temp_api_get_token))
         .route("/tempApiGetOtp", get(temp_api_get_otp))
+        .nest("/admin", admin_routes())
+        .nest("/contest", contest_routes())
+        .route("/contests", get(list_public_contests_handler))
+        .nest("/user", user_routes())
         .nest("/wallet", wallet_routes())
         .route_layer(middleware::from_fn_with_state(state, auth_middleware))
 }
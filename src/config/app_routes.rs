@@ -8,7 +8,14 @@ use axum::{
 };
 use tower::ServiceBuilder;
 use tower_http::{
-    cors::CorsLayer, set_header::SetResponseHeaderLayer, timeout::TimeoutLayer, trace::TraceLayer,
+    compression::{
+        predicate::{NotForContentType, Predicate},
+        CompressionLayer, DefaultPredicate,
+    },
+    cors::CorsLayer,
+    set_header::SetResponseHeaderLayer,
+    timeout::TimeoutLayer,
+    trace::TraceLayer,
     ServiceBuilderExt,
 };
 use utoipa::OpenApi;
@@ -25,12 +32,16 @@ pub fn build_app_routes(state: Arc<AppState>) -> Router {
     let trace_layer = TraceLayer::new_for_http();
     let cors_layer = CorsLayer::permissive();
     let timeout_layer = TimeoutLayer::new(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+    // compressed server-sent events are held back in the encoder buffer
+    let compress_predicate =
+        DefaultPredicate::new().and(NotForContentType::const_new("text/event-stream"));
+    let compression_layer = CompressionLayer::new().compress_when(compress_predicate);
     let middleware = ServiceBuilder::new()
         .layer(timeout_layer)
         .layer(cors_layer)
         .layer(set_response_header_layer)
         .map_response_body(boxed)
-        .compression()
+        .layer(compression_layer)
         .trim_trailing_slash()
         .catch_panic()
         .layer(trace_layer)
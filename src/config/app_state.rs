@@ -1,7 +1,14 @@
 use crate::import_double;
-use std::sync::Arc;
+use mongodb::bson::oid::ObjectId;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
 
-use crate::helpers::Helpers;
+use crate::{
+    constants::*,
+    helpers::Helpers,
+    models::{ContestEvent, ContestUpdate, LeaderboardEntry},
+    utils::TtlCache,
+};
 
 import_double!(DbClient, ExternalApi, Validators, Utility);
 
@@ -11,6 +18,8 @@ pub struct AppState {
     validators: Arc<Validators>,
     utility: Arc<Utility>,
     helpers: Arc<Helpers>,
+    leaderboard_cache: TtlCache<ObjectId, Arc<Vec<LeaderboardEntry>>>,
+    contest_events: broadcast::Sender<ContestEvent>,
 }
 
 impl AppState {
@@ -26,6 +35,8 @@ impl AppState {
             utility: Arc::new(utility),
             validators: Arc::new(validators),
             helpers: Arc::new(helpers),
+            leaderboard_cache: leaderboard_cache(),
+            contest_events: broadcast::channel(CONTEST_EVENTS_CAPACITY).0,
         }
     }
     pub fn db(&self) -> &DbClient {
@@ -43,6 +54,24 @@ impl AppState {
     pub fn helpers(&self) -> &Helpers {
         self.helpers.as_ref()
     }
+    /// Leaderboards of the live contests
+    pub fn leaderboard_cache(&self) -> &TtlCache<ObjectId, Arc<Vec<LeaderboardEntry>>> {
+        &self.leaderboard_cache
+    }
+    /// Live updates of the contests, slow subscribers miss the oldest updates
+    pub fn contest_events(&self) -> &broadcast::Sender<ContestEvent> {
+        &self.contest_events
+    }
+    pub fn publish_contest_event(&self, contest_id: ObjectId, update: ContestUpdate) {
+        // sending fails only when nobody is subscribed
+        let _ = self
+            .contest_events
+            .send(ContestEvent { contest_id, update });
+    }
+}
+
+fn leaderboard_cache() -> TtlCache<ObjectId, Arc<Vec<LeaderboardEntry>>> {
+    TtlCache::new(Duration::from_secs(LEADERBOARD_CACHE_TTL_SECS))
 }
 
 #[cfg(test)]
@@ -59,6 +88,8 @@ impl AppState {
             validators,
             utility,
             helpers,
+            leaderboard_cache: leaderboard_cache(),
+            contest_events: broadcast::channel(CONTEST_EVENTS_CAPACITY).0,
         }
     }
     pub fn get_mut_db(&mut self) -> &mut DbClient {
@@ -6,8 +6,9 @@ use mongodb::{
     bson::{Bson, Document},
     error::{Error as MongoError, Result as MongoResult},
     options::{
-        AggregateOptions, ClientOptions, DeleteOptions, FindOneAndUpdateOptions, FindOneOptions,
-        FindOptions, InsertOneOptions, SessionOptions, TransactionOptions, UpdateOptions,
+        AggregateOptions, ClientOptions, DeleteOptions, DistinctOptions, FindOneAndUpdateOptions,
+        FindOneOptions, FindOptions, InsertOneOptions, SessionOptions, TransactionOptions,
+        UpdateOptions,
     },
     results::{InsertOneResult, UpdateResult as MongoUpdateResult},
     Client,
@@ -16,6 +17,8 @@ use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{constants::*, import_double};
 
+use super::db_retry::RetryPolicy;
+
 import_double!(DbSession);
 
 #[derive(Debug)]
@@ -67,7 +70,7 @@ impl TryFrom<MongoUpdateResult> for UpdateResult {
     }
 }
 
-pub struct DbClient(Client);
+pub struct DbClient(Client, RetryPolicy);
 
 impl DbClient {
     pub async fn new() -> Self {
@@ -86,7 +89,7 @@ impl DbClient {
         client_options.connect_timeout = Some(timeout);
         let client =
             Client::with_options(client_options).expect("Not able to create mongodb Client");
-        Self(client)
+        Self(client, RetryPolicy::from_env())
     }
 
     pub async fn find_one<T>(
@@ -206,6 +209,18 @@ impl DbClient {
         Ok(data)
     }
 
+    pub async fn distinct(
+        &self,
+        db: &str,
+        coll: &str,
+        field: &str,
+        filter: Option<Document>,
+        options: Option<DistinctOptions>,
+    ) -> MongoResult<Vec<Bson>> {
+        let collection = self.0.database(db).collection::<Document>(coll);
+        collection.distinct(field, filter, options).await
+    }
+
     pub async fn execute_transaction<F>(
         &self,
         session_options: Option<SessionOptions>,
@@ -223,7 +238,7 @@ impl DbClient {
 
     async fn start_session(&self, options: Option<SessionOptions>) -> MongoResult<DbSession> {
         let session = self.0.start_session(options).await?;
-        let session = DbSession::new(session);
+        let session = DbSession::new(session, self.1);
         Ok(session)
     }
 }
@@ -308,6 +323,15 @@ mockall::mock! {
             options: Option<AggregateOptions>,
         ) -> MongoResult<Vec<Document>>;
 
+        pub async fn distinct(
+            &self,
+            db: &str,
+            coll: &str,
+            field: &str,
+            filter: Option<Document>,
+            options: Option<DistinctOptions>,
+        ) -> MongoResult<Vec<Bson>>;
+
         pub async fn execute_transaction<F>(
             &self,
             session_options: Option<SessionOptions>,
@@ -1,27 +1,33 @@
+use std::time::Instant;
+
 use futures::future::BoxFuture;
 use mongodb::{
     bson::Document,
     error::{
-        Result as MongoResult, TRANSIENT_TRANSACTION_ERROR, UNKNOWN_TRANSACTION_COMMIT_RESULT,
+        Error as MongoError, Result as MongoResult, TRANSIENT_TRANSACTION_ERROR,
+        UNKNOWN_TRANSACTION_COMMIT_RESULT,
     },
     options::{
-        FindOneAndUpdateOptions, FindOneOptions, FindOptions, InsertOneOptions, TransactionOptions,
-        UpdateModifications, UpdateOptions,
+        AggregateOptions, FindOneAndUpdateOptions, FindOneOptions, FindOptions, InsertOneOptions,
+        TransactionOptions, UpdateModifications, UpdateOptions,
     },
     ClientSession,
 };
 use serde::{de::DeserializeOwned, Serialize};
 
-use super::database::{InsertedId, UpdateResult};
+use super::{
+    database::{InsertedId, UpdateResult},
+    db_retry::{RetryPolicy, RetryStage},
+};
 
 #[cfg(test)]
 use mockall::mock;
 
-pub struct DbSession(ClientSession);
+pub struct DbSession(ClientSession, RetryPolicy);
 
 impl DbSession {
-    pub fn new(session: ClientSession) -> Self {
-        Self(session)
+    pub fn new(session: ClientSession, retry_policy: RetryPolicy) -> Self {
+        Self(session, retry_policy)
     }
 
     pub async fn find_with_session<T>(
@@ -129,6 +135,28 @@ impl DbSession {
         result.try_into()
     }
 
+    pub async fn aggregate_with_session(
+        &mut self,
+        db: &str,
+        coll: &str,
+        pipeline: Vec<Document>,
+        options: Option<AggregateOptions>,
+    ) -> MongoResult<Vec<Document>> {
+        let client = self.0.client();
+        let collection = client.database(db).collection::<Document>(coll);
+        let mut cursor = collection
+            .aggregate_with_session(pipeline, options, &mut self.0)
+            .await?;
+        let mut data = vec![];
+        while let Some(doc) = cursor.next(&mut self.0).await {
+            data.push(doc?);
+        }
+        Ok(data)
+    }
+
+    /// Execute the closure in a transaction and commit it.
+    /// The whole transaction is retried on TransientTransactionError and the commit is
+    /// retried on UnknownTransactionCommitResult as per the retry policy.
     pub async fn execute_transaction<F>(
         &mut self,
         options: Option<TransactionOptions>,
@@ -137,41 +165,61 @@ impl DbSession {
     where
         F: for<'a> Fn(&'a mut DbSession) -> BoxFuture<'a, MongoResult<()>>,
     {
-        self.0.start_transaction(options).await?;
-        while let Err(e) = f(self).await {
-            if e.contains_label(TRANSIENT_TRANSACTION_ERROR) {
-                continue;
-            } else {
-                self.abort_transaction().await?;
-                return Err(e);
+        let started = Instant::now();
+        let mut attempt = 1;
+        loop {
+            self.0.start_transaction(options.clone()).await?;
+            let result = match f(self).await {
+                Ok(_) => self.commit_transaction(started).await,
+                Err(e) => {
+                    self.abort_transaction().await;
+                    Err(e)
+                }
+            };
+            match result {
+                Err(e) if e.contains_label(TRANSIENT_TRANSACTION_ERROR) => {
+                    let delay = self
+                        .1
+                        .next_retry(RetryStage::Transaction, attempt, started.elapsed(), &e)
+                        .map_err(MongoError::custom)?;
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
             }
         }
-        self.commit_transaction().await?;
-        Ok(())
     }
 
-    async fn abort_transaction(&mut self) -> MongoResult<()> {
-        self.0.abort_transaction().await?;
-        Ok(())
+    /// Abort the transaction, failure is only logged so that
+    /// the original error of the transaction is returned
+    async fn abort_transaction(&mut self) {
+        if let Err(e) = self.0.abort_transaction().await {
+            tracing::warn!("Not able to abort transaction: {e}");
+        }
     }
 
-    async fn commit_transaction(&mut self) -> MongoResult<()> {
-        while let Err(e) = self.0.commit_transaction().await {
-            if e.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT) {
-                continue;
-            } else {
-                self.abort_transaction().await?;
-                return Err(e);
+    async fn commit_transaction(&mut self, started: Instant) -> MongoResult<()> {
+        let mut attempt = 1;
+        loop {
+            match self.0.commit_transaction().await {
+                Err(e) if e.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT) => {
+                    let delay = self
+                        .1
+                        .next_retry(RetryStage::Commit, attempt, started.elapsed(), &e)
+                        .map_err(MongoError::custom)?;
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
             }
         }
-        Ok(())
     }
 }
 
 #[cfg(test)]
 mock! {
     pub DbSession {
-        pub fn new(session: ClientSession) -> Self;
+        pub fn new(session: ClientSession, retry_policy: RetryPolicy) -> Self;
 
         pub async fn execute_transaction<F>(
             &mut self,
@@ -240,5 +288,13 @@ mock! {
             update: Document,
             options: Option<UpdateOptions>,
         ) -> MongoResult<UpdateResult>;
+
+        pub async fn aggregate_with_session(
+            &mut self,
+            db: &str,
+            coll: &str,
+            pipeline: Vec<Document>,
+            options: Option<AggregateOptions>,
+        ) -> MongoResult<Vec<Document>>;
     }
 }
@@ -0,0 +1,263 @@
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use mongodb::error::Error as MongoError;
+use rand::Rng;
+
+use crate::constants::*;
+
+/// Retry policy of the database transactions.
+/// Configurable with `DB_TXN_MAX_ATTEMPTS`, `DB_TXN_BASE_DELAY_MS`,
+/// `DB_TXN_MAX_DELAY_MS` and `DB_TXN_DEADLINE_MS` env variables.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// maximum attempts including the first one
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// overall time allowed for the transaction including all the retries
+    pub deadline: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DB_TXN_MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(DB_TXN_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(DB_TXN_MAX_DELAY_MS),
+            deadline: Duration::from_millis(DB_TXN_DEADLINE_MS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        let env_or = |key: &str, default: u64| {
+            std::env::var(key)
+                .unwrap_or_default()
+                .parse::<u64>()
+                .unwrap_or(default)
+        };
+        let max_attempts = env_or("DB_TXN_MAX_ATTEMPTS", DB_TXN_MAX_ATTEMPTS as u64);
+        Self {
+            max_attempts: (max_attempts as u32).max(1),
+            base_delay: Duration::from_millis(env_or("DB_TXN_BASE_DELAY_MS", DB_TXN_BASE_DELAY_MS)),
+            max_delay: Duration::from_millis(env_or("DB_TXN_MAX_DELAY_MS", DB_TXN_MAX_DELAY_MS)),
+            deadline: Duration::from_millis(env_or("DB_TXN_DEADLINE_MS", DB_TXN_DEADLINE_MS)),
+        }
+    }
+
+    /// Exponential backoff with equal jitter, the delay after the given attempt is
+    /// a random duration between half and full of `base_delay * 2^(attempt - 1)`
+    /// capped by `max_delay`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exp)
+            .min(self.max_delay)
+            .as_millis() as u64;
+        let half = delay / 2;
+        let jitter = rand::thread_rng().gen_range(0..=delay - half);
+        Duration::from_millis(half + jitter)
+    }
+
+    /// Returns the delay before the next attempt or the error if no more retries are allowed
+    pub fn next_retry(
+        &self,
+        stage: RetryStage,
+        attempt: u32,
+        elapsed: Duration,
+        err: &MongoError,
+    ) -> Result<Duration, TransactionRetryError> {
+        if attempt >= self.max_attempts {
+            RETRY_METRICS.record_exhausted();
+            let err = TransactionRetryError::AttemptsExhausted {
+                stage,
+                attempts: attempt,
+                last_error: err.to_string(),
+            };
+            tracing::error!("{err}");
+            return Err(err);
+        }
+        let delay = self.backoff(attempt);
+        if elapsed + delay > self.deadline {
+            RETRY_METRICS.record_exhausted();
+            let err = TransactionRetryError::DeadlineExceeded {
+                stage,
+                attempts: attempt,
+                elapsed,
+                last_error: err.to_string(),
+            };
+            tracing::error!("{err}");
+            return Err(err);
+        }
+        RETRY_METRICS.record_retry(stage);
+        tracing::warn!(
+            "Retrying {} after attempt {} in {:?}, error: {}",
+            stage,
+            attempt,
+            delay,
+            err
+        );
+        Ok(delay)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RetryStage {
+    /// the whole transaction is retried on TransientTransactionError
+    Transaction,
+    /// only commit is retried on UnknownTransactionCommitResult
+    Commit,
+}
+
+impl Display for RetryStage {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Transaction => write!(f, "transaction"),
+            Self::Commit => write!(f, "commit"),
+        }
+    }
+}
+
+/// Error returned when a transaction can not be completed within the retry policy
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionRetryError {
+    AttemptsExhausted {
+        stage: RetryStage,
+        attempts: u32,
+        last_error: String,
+    },
+    DeadlineExceeded {
+        stage: RetryStage,
+        attempts: u32,
+        elapsed: Duration,
+        last_error: String,
+    },
+}
+
+impl Display for TransactionRetryError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::AttemptsExhausted {
+                stage,
+                attempts,
+                last_error,
+            } => write!(
+                f,
+                "Database {stage} failed after {attempts} attempts, last error: {last_error}"
+            ),
+            Self::DeadlineExceeded {
+                stage,
+                attempts,
+                elapsed,
+                last_error,
+            } => write!(
+                f,
+                "Database {stage} deadline exceeded after {attempts} attempts in {elapsed:?}, last error: {last_error}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TransactionRetryError {}
+
+/// Counters of the database transaction retries
+#[derive(Debug, Default)]
+pub struct RetryMetrics {
+    transaction_retries: AtomicU64,
+    commit_retries: AtomicU64,
+    retries_exhausted: AtomicU64,
+}
+
+pub static RETRY_METRICS: RetryMetrics = RetryMetrics {
+    transaction_retries: AtomicU64::new(0),
+    commit_retries: AtomicU64::new(0),
+    retries_exhausted: AtomicU64::new(0),
+};
+
+impl RetryMetrics {
+    fn record_retry(&self, stage: RetryStage) {
+        let (name, counter) = match stage {
+            RetryStage::Transaction => ("db_transaction_retries_total", &self.transaction_retries),
+            RetryStage::Commit => ("db_commit_retries_total", &self.commit_retries),
+        };
+        let value = counter.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::info!(target: "metrics", metric = name, value);
+    }
+
+    fn record_exhausted(&self) {
+        let value = self.retries_exhausted.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::info!(target: "metrics", metric = "db_retries_exhausted_total", value);
+    }
+
+    pub fn transaction_retries(&self) -> u64 {
+        self.transaction_retries.load(Ordering::Relaxed)
+    }
+
+    pub fn commit_retries(&self) -> u64 {
+        self.commit_retries.load(Ordering::Relaxed)
+    }
+
+    pub fn retries_exhausted(&self) -> u64 {
+        self.retries_exhausted.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            deadline: Duration::from_millis(1000),
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = policy();
+        for _ in 0..20 {
+            let delay = policy.backoff(1).as_millis();
+            assert!((50..=100).contains(&delay));
+            let delay = policy.backoff(2).as_millis();
+            assert!((100..=200).contains(&delay));
+            // capped by max delay
+            let delay = policy.backoff(10).as_millis();
+            assert!((150..=300).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn test_next_retry() {
+        let policy = policy();
+        let err = MongoError::custom("write conflict");
+        let retries = RETRY_METRICS.commit_retries();
+        let exhausted = RETRY_METRICS.retries_exhausted();
+        let delay = policy.next_retry(RetryStage::Commit, 1, Duration::ZERO, &err);
+        assert!(delay.is_ok());
+        assert!(RETRY_METRICS.commit_retries() > retries);
+        match policy.next_retry(RetryStage::Commit, 3, Duration::ZERO, &err) {
+            Err(TransactionRetryError::AttemptsExhausted { attempts, .. }) => {
+                assert_eq!(attempts, 3)
+            }
+            _ => panic!(),
+        };
+        let elapsed = Duration::from_millis(990);
+        match policy.next_retry(RetryStage::Transaction, 1, elapsed, &err) {
+            Err(TransactionRetryError::DeadlineExceeded { stage, .. }) => {
+                assert_eq!(stage, RetryStage::Transaction)
+            }
+            _ => panic!(),
+        };
+        assert!(RETRY_METRICS.retries_exhausted() >= exhausted + 2);
+    }
+}
@@ -1,15 +1,22 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
 };
 
-use crate::models::GenericResponse;
+use mongodb::error::Error as MongoError;
+
+use crate::{constants::CONFLICT_RETRY_AFTER_SECS, models::GenericResponse};
+
+use super::db_retry::TransactionRetryError;
 
 #[derive(Debug)]
 pub enum AppError {
     BadRequest(String),
     NotFound(String),
     Auth(String),
+    Forbidden(String),
+    /// Concurrent modification, the request can be retried
+    Conflict(String),
     AnyError(anyhow::Error),
 }
 
@@ -17,6 +24,54 @@ impl AppError {
     pub fn unknown_error() -> Self {
         Self::AnyError(anyhow::anyhow!("Unknown error"))
     }
+
+    /// Convert an error returned from a database transaction back to AppError.
+    /// Errors raised as AppError inside the transaction keep their original variant.
+    pub fn from_db_error(err: MongoError) -> Self {
+        match err.get_custom::<AppError>() {
+            Some(Self::BadRequest(msg)) => Self::BadRequest(msg.to_owned()),
+            Some(Self::NotFound(msg)) => Self::NotFound(msg.to_owned()),
+            Some(Self::Auth(msg)) => Self::Auth(msg.to_owned()),
+            Some(Self::Forbidden(msg)) => Self::Forbidden(msg.to_owned()),
+            Some(Self::Conflict(msg)) => Self::Conflict(msg.to_owned()),
+            Some(Self::AnyError(e)) => Self::AnyError(anyhow::anyhow!(e.to_string())),
+            // transaction kept conflicting with other writes till retries were exhausted
+            None => match err.get_custom::<TransactionRetryError>() {
+                Some(e) => Self::Conflict(e.to_string()),
+                None => Self::AnyError(err.into()),
+            },
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Conflict(_))
+    }
+
+    /// Message of the error if the operation is rejected, e.g. by a validation or a wallet
+    /// freeze, so that retrying it as it is fails again
+    pub fn rejection(&self) -> Option<&str> {
+        match self {
+            Self::BadRequest(msg)
+            | Self::NotFound(msg)
+            | Self::Auth(msg)
+            | Self::Forbidden(msg) => Some(msg),
+            Self::Conflict(_) | Self::AnyError(_) => None,
+        }
+    }
+}
+
+impl From<AppError> for MongoError {
+    fn from(err: AppError) -> Self {
+        match err {
+            // database error wrapped with `?` is passed as is, so that it's error labels
+            // and an AppError raised deeper inside the transaction are not lost
+            AppError::AnyError(e) => match e.downcast::<MongoError>() {
+                Ok(e) => e,
+                Err(e) => MongoError::custom(AppError::AnyError(e)),
+            },
+            err => MongoError::custom(err),
+        }
+    }
 }
 
 impl<E: Into<anyhow::Error>> From<E> for AppError {
@@ -43,6 +98,17 @@ impl IntoResponse for AppError {
                 let res = GenericResponse::json_response(false, msg.as_str());
                 (StatusCode::UNAUTHORIZED, res).into_response()
             }
+            Self::Forbidden(msg) => {
+                tracing::debug!("Forbidden: {}", msg);
+                let res = GenericResponse::json_response(false, msg.as_str());
+                (StatusCode::FORBIDDEN, res).into_response()
+            }
+            Self::Conflict(msg) => {
+                tracing::debug!("Conflict: {}", msg);
+                let res = GenericResponse::json_response(false, msg.as_str());
+                let retry_after = [(header::RETRY_AFTER, CONFLICT_RETRY_AFTER_SECS.to_string())];
+                (StatusCode::CONFLICT, retry_after, res).into_response()
+            }
             Self::AnyError(err) => {
                 let msg = format!("Something went wrong: {err}");
                 tracing::debug!("{msg}");
@@ -56,6 +122,8 @@ impl IntoResponse for AppError {
 #[cfg(test)]
 mod tests {
 
+    use crate::config::db_retry::RetryStage;
+
     use super::*;
 
     async fn check_response(status_code: StatusCode, msg: &str, app_error: AppError) {
@@ -92,6 +160,23 @@ mod tests {
         check_response(StatusCode::UNAUTHORIZED, msg, app_error).await;
     }
 
+    #[tokio::test]
+    async fn test_app_error_forbidden() {
+        let msg = "Forbidden error message";
+        let app_error = AppError::Forbidden(msg.to_owned());
+        check_response(StatusCode::FORBIDDEN, msg, app_error).await;
+    }
+
+    #[tokio::test]
+    async fn test_app_error_conflict() {
+        let msg = "Conflict error message";
+        let res = AppError::Conflict(msg.to_owned()).into_response();
+        let retry_after = res.headers().get(header::RETRY_AFTER).unwrap();
+        assert_eq!(retry_after, &CONFLICT_RETRY_AFTER_SECS.to_string());
+        let app_error = AppError::Conflict(msg.to_owned());
+        check_response(StatusCode::CONFLICT, msg, app_error).await;
+    }
+
     #[tokio::test]
     async fn test_app_error_any() {
         let msg = "anyhow error message";
@@ -101,6 +186,53 @@ mod tests {
         check_response(StatusCode::INTERNAL_SERVER_ERROR, &msg, app_error).await;
     }
 
+    #[test]
+    fn test_app_error_rejection() {
+        let err = AppError::Forbidden("Wallet credits are frozen".into());
+        assert_eq!(err.rejection(), Some("Wallet credits are frozen"));
+        assert_eq!(AppError::Conflict("conflict".into()).rejection(), None);
+        assert_eq!(AppError::unknown_error().rejection(), None);
+    }
+
+    #[test]
+    fn test_app_error_from_db_error() {
+        let err: MongoError = AppError::BadRequest("bad request".into()).into();
+        match AppError::from_db_error(err) {
+            AppError::BadRequest(msg) => assert_eq!(msg, "bad request"),
+            _ => panic!(),
+        };
+        let err: MongoError = AppError::NotFound("not found".into()).into();
+        match AppError::from_db_error(err) {
+            AppError::NotFound(msg) => assert_eq!(msg, "not found"),
+            _ => panic!(),
+        };
+        let err = MongoError::custom("some error");
+        match AppError::from_db_error(err) {
+            AppError::AnyError(_) => {}
+            _ => panic!(),
+        };
+        // retries exhausted in the transaction
+        let retry_err = TransactionRetryError::AttemptsExhausted {
+            stage: RetryStage::Commit,
+            attempts: 5,
+            last_error: "write conflict".into(),
+        };
+        match AppError::from_db_error(MongoError::custom(retry_err.clone())) {
+            AppError::Conflict(msg) => assert_eq!(msg, retry_err.to_string()),
+            _ => panic!(),
+        };
+        let err: MongoError = AppError::Conflict("conflict".into()).into();
+        let app_error = AppError::from_db_error(err);
+        assert!(app_error.is_retryable());
+        // AppError nested in a database error converted with `?`
+        let err: MongoError = AppError::Forbidden("frozen".into()).into();
+        let err: MongoError = AppError::from(err).into();
+        match AppError::from_db_error(err) {
+            AppError::Forbidden(msg) => assert_eq!(msg, "frozen"),
+            _ => panic!(),
+        };
+    }
+
     #[tokio::test]
     async fn test_app_error_unknown_error() {
         let app_error = AppError::unknown_error();
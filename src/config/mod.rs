@@ -4,6 +4,7 @@ pub(crate) mod app_state;
 pub(crate) mod auth_middleware;
 pub(crate) mod database;
 pub(crate) mod database_session;
+pub(crate) mod db_retry;
 pub(crate) mod error_handler;
 pub(crate) mod extractor;
 pub(crate) mod jwt;
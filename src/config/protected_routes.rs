@@ -4,12 +4,14 @@ const UNPROTECTED_PATHS: [&str; 4] = ["/ping", "/tempApiGetOtp", "/tempApiGetTok
 
 const ADMIN_ONLY_PATHS: [&str; 1] = ["/admin/login"];
 
+const ADMIN_PATH_PREFIX: &str = "/admin/";
+
 pub fn is_unprotected_path(uri: &Uri) -> bool {
     UNPROTECTED_PATHS.contains(&uri.path())
 }
 
 pub fn is_admin_only_path(uri: &Uri) -> bool {
-    ADMIN_ONLY_PATHS.contains(&uri.path())
+    ADMIN_ONLY_PATHS.contains(&uri.path()) || uri.path().starts_with(ADMIN_PATH_PREFIX)
 }
 
 #[cfg(test)]
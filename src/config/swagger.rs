@@ -12,20 +12,154 @@ use utoipa::{
         crate::handlers::ping::temp_api_get_otp,
         crate::handlers::wallet::add_bal::add_bal_init_handler,
         crate::handlers::wallet::add_bal::add_bal_end_handler,
+        crate::handlers::wallet::balance::get_balance_handler,
+        crate::handlers::wallet::statement::get_statement_handler,
+        crate::handlers::wallet::withdraw::withdraw_handler,
+        crate::handlers::wallet::tax_certificate::get_tax_certificate_handler,
+        crate::handlers::user::referral::get_referral_code_handler,
+        crate::handlers::user::referral::apply_referral_code_handler,
+        crate::handlers::user::gaming_limit::get_gaming_limits_handler,
+        crate::handlers::user::gaming_limit::update_gaming_limits_handler,
+        crate::handlers::user::gaming_limit::self_exclusion_handler,
+        crate::handlers::contest::join::join_contest_handler,
+        crate::handlers::contest::join::join_private_contest_handler,
+        crate::handlers::contest::play::start_play_handler,
+        crate::handlers::contest::play::get_question_handler,
+        crate::handlers::contest::play::submit_answer_handler,
+        crate::handlers::contest::play::finish_play_handler,
+        crate::handlers::contest::leaderboard::leaderboard_handler,
+        crate::handlers::contest::list::list_public_contests_handler,
+        crate::handlers::contest::detail::contest_detail_handler,
+        crate::handlers::contest::live::live_contest_handler,
+        crate::handlers::contest::private::question_sets_handler,
+        crate::handlers::contest::private::create_private_contest_handler,
+        crate::handlers::contest::waitlist::join_waitlist_handler,
+        crate::handlers::contest::waitlist::leave_contest_handler,
+        crate::handlers::admin::special_referral::create_special_referral_handler,
+        crate::handlers::admin::special_referral::update_special_referral_handler,
+        crate::handlers::admin::special_referral::list_special_referral_handler,
+        crate::handlers::admin::special_referral::special_referral_report_handler,
+        crate::handlers::admin::wallet_statement::export_wallet_statements_handler,
+        crate::handlers::admin::wallet_freeze::freeze_wallet_handler,
+        crate::handlers::admin::wallet_freeze::unfreeze_wallet_handler,
+        crate::handlers::admin::wallet_freeze::wallet_freeze_audits_handler,
+        crate::handlers::admin::contest::create_contest_handler,
+        crate::handlers::admin::contest::update_contest_handler,
+        crate::handlers::admin::contest::list_contests_handler,
+        crate::handlers::admin::contest::delete_contest_handler,
+        crate::handlers::admin::contest::publish_contest_handler,
+        crate::handlers::admin::contest::cancel_contest_handler,
+        crate::handlers::admin::contest::prize_preview_handler,
+        crate::handlers::admin::contest_template::create_contest_template_handler,
+        crate::handlers::admin::contest_template::update_contest_template_handler,
+        crate::handlers::admin::contest_template::list_contest_templates_handler,
+        crate::handlers::admin::question_set::create_question_set_handler,
+        crate::handlers::admin::question_set::update_question_set_handler,
 
     ),
     components(
         schemas(
             crate::models::AddBalInitReq,
             crate::models::AddBalEndReq,
+            crate::models::WithdrawReq,
+            crate::models::FreezeWalletReq,
+            crate::models::UnfreezeWalletReq,
+            crate::models::ApplyReferralReq,
+            crate::models::CreateSpecialReferralReq,
+            crate::models::UpdateSpecialReferralReq,
+            crate::models::UpdateGamingLimitsReq,
+            crate::models::PeriodLimitsReq,
+            crate::models::SelfExclusionReq,
+            crate::models::CreateContestReq,
+            crate::models::UpdateContestReq,
+            crate::models::CreateContestTemplateReq,
+            crate::models::UpdateContestTemplateReq,
+            crate::models::CreateQuestionSetReq,
+            crate::models::UpdateQuestionSetReq,
+            crate::models::CreatePrivateContestReq,
+            crate::models::SubmitAnswerReq,
 
             crate::models::GenericResponse,
             crate::models::AddBalInitRes,
+            crate::models::WithdrawRes,
+            crate::models::ReferralCodeRes,
+            crate::models::WalletBalanceRes,
+            crate::models::SpecialReferralCodesRes,
+            crate::models::SpecialReferralReportRes,
+            crate::models::GamingLimitsRes,
+            crate::models::WalletFreezeAuditsRes,
+            crate::models::ContestRes,
+            crate::models::JoinContestRes,
+            crate::models::WaitlistRes,
+            crate::models::PlayTrackerRes,
+            crate::models::QuestionRes,
+            crate::models::LeaderboardRes,
+            crate::models::ContestsRes,
+            crate::models::ContestListRes,
+            crate::models::ContestDetailRes,
+            crate::models::PrizePreviewRes,
+            crate::models::ContestTemplateRes,
+            crate::models::ContestTemplatesRes,
+            crate::models::QuestionSetRes,
+            crate::models::QuestionSetsRes,
+            crate::models::PrivateContestRes,
 
             crate::models::Money,
+            crate::models::BonusExpiry,
+            crate::models::StatementFormat,
+            crate::models::TaxSummary,
             crate::models::LoginScheme,
             crate::models::User,
             crate::models::AdminUser,
+            crate::models::SpecialReferralCode,
+            crate::models::ReferralTargetSegment,
+            crate::models::ReferralRedemption,
+            crate::models::GamingLimits,
+            crate::models::PeriodLimits,
+            crate::models::Limit,
+            crate::models::PendingLimit,
+            crate::models::WalletFreeze,
+            crate::models::WalletFreezeScope,
+            crate::models::WalletFreezeAction,
+            crate::models::WalletFreezeAudit,
+            crate::models::Contest,
+            crate::models::ContestStatus,
+            crate::models::ContestStatusChange,
+            crate::models::PrizePoolType,
+            crate::models::HouseTransaction,
+            crate::models::HouseTransactionType,
+            crate::models::ContestMedia,
+            crate::models::MediaType,
+            crate::models::RankPrize,
+            crate::models::PrizeType,
+            crate::models::TieRule,
+            crate::models::PrizePayout,
+            crate::models::Question,
+            crate::models::PlayTracker,
+            crate::models::PlayTrackerStatus,
+            crate::models::WaitlistEntry,
+            crate::models::WaitlistStatus,
+            crate::models::PlayAnswer,
+            crate::models::ServedQuestion,
+            crate::models::QuestionType,
+            crate::models::QuestionMedia,
+            crate::models::QuestionView,
+            crate::models::ScoringRule,
+            crate::models::LeaderboardEntry,
+            crate::models::ParticipantCount,
+            crate::models::LeaderboardSnapshot,
+            crate::models::StatusUpdate,
+            crate::models::ContestTemplate,
+            crate::models::Recurrence,
+            crate::models::DayOfWeek,
+            crate::models::ContestVisibility,
+            crate::models::ContestMode,
+            crate::models::ContestPhase,
+            crate::models::ContestSort,
+            crate::models::SortOrder,
+            crate::models::ContestSummary,
+            crate::models::QuestionSet,
+            crate::models::QuestionSetView,
 
         )
     ),
@@ -6,6 +6,11 @@ pub const REQUEST_TIMEOUT_SECS: u64 = 3;
 pub const MONGO_MIN_POOL_SIZE: u32 = 5;
 pub const MONGO_MAX_POOL_SIZE: u32 = 10;
 pub const MONGO_CONN_TIMEOUT: u64 = 10;
+pub const DB_TXN_MAX_ATTEMPTS: u32 = 5;
+pub const DB_TXN_BASE_DELAY_MS: u64 = 50;
+pub const DB_TXN_MAX_DELAY_MS: u64 = 1000;
+pub const DB_TXN_DEADLINE_MS: u64 = 10 * 1000;
+pub const CONFLICT_RETRY_AFTER_SECS: u64 = 1;
 
 pub const DEFAULT_QUERY_LIMIT: u64 = 1000;
 pub const OTP_LENGTH: u32 = 6;
@@ -24,9 +29,47 @@ pub const AWS_REGION: &str = "ap-south-1";
 pub const AWS_BUCKET: &str = "trailsbuddy-1";
 pub const MULTIPART_BODY_LIMIT: usize = 100 * 1024 * 1024;
 pub const WITHDRAW_BAL_MIN_AMOUNT: u64 = 10;
+pub const IST_OFFSET_SECS: i32 = 5 * 60 * 60 + 30 * 60;
+pub const STATEMENT_MAX_RANGE_SECS: u64 = 366 * 24 * 60 * 60;
+pub const STATEMENT_EXPORT_PAGE_SIZE: i64 = 100;
+pub const LIMIT_RAISE_COOLING_OFF_SECS: u64 = 24 * 60 * 60;
+pub const SELF_EXCLUSION_MAX_DAYS: u64 = 5 * 365;
+pub const CONTEST_MAX_PLAYERS: u32 = 100_000;
+pub const CONTEST_MAX_ENTRIES: u32 = 10;
+pub const PRACTICE_PLAYS_DAILY_LIMIT: u64 = 3;
+pub const CONTEST_MAX_QUESTIONS: u64 = 50;
+pub const PRIVATE_CONTEST_MAX_ENTRY_FEE: u64 = 100;
+pub const PRIVATE_CONTEST_MAX_PLAYERS: u32 = 50;
+pub const PRIVATE_CONTEST_MAX_DURATION_SECS: u64 = 24 * 60 * 60;
+pub const PRIVATE_CONTEST_DEFAULT_COMMISSION_PERCENT: u32 = 10;
+pub const CONTEST_DEFAULT_COMMISSION_PERCENT: u32 = 10;
+pub const INVITE_CODE_MAX_RETRY: u32 = 5;
+pub const LEADERBOARD_DEFAULT_PAGE_SIZE: u64 = 20;
+pub const LEADERBOARD_MAX_PAGE_SIZE: u64 = 100;
+pub const CONTEST_LIST_DEFAULT_PAGE_SIZE: u64 = 20;
+pub const LEADERBOARD_CACHE_TTL_SECS: u64 = 10;
+pub const LIVE_LEADERBOARD_SIZE: usize = 10;
+pub const CONTEST_EVENTS_CAPACITY: usize = 256;
+pub const FINANCIAL_YEAR_START_MONTH: u32 = 4;
+pub const TDS_DEFAULT_RATE_PERCENT: u64 = 30;
+pub const TDS_DEFAULT_THRESHOLD: u64 = 0;
 pub const FINALIZE_CONTEST_JOB_INTERVAL: u64 = 5 * 60;
+pub const FINALIZE_CONTEST_JOB_FETCH_LIMIT: i64 = 10;
+pub const CANCEL_CONTEST_JOB_INTERVAL: u64 = 60;
+pub const CONTEST_STATUS_JOB_INTERVAL: u64 = 15;
+pub const CONTEST_STATUS_JOB_FETCH_LIMIT: i64 = 50;
+pub const WAITLIST_PROMOTING_TIMEOUT_SECS: u64 = 5 * 60;
+pub const CANCEL_CONTEST_JOB_FETCH_LIMIT: i64 = 10;
+pub const LIVE_LEADERBOARD_JOB_INTERVAL: u64 = LEADERBOARD_CACHE_TTL_SECS;
+pub const LIVE_LEADERBOARD_JOB_FETCH_LIMIT: i64 = 50;
+pub const CONTEST_TEMPLATE_JOB_INTERVAL: u64 = 60 * 60;
+pub const CONTEST_TEMPLATE_JOB_FETCH_LIMIT: i64 = 100;
+pub const CONTEST_SCHEDULE_AHEAD_SECS: u64 = 3 * 24 * 60 * 60;
 pub const NOTIFICATION_JOB_INTERVAL: u64 = 2 * 60;
 pub const CLEANUP_JOB_INTERVAL: u64 = 24 * 60 * 60;
+pub const BONUS_EXPIRY_JOB_INTERVAL: u64 = 60 * 60;
+pub const BONUS_EXPIRY_JOB_FETCH_LIMIT: i64 = 100;
+pub const BONUS_VALIDITY_SECS: u64 = 30 * 24 * 60 * 60;
 pub const USED_TOKEN_RETENTION: u64 = 10;
 pub const OTP_RETENTION: u64 = 10;
 pub const PUSH_ICON_COLOR: &str = "#EA3333";
@@ -37,6 +80,8 @@ pub const NOTI_JOB_MAX_RETRY_COUNT: u32 = 10;
 pub const NOTI_JOB_FETCH_LIMIT: i64 = 10;
 pub const REFERRER_BONUS: u64 = 100;
 pub const REFERRAL_BONUS: u64 = 100;
+pub const REFERRAL_CODE_MAX_RETRY: u32 = 5;
+pub const DUPLICATE_KEY_ERROR_CODE: i32 = 11000;
 
 #[cfg(not(test))]
 pub const DB_NAME: &str = "treatviewers2";
@@ -51,15 +96,25 @@ pub const COLL_OTP: &str = "otps";
 pub const COLL_USED_TOKENS: &str = "usedTokens";
 pub const COLL_NOTIFICATIONS: &str = "notifications";
 pub const COLL_CONTESTS: &str = "contests";
+pub const COLL_CONTEST_TEMPLATES: &str = "contestTemplates";
+pub const COLL_QUESTION_SETS: &str = "questionSets";
 pub const COLL_PLAY_TRACKERS: &str = "playTrackers";
+pub const COLL_CONTEST_WAITLIST: &str = "contestWaitlist";
+pub const COLL_HOUSE_TRANSACTIONS: &str = "houseTransactions";
+pub const COLL_HOUSE_ACCOUNT: &str = "houseAccount";
 pub const COLL_WALLETS: &str = "wallets";
 pub const COLL_WALLET_TRANSACTIONS: &str = "walletTransactions";
 pub const COLL_NOTIFICATION_REQUESTS: &str = "notificationRequests";
 pub const COLL_NOTIFICATION_CONTENTS: &str = "notificationContents";
 pub const COLL_SPECIAL_REFERRAL_CODES: &str = "specialReferralCodes";
 pub const COLL_ADMIN_USERS: &str = "adminUsers";
+pub const COLL_BONUS_LOTS: &str = "bonusLots";
+pub const COLL_GAMING_LIMITS: &str = "gamingLimits";
+pub const COLL_WALLET_FREEZE_AUDITS: &str = "walletFreezeAudits";
 
 pub const USER_ID_SEQ: &str = "USER_ID_SEQ";
+pub const HOUSE_ACCOUNT_ID: &str = "house";
 
 pub const EVENT_CREDIT_PRIZE: &str = "EVENT_CREDIT_PRIZE";
 pub const EVENT_CONTEST_CANCEL_MIN_PLAYER: &str = "EVENT_CONTEST_CANCEL_MIN_PLAYER";
+pub const EVENT_WAITLIST_PROMOTED: &str = "EVENT_WAITLIST_PROMOTED";
@@ -0,0 +1,755 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use mongodb::{
+    bson::{doc, oid::ObjectId, to_bson, to_document},
+    options::FindOptions,
+};
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody, ValidatedParams},
+    constants::*,
+    jobs::cancel_contest::refund_contest,
+    models::*,
+    utils::{get_epoch_ts, is_duplicate_key_error},
+};
+
+/// Create contest
+///
+/// Create a new contest
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/contest",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = CreateContestReq,
+    responses(
+        (status = StatusCode::OK, description = "Contest created", body = ContestRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn create_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedBody(body): ValidatedBody<CreateContestReq>,
+) -> Result<Json<ContestRes>, AppError> {
+    let mut contest = Contest::from(body);
+    contest.created_ts = Some(get_epoch_ts());
+    contest.created_by = Some(claims.id);
+    let result = state
+        .db()
+        .insert_one::<Contest>(DB_NAME, COLL_CONTESTS, &contest, None)
+        .await;
+    match result {
+        Err(e) if is_duplicate_key_error(&e) => {
+            let err = "Contest title already exists";
+            Err(AppError::BadRequest(err.into()))
+        }
+        Err(e) => Err(e.into()),
+        Ok(inserted_id) => {
+            contest.id = Some(ObjectId::parse_str(inserted_id.0)?);
+            let res = ContestRes {
+                success: true,
+                contest,
+            };
+            Ok(Json(res))
+        }
+    }
+}
+
+/// Update contest
+///
+/// Update details of a contest which no player has joined yet
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/contest/{contest_id}",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+    ),
+    security(("authorization" = [])),
+    request_body = UpdateContestReq,
+    responses(
+        (status = StatusCode::OK, description = "Contest updated", body = ContestRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn update_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+    ValidatedBody(body): ValidatedBody<UpdateContestReq>,
+) -> Result<Json<ContestRes>, AppError> {
+    let db = state.db();
+    let mut contest = get_editable_contest(&state, &contest_id, "updated").await?;
+    if contest.status != ContestStatus::Draft && body.updates_prize_table() {
+        let err = "Entry fee and prizes can not be updated after the contest is published";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    let status = to_bson(&contest.status)?;
+    contest.apply_update(body);
+    contest.updated_ts = Some(get_epoch_ts());
+    contest.updated_by = Some(claims.id);
+    state
+        .validators()
+        .validate_contest(db, state.helpers(), &contest)
+        .await?;
+    let mut update = to_document(&contest)?;
+    let keys = [
+        "_id",
+        "joinedPlayers",
+        "status",
+        "statusHistory",
+        "createdTs",
+        "createdBy",
+    ];
+    for key in keys {
+        update.remove(key);
+    }
+    // contest must still be editable at the time of update
+    let filter = doc! {"_id": contest_id, "joinedPlayers": 0, "status": status};
+    let update = doc! {"$set": update};
+    let result = db
+        .update_one(DB_NAME, COLL_CONTESTS, filter, update, None)
+        .await;
+    match result {
+        Err(e) if is_duplicate_key_error(&e) => {
+            let err = "Contest title already exists";
+            Err(AppError::BadRequest(err.into()))
+        }
+        Err(e) => Err(e.into()),
+        Ok(result) if result.matched_count == 0 => {
+            let err = "Contest can not be updated after players joined";
+            Err(AppError::BadRequest(err.into()))
+        }
+        Ok(_) => {
+            let res = ContestRes {
+                success: true,
+                contest,
+            };
+            Ok(Json(res))
+        }
+    }
+}
+
+/// List contests
+///
+/// Get the list of contests, latest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/contests",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ContestListParams,
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "List of contests", body = ContestsRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn list_contests_handler(
+    State(state): State<Arc<AppState>>,
+    ValidatedParams(params): ValidatedParams<ContestListParams>,
+) -> Result<Json<ContestsRes>, AppError> {
+    let filter = match params.status {
+        Some(status) => Some(doc! {"status": to_bson(&status)?}),
+        None => None,
+    };
+    let options = FindOptions::builder()
+        .sort(doc! {"startTs": -1})
+        .limit(DEFAULT_QUERY_LIMIT as i64)
+        .build();
+    let contests = state
+        .db()
+        .find::<Contest>(DB_NAME, COLL_CONTESTS, filter, Some(options))
+        .await?;
+    let res = ContestsRes {
+        success: true,
+        contests,
+    };
+    Ok(Json(res))
+}
+
+/// Delete contest
+///
+/// Delete a contest which no player has joined yet
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/contest/{contest_id}",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest deleted", body = GenericResponse),
+    ),
+    tag = "Admin API"
+)]
+pub async fn delete_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Path(contest_id): Path<ObjectId>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let contest = get_editable_contest(&state, &contest_id, "deleted").await?;
+    let filter = doc! {
+        "_id": contest_id,
+        "joinedPlayers": 0,
+        "status": to_bson(&contest.status)?
+    };
+    let deleted_count = state
+        .db()
+        .delete_many(DB_NAME, COLL_CONTESTS, filter, None)
+        .await?;
+    if deleted_count == 0 {
+        let err = "Contest can not be deleted after players joined";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    Ok(GenericResponse::json_response(
+        true,
+        "Contest deleted successfully",
+    ))
+}
+
+/// Publish contest
+///
+/// Publish a draft contest to open it for joining
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/contest/{contest_id}/publish",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest published", body = ContestRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn publish_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+) -> Result<Json<ContestRes>, AppError> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    let contest = contest_helpers
+        .get_contest(db, &contest_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )))?;
+    if contest.status != ContestStatus::Draft {
+        return Err(AppError::BadRequest("Contest is already published".into()));
+    }
+    state
+        .validators()
+        .validate_contest(db, state.helpers(), &contest)
+        .await?;
+    let contest = contest_helpers
+        .transition_status(
+            db,
+            &contest_id,
+            &ContestStatus::Draft,
+            &ContestStatus::Published,
+            Some(claims.id),
+            None,
+        )
+        .await?
+        .ok_or(AppError::BadRequest("Contest is already published".into()))?;
+    let res = ContestRes {
+        success: true,
+        contest,
+    };
+    Ok(Json(res))
+}
+
+/// Prize preview
+///
+/// Get the payout table of the contest if the given number of players participate
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/contest/{contest_id}/prizePreview",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+        PrizePreviewParams,
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Payout table of the contest", body = PrizePreviewRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn prize_preview_handler(
+    State(state): State<Arc<AppState>>,
+    Path(contest_id): Path<ObjectId>,
+    ValidatedParams(params): ValidatedParams<PrizePreviewParams>,
+) -> Result<Json<PrizePreviewRes>, AppError> {
+    let contest = state
+        .helpers()
+        .contest_helpers()
+        .get_contest(state.db(), &contest_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )))?;
+    let participants = params.participants.unwrap_or(contest.max_players);
+    let payouts = contest
+        .prize_structure_for(participants)
+        .preview(participants);
+    let total_payout = payouts
+        .iter()
+        .map(|p| (p.rank_to - p.rank_from + 1) as u64 * p.amount)
+        .sum();
+    let res = PrizePreviewRes {
+        success: true,
+        participants,
+        prize_pool: contest.prize_pool_for(participants),
+        total_payout,
+        payouts,
+    };
+    Ok(Json(res))
+}
+
+/// Cancel contest
+///
+/// Cancel a contest short of the minimum players and refund the entry fees of the players
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/contest/{contest_id}/cancel",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest cancelled", body = GenericResponse),
+    ),
+    tag = "Admin API"
+)]
+pub async fn cancel_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let contest_helpers = state.helpers().contest_helpers();
+    let contest = contest_helpers
+        .get_contest(state.db(), &contest_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )))?;
+    if contest.status != ContestStatus::Published {
+        let err = "Only published contests can be cancelled";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    if !contest.is_under_subscribed() {
+        let err = "Contest has the minimum players, can not be cancelled";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    let cancelled = contest_helpers
+        .cancel_contest(state.db(), &contest_id, Some(claims.id))
+        .await?;
+    if !cancelled {
+        return Err(AppError::BadRequest("Contest can not be cancelled".into()));
+    }
+    state.publish_contest_event(contest_id, ContestUpdate::status(ContestStatus::Cancelled));
+    // pending refunds are retried by the cancel contest job
+    if let Err(e) = refund_contest(state.clone(), contest).await {
+        tracing::error!("Not able to refund contest {}: {:?}", contest_id, e);
+    }
+    Ok(GenericResponse::json_response(
+        true,
+        "Contest cancelled successfully",
+    ))
+}
+
+/// Contest can be edited only if it's not started and no player has joined yet.
+/// Entry fee and prizes can be edited only in draft.
+async fn get_editable_contest(
+    state: &Arc<AppState>,
+    contest_id: &ObjectId,
+    action: &str,
+) -> Result<Contest, AppError> {
+    let contest = state
+        .helpers()
+        .contest_helpers()
+        .get_contest(state.db(), contest_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )))?;
+    if !contest.status.is_editable() || contest.has_started(get_epoch_ts()) {
+        let err = format!("Contest can not be {action} after it is started");
+        return Err(AppError::BadRequest(err));
+    }
+    if contest.joined_players > 0 {
+        let err = format!("Contest can not be {action} after players joined");
+        return Err(AppError::BadRequest(err));
+    }
+    Ok(contest)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+    use serde_json::json;
+
+    use crate::{
+        config::{build_app_routes, database::InsertedId},
+        helpers::Helpers,
+        import_double,
+        utils::test_helper::{
+            build_delete_request, build_get_request, build_post_request, mock_admin_token,
+            oneshot_request,
+        },
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_contest_handler() {
+        let token = "DUMMY_TOKEN";
+        let ts = get_epoch_ts();
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_validators()
+            .expect_validate_contest()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(|_: &Helpers| true),
+                function(|contest: &Contest| contest.title == "Trailer quiz"),
+            )
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_insert_one::<Contest>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTESTS),
+                function(|contest: &Contest| {
+                    contest.created_by == Some(1) && contest.status == ContestStatus::Draft
+                }),
+                function(Option::is_none),
+            )
+            .returning(move |_, _, _, _| Ok(InsertedId(contest_id.to_hex())));
+        let app = build_app_routes(Arc::new(state));
+        let body = json!({
+            "title": "Trailer quiz",
+            "category": "Movies",
+            "banner": "https://example.com/banner.png",
+            "entryFee": 10,
+            "bonusUsagePercent": 20,
+            "prizePool": 100,
+            "prizes": [{"rankFrom": 1, "rankTo": 1, "amount": 100}],
+            "minPlayers": 2,
+            "maxPlayers": 20,
+            "startTs": ts + 100,
+            "endTs": ts + 200,
+            "media": {"mediaType": "movie", "name": "Movie 1"},
+            "questions": [{"question": "Question 1", "options": ["A", "B"], "answer": 0}]
+        });
+        let req = build_post_request("/api/v1/admin/contest", &body.to_string(), Some(token));
+        let res = oneshot_request::<ContestRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.contest.id, Some(contest_id));
+    }
+
+    #[tokio::test]
+    async fn test_update_contest_handler_after_players_joined() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(|_, _| {
+                let contest = Contest {
+                    joined_players: 1,
+                    start_ts: get_epoch_ts() + 100,
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        state.get_mut_validators().expect_validate_contest().never();
+        state.get_mut_db().expect_update_one().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/contest/{contest_id}");
+        let body = json!({"title": "New title"});
+        let req = build_post_request(&path, &body.to_string(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(!res.success);
+        assert_eq!(
+            res.message,
+            "Contest can not be updated after players joined"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_contests_handler() {
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_db()
+            .expect_find::<Contest>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTESTS),
+                eq(Some(doc! {"status": "published"})),
+                function(|options: &Option<FindOptions>| options.is_some()),
+            )
+            .returning(|_, _, _, _| Ok(vec![Contest::default(), Contest::default()]));
+        let app = build_app_routes(Arc::new(state));
+        let req = build_get_request("/api/v1/admin/contests?status=published", Some(token));
+        let res = oneshot_request::<ContestsRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.contests.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_contest_handler() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .once()
+            .returning(|_, _| {
+                let contest = Contest {
+                    start_ts: get_epoch_ts() + 100,
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        state
+            .get_mut_db()
+            .expect_delete_many()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTESTS),
+                eq(doc! {"_id": contest_id, "joinedPlayers": 0, "status": "draft"}),
+                function(Option::is_none),
+            )
+            .returning(|_, _, _, _| Ok(1));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/contest/{contest_id}");
+        let req = build_delete_request(&path, Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.message, "Contest deleted successfully");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_contest_handler() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| {
+                let contest = Contest {
+                    id: Some(contest_id),
+                    min_players: 10,
+                    joined_players: 1,
+                    status: ContestStatus::Published,
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_cancel_contest()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id), eq(Some(1)))
+            .returning(|_, _, _| Ok(true));
+        contest_helpers
+            .expect_get_unrefunded_play_trackers()
+            .once()
+            .returning(move |_, _| Ok(vec![PlayTracker::new(contest_id, 7, Money::new(40, 10))]));
+        contest_helpers
+            .expect_mark_contest_refunded()
+            .once()
+            .returning(|_, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/contest/{contest_id}/cancel");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_contest_handler_min_players_joined() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(|_, _| {
+                let contest = Contest {
+                    min_players: 2,
+                    joined_players: 2,
+                    status: ContestStatus::Published,
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        contest_helpers.expect_cancel_contest().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/contest/{contest_id}/cancel");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(
+            res.message,
+            "Contest has the minimum players, can not be cancelled"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prize_preview_handler() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(|_, _| {
+                let prizes = vec![
+                    RankPrize {
+                        rank_from: 1,
+                        rank_to: 1,
+                        prize_type: PrizeType::Percent,
+                        amount: 50,
+                    },
+                    RankPrize {
+                        rank_from: 2,
+                        rank_to: 10,
+                        prize_type: PrizeType::Fixed,
+                        amount: 20,
+                    },
+                ];
+                let contest = Contest {
+                    prize_pool: 1000,
+                    prizes,
+                    max_players: 100,
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/contest/{contest_id}/prizePreview?participants=5");
+        let req = build_get_request(&path, Some(token));
+        let res = oneshot_request::<PrizePreviewRes>(app, req, Some(StatusCode::OK)).await;
+        assert_eq!(res.participants, 5);
+        assert_eq!(res.payouts.len(), 2);
+        assert_eq!(res.payouts[1].rank_to, 5);
+        assert_eq!(res.total_payout, 580);
+    }
+
+    #[tokio::test]
+    async fn test_update_contest_handler_prizes_after_published() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .once()
+            .returning(|_, _| {
+                let contest = Contest {
+                    start_ts: get_epoch_ts() + 100,
+                    status: ContestStatus::Published,
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        state.get_mut_db().expect_update_one().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/contest/{contest_id}");
+        let body = json!({"prizePool": 500});
+        let req = build_post_request(&path, &body.to_string(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(
+            res.message,
+            "Entry fee and prizes can not be updated after the contest is published"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_publish_contest_handler() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(|_, _| Ok(Some(Contest::default())));
+        contest_helpers
+            .expect_transition_status()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(ContestStatus::Draft),
+                eq(ContestStatus::Published),
+                eq(Some(1)),
+                eq(None),
+            )
+            .returning(|_, _, _, to, _, _| {
+                let contest = Contest {
+                    status: to.clone(),
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        state
+            .get_mut_validators()
+            .expect_validate_contest()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/contest/{contest_id}/publish");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<ContestRes>(app, req, Some(StatusCode::OK)).await;
+        assert_eq!(res.contest.status, ContestStatus::Published);
+    }
+}
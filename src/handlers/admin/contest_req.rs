@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+
+use crate::{
+    config::{AppError, AppState},
+    models::{Contest, CreateContestReq},
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for CreateContestReq {
+    async fn validate_extra(
+        &self,
+        state: Arc<AppState>,
+        _user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        let contest = Contest::from(self.clone());
+        state
+            .validators()
+            .validate_contest(state.db(), state.helpers(), &contest)
+            .await
+    }
+}
@@ -0,0 +1,341 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use mongodb::{
+    bson::{doc, oid::ObjectId, to_document},
+    options::FindOptions,
+};
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody},
+    constants::*,
+    models::*,
+    utils::{get_epoch_ts, is_duplicate_key_error},
+};
+
+/// Create contest template
+///
+/// Create a template to schedule the contests as per the recurrence
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/contestTemplate",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = CreateContestTemplateReq,
+    responses(
+        (status = StatusCode::OK, description = "Contest template created", body = ContestTemplateRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn create_contest_template_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedBody(body): ValidatedBody<CreateContestTemplateReq>,
+) -> Result<Json<ContestTemplateRes>, AppError> {
+    let mut template = ContestTemplate::from(body);
+    template.created_ts = Some(get_epoch_ts());
+    template.created_by = Some(claims.id);
+    let result = state
+        .db()
+        .insert_one::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, &template, None)
+        .await;
+    match result {
+        Err(e) if is_duplicate_key_error(&e) => Err(duplicate_title_error()),
+        Err(e) => Err(e.into()),
+        Ok(inserted_id) => {
+            template.id = Some(ObjectId::parse_str(inserted_id.0)?);
+            let res = ContestTemplateRes {
+                success: true,
+                template,
+            };
+            Ok(Json(res))
+        }
+    }
+}
+
+/// Update contest template
+///
+/// Update or pause a contest template, contests already created from it are not changed
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/contestTemplate/{template_id}",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("template_id" = String, Path, description = "contest template id"),
+    ),
+    security(("authorization" = [])),
+    request_body = UpdateContestTemplateReq,
+    responses(
+        (status = StatusCode::OK, description = "Contest template updated", body = ContestTemplateRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn update_contest_template_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(template_id): Path<ObjectId>,
+    ValidatedBody(body): ValidatedBody<UpdateContestTemplateReq>,
+) -> Result<Json<ContestTemplateRes>, AppError> {
+    let db = state.db();
+    let mut template = state
+        .helpers()
+        .contest_helpers()
+        .get_contest_template(db, &template_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest template {template_id} not found"
+        )))?;
+    template.apply_update(body);
+    template.updated_ts = Some(get_epoch_ts());
+    template.updated_by = Some(claims.id);
+    state
+        .validators()
+        .validate_contest_template(db, state.helpers(), &template)
+        .await?;
+    let mut update = to_document(&template)?;
+    // scheduled time is updated only by the scheduler
+    for key in ["_id", "scheduledTill", "createdTs", "createdBy"] {
+        update.remove(key);
+    }
+    let filter = doc! {"_id": template_id};
+    let update = doc! {"$set": update};
+    let result = db
+        .update_one(DB_NAME, COLL_CONTEST_TEMPLATES, filter, update, None)
+        .await;
+    match result {
+        Err(e) if is_duplicate_key_error(&e) => Err(duplicate_title_error()),
+        Err(e) => Err(e.into()),
+        Ok(_) => {
+            let res = ContestTemplateRes {
+                success: true,
+                template,
+            };
+            Ok(Json(res))
+        }
+    }
+}
+
+/// List contest templates
+///
+/// Get the list of contest templates, latest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/contestTemplates",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "List of contest templates", body = ContestTemplatesRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn list_contest_templates_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ContestTemplatesRes>, AppError> {
+    let options = FindOptions::builder()
+        .sort(doc! {"_id": -1})
+        .limit(DEFAULT_QUERY_LIMIT as i64)
+        .build();
+    let templates = state
+        .db()
+        .find::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, None, Some(options))
+        .await?;
+    let res = ContestTemplatesRes {
+        success: true,
+        templates,
+    };
+    Ok(Json(res))
+}
+
+fn duplicate_title_error() -> AppError {
+    AppError::BadRequest("Contest template title already exists".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+    use mongodb::bson::Document;
+    use serde_json::json;
+
+    use crate::{
+        config::{
+            build_app_routes,
+            database::{InsertedId, UpdateResult},
+        },
+        helpers::Helpers,
+        import_double,
+        utils::test_helper::{
+            build_get_request, build_post_request, mock_admin_token, oneshot_request,
+        },
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_contest_template_handler() {
+        let token = "DUMMY_TOKEN";
+        let template_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_validators()
+            .expect_validate_contest_template()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(|_: &Helpers| true),
+                function(|template: &ContestTemplate| template.recurrence.days.len() == 2),
+            )
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_insert_one::<ContestTemplate>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTEST_TEMPLATES),
+                function(|template: &ContestTemplate| {
+                    template.created_by == Some(1) && !template.paused
+                }),
+                function(Option::is_none),
+            )
+            .returning(move |_, _, _, _| Ok(InsertedId(template_id.to_hex())));
+        let app = build_app_routes(Arc::new(state));
+        let body = json!({
+            "title": "Weekend trivia",
+            "category": "Movies",
+            "banner": "https://example.com/banner.png",
+            "entryFee": 10,
+            "bonusUsagePercent": 20,
+            "prizePool": 100,
+            "prizes": [{"rankFrom": 1, "rankTo": 1, "amount": 100}],
+            "minPlayers": 2,
+            "maxPlayers": 20,
+            "durationSecs": 1800,
+            "media": {"mediaType": "movie", "name": "Movie 1"},
+            "questions": [{"question": "Question 1", "options": ["A", "B"], "answer": 0}],
+            "recurrence": {"days": ["saturday", "sunday"], "hour": 21, "minute": 0}
+        });
+        let req = build_post_request(
+            "/api/v1/admin/contestTemplate",
+            &body.to_string(),
+            Some(token),
+        );
+        let res = oneshot_request::<ContestTemplateRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.template.id, Some(template_id));
+    }
+
+    #[tokio::test]
+    async fn test_create_contest_template_handler_invalid_recurrence() {
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_validators()
+            .expect_validate_contest_template()
+            .never();
+        let app = build_app_routes(Arc::new(state));
+        let body = json!({
+            "title": "Daily trivia",
+            "category": "Movies",
+            "banner": "https://example.com/banner.png",
+            "entryFee": 10,
+            "bonusUsagePercent": 20,
+            "prizePool": 100,
+            "prizes": [],
+            "minPlayers": 2,
+            "maxPlayers": 20,
+            "durationSecs": 1800,
+            "media": {"mediaType": "movie", "name": "Movie 1"},
+            "questions": [{"question": "Question 1", "options": ["A", "B"], "answer": 0}],
+            "recurrence": {"hour": 24, "minute": 0}
+        });
+        let req = build_post_request(
+            "/api/v1/admin/contestTemplate",
+            &body.to_string(),
+            Some(token),
+        );
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(!res.success);
+    }
+
+    #[tokio::test]
+    async fn test_update_contest_template_handler_pause() {
+        let token = "DUMMY_TOKEN";
+        let template_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest_template()
+            .once()
+            .with(function(|_: &DbClient| true), eq(template_id))
+            .returning(move |_, _| {
+                let template = ContestTemplate {
+                    id: Some(template_id),
+                    title: "Daily trivia".into(),
+                    scheduled_till: Some(get_epoch_ts() + 100),
+                    ..Default::default()
+                };
+                Ok(Some(template))
+            });
+        state
+            .get_mut_validators()
+            .expect_validate_contest_template()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_update_one()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTEST_TEMPLATES),
+                eq(doc! {"_id": template_id}),
+                function(|update: &Document| {
+                    let set = update.get_document("$set").unwrap();
+                    set.get_bool("paused") == Ok(true)
+                        && set.get_i64("updatedBy") == Ok(1)
+                        && !set.contains_key("scheduledTill")
+                }),
+                function(Option::is_none),
+            )
+            .returning(|_, _, _, _, _| Ok(UpdateResult::new(1, 1, None)));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/contestTemplate/{template_id}");
+        let body = json!({"paused": true});
+        let req = build_post_request(&path, &body.to_string(), Some(token));
+        let res = oneshot_request::<ContestTemplateRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert!(res.template.paused);
+    }
+
+    #[tokio::test]
+    async fn test_list_contest_templates_handler() {
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_db()
+            .expect_find::<ContestTemplate>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTEST_TEMPLATES),
+                eq(None),
+                function(|_: &Option<FindOptions>| true),
+            )
+            .returning(|_, _, _, _| Ok(vec![ContestTemplate::default()]));
+        let app = build_app_routes(Arc::new(state));
+        let req = build_get_request("/api/v1/admin/contestTemplates", Some(token));
+        let res = oneshot_request::<ContestTemplatesRes>(app, req, Some(StatusCode::OK)).await;
+        assert_eq!(res.templates.len(), 1);
+    }
+}
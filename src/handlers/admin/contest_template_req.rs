@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+
+use crate::{
+    config::{AppError, AppState},
+    models::{ContestTemplate, CreateContestTemplateReq},
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for CreateContestTemplateReq {
+    async fn validate_extra(
+        &self,
+        state: Arc<AppState>,
+        _user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        let template = ContestTemplate::from(self.clone());
+        state
+            .validators()
+            .validate_contest_template(state.db(), state.helpers(), &template)
+            .await
+    }
+}
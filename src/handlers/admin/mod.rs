@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    routing::{get, post},
+    Router,
+};
+
+use crate::config::AppState;
+
+pub(crate) mod contest;
+mod contest_req;
+pub(crate) mod contest_template;
+mod contest_template_req;
+pub(crate) mod question_set;
+mod question_set_req;
+pub(crate) mod special_referral;
+mod special_referral_req;
+pub(crate) mod wallet_freeze;
+mod wallet_freeze_req;
+pub(crate) mod wallet_statement;
+
+use contest::*;
+use contest_template::*;
+use question_set::*;
+use special_referral::*;
+use wallet_freeze::*;
+use wallet_statement::*;
+
+pub fn admin_routes() -> Router<Arc<AppState>, Body> {
+    Router::new()
+        .route(
+            "/specialReferralCode",
+            post(create_special_referral_handler),
+        )
+        .route(
+            "/specialReferralCode/:referral_code",
+            post(update_special_referral_handler),
+        )
+        .route(
+            "/specialReferralCode/:referral_code/report",
+            get(special_referral_report_handler),
+        )
+        .route("/specialReferralCodes", get(list_special_referral_handler))
+        .route("/walletStatements", get(export_wallet_statements_handler))
+        .route("/wallet/:user_id/freeze", post(freeze_wallet_handler))
+        .route("/wallet/:user_id/unfreeze", post(unfreeze_wallet_handler))
+        .route(
+            "/wallet/:user_id/freezeAudits",
+            get(wallet_freeze_audits_handler),
+        )
+        .route("/contest", post(create_contest_handler))
+        .route(
+            "/contest/:contest_id",
+            post(update_contest_handler).delete(delete_contest_handler),
+        )
+        .route(
+            "/contest/:contest_id/publish",
+            post(publish_contest_handler),
+        )
+        .route("/contest/:contest_id/cancel", post(cancel_contest_handler))
+        .route(
+            "/contest/:contest_id/prizePreview",
+            get(prize_preview_handler),
+        )
+        .route("/contests", get(list_contests_handler))
+        .route("/contestTemplate", post(create_contest_template_handler))
+        .route(
+            "/contestTemplate/:template_id",
+            post(update_contest_template_handler),
+        )
+        .route("/contestTemplates", get(list_contest_templates_handler))
+        .route("/questionSet", post(create_question_set_handler))
+        .route(
+            "/questionSet/:question_set_id",
+            post(update_question_set_handler),
+        )
+}
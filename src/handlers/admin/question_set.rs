@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use mongodb::bson::{doc, oid::ObjectId};
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody},
+    constants::*,
+    models::*,
+    utils::{get_epoch_ts, is_duplicate_key_error},
+};
+
+/// Create question set
+///
+/// Create a question set for the private contests, users can use it only after approval
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/questionSet",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = CreateQuestionSetReq,
+    responses(
+        (status = StatusCode::OK, description = "Question set created", body = QuestionSetRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn create_question_set_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedBody(body): ValidatedBody<CreateQuestionSetReq>,
+) -> Result<Json<QuestionSetRes>, AppError> {
+    let mut question_set = QuestionSet::from(body);
+    question_set.created_ts = Some(get_epoch_ts());
+    question_set.created_by = Some(claims.id);
+    let result = state
+        .db()
+        .insert_one::<QuestionSet>(DB_NAME, COLL_QUESTION_SETS, &question_set, None)
+        .await;
+    match result {
+        Err(e) if is_duplicate_key_error(&e) => Err(AppError::BadRequest(
+            "Question set name already exists".into(),
+        )),
+        Err(e) => Err(e.into()),
+        Ok(inserted_id) => {
+            question_set.id = Some(ObjectId::parse_str(inserted_id.0)?);
+            let res = QuestionSetRes {
+                success: true,
+                question_set,
+            };
+            Ok(Json(res))
+        }
+    }
+}
+
+/// Update question set
+///
+/// Approve or withdraw a question set, private contests already created from it are not changed
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/questionSet/{question_set_id}",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("question_set_id" = String, Path, description = "question set id"),
+    ),
+    security(("authorization" = [])),
+    request_body = UpdateQuestionSetReq,
+    responses(
+        (status = StatusCode::OK, description = "Question set updated", body = QuestionSetRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn update_question_set_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(question_set_id): Path<ObjectId>,
+    ValidatedBody(body): ValidatedBody<UpdateQuestionSetReq>,
+) -> Result<Json<QuestionSetRes>, AppError> {
+    let db = state.db();
+    let mut question_set = state
+        .helpers()
+        .contest_helpers()
+        .get_question_set(db, &question_set_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Question set {question_set_id} not found"
+        )))?;
+    let ts = get_epoch_ts();
+    let filter = doc! {"_id": question_set_id};
+    let update = doc! {
+        "$set": {
+            "approved": body.approved,
+            "updatedTs": ts as i64,
+            "updatedBy": claims.id,
+        }
+    };
+    db.update_one(DB_NAME, COLL_QUESTION_SETS, filter, update, None)
+        .await?;
+    question_set.approved = body.approved;
+    question_set.updated_ts = Some(ts);
+    question_set.updated_by = Some(claims.id);
+    let res = QuestionSetRes {
+        success: true,
+        question_set,
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+    use mongodb::bson::Document;
+    use serde_json::json;
+
+    use crate::{
+        config::{
+            build_app_routes,
+            database::{InsertedId, UpdateResult},
+        },
+        helpers::Helpers,
+        import_double,
+        utils::test_helper::{build_post_request, mock_admin_token, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_question_set_handler() {
+        let token = "DUMMY_TOKEN";
+        let question_set_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_validators()
+            .expect_validate_question_set()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(|_: &Helpers| true),
+                function(|question_set: &QuestionSet| question_set.questions.len() == 1),
+            )
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_insert_one::<QuestionSet>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_QUESTION_SETS),
+                function(|question_set: &QuestionSet| {
+                    question_set.created_by == Some(1) && !question_set.approved
+                }),
+                function(Option::is_none),
+            )
+            .returning(move |_, _, _, _| Ok(InsertedId(question_set_id.to_hex())));
+        let app = build_app_routes(Arc::new(state));
+        let body = json!({
+            "name": "Movie trivia",
+            "category": "Movies",
+            "media": {"mediaType": "movie", "name": "Movie 1"},
+            "questions": [{"question": "Question 1", "options": ["A", "B"], "answer": 0}]
+        });
+        let req = build_post_request("/api/v1/admin/questionSet", &body.to_string(), Some(token));
+        let res = oneshot_request::<QuestionSetRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.question_set.id, Some(question_set_id));
+    }
+
+    #[tokio::test]
+    async fn test_update_question_set_handler_approve() {
+        let token = "DUMMY_TOKEN";
+        let question_set_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_question_set()
+            .once()
+            .with(function(|_: &DbClient| true), eq(question_set_id))
+            .returning(move |_, _| {
+                let question_set = QuestionSet {
+                    id: Some(question_set_id),
+                    name: "Movie trivia".into(),
+                    ..Default::default()
+                };
+                Ok(Some(question_set))
+            });
+        state
+            .get_mut_db()
+            .expect_update_one()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_QUESTION_SETS),
+                eq(doc! {"_id": question_set_id}),
+                function(|update: &Document| {
+                    let set = update.get_document("$set").unwrap();
+                    set.get_bool("approved") == Ok(true) && set.get_i32("updatedBy") == Ok(1)
+                }),
+                function(Option::is_none),
+            )
+            .returning(|_, _, _, _, _| Ok(UpdateResult::new(1, 1, None)));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/questionSet/{question_set_id}");
+        let body = json!({"approved": true});
+        let req = build_post_request(&path, &body.to_string(), Some(token));
+        let res = oneshot_request::<QuestionSetRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert!(res.question_set.approved);
+    }
+
+    #[tokio::test]
+    async fn test_update_question_set_handler_not_found() {
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        mock_admin_token(&mut state, token);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_question_set()
+            .once()
+            .returning(|_, _| Ok(None));
+        state.get_mut_db().expect_update_one().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/questionSet/{}", ObjectId::new());
+        let body = json!({"approved": true});
+        let req = build_post_request(&path, &body.to_string(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::NOT_FOUND)).await;
+        assert!(!res.success);
+    }
+}
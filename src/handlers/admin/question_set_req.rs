@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+
+use crate::{
+    config::{AppError, AppState},
+    models::{CreateQuestionSetReq, QuestionSet},
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for CreateQuestionSetReq {
+    async fn validate_extra(
+        &self,
+        state: Arc<AppState>,
+        _user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        let question_set = QuestionSet::from(self.clone());
+        state
+            .validators()
+            .validate_question_set(state.db(), state.helpers(), &question_set)
+            .await
+    }
+}
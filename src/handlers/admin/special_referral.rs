@@ -0,0 +1,376 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use mongodb::{
+    bson::{doc, to_bson, Document},
+    options::FindOptions,
+};
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody},
+    constants::*,
+    models::*,
+    utils::{get_epoch_ts, is_duplicate_key_error},
+};
+
+/// Create special referral code
+///
+/// Create a referral code for special campaigns like influencers and partners
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/specialReferralCode",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = CreateSpecialReferralReq,
+    responses(
+        (status = StatusCode::OK, description = "Special referral code created", body = GenericResponse),
+    ),
+    tag = "Admin API"
+)]
+pub async fn create_special_referral_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedBody(body): ValidatedBody<CreateSpecialReferralReq>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let ts = get_epoch_ts();
+    let special_referral_code = SpecialReferralCode {
+        referral_code: body.referral_code,
+        campaign_name: body.campaign_name,
+        bonus: body.bonus,
+        max_redemptions: body.max_redemptions,
+        valid_from: body.valid_from,
+        valid_till: body.valid_till,
+        target_segment: body.target_segment,
+        is_active: true,
+        created_ts: Some(ts),
+        created_by: Some(claims.id),
+        ..Default::default()
+    };
+    let result = state
+        .db()
+        .insert_one::<SpecialReferralCode>(
+            DB_NAME,
+            COLL_SPECIAL_REFERRAL_CODES,
+            &special_referral_code,
+            None,
+        )
+        .await;
+    match result {
+        Err(e) if is_duplicate_key_error(&e) => {
+            let err = "referralCode already exists";
+            Err(AppError::BadRequest(err.into()))
+        }
+        Err(e) => Err(e.into()),
+        Ok(_) => Ok(GenericResponse::json_response(
+            true,
+            "Special referral code created successfully",
+        )),
+    }
+}
+
+/// Update special referral code
+///
+/// Update details of a special referral code
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/specialReferralCode/{referral_code}",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("referral_code" = String, Path, description = "special referral code"),
+    ),
+    security(("authorization" = [])),
+    request_body = UpdateSpecialReferralReq,
+    responses(
+        (status = StatusCode::OK, description = "Special referral code updated", body = GenericResponse),
+    ),
+    tag = "Admin API"
+)]
+pub async fn update_special_referral_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(referral_code): Path<String>,
+    ValidatedBody(body): ValidatedBody<UpdateSpecialReferralReq>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let mut filter = doc! {"referralCode": &referral_code};
+    if body.valid_from.is_some() || body.valid_till.is_some() {
+        // validity window is checked after merging with the stored code as only
+        // one side of the window may be updated
+        let special_referral_code = state
+            .helpers()
+            .user_helpers()
+            .get_special_referral_code(state.db(), &referral_code)
+            .await?
+            .ok_or(AppError::NotFound(format!(
+                "Special referral code {referral_code} not found"
+            )))?;
+        let valid_from = body.valid_from.unwrap_or(special_referral_code.valid_from);
+        let valid_till = body.valid_till.unwrap_or(special_referral_code.valid_till);
+        if valid_till <= valid_from {
+            let err = "validTill must be greater than validFrom";
+            return Err(AppError::BadRequest(err.into()));
+        }
+        // the other side of the window must not have changed in the meantime
+        filter.insert("validFrom", special_referral_code.valid_from as i64);
+        filter.insert("validTill", special_referral_code.valid_till as i64);
+    }
+    let ts = get_epoch_ts() as i64;
+    let mut update = doc! {"updatedTs": ts, "updatedBy": claims.id};
+    if let Some(campaign_name) = body.campaign_name {
+        update.insert("campaignName", campaign_name);
+    }
+    if let Some(bonus) = body.bonus {
+        update.insert("bonus", bonus as i64);
+    }
+    if let Some(max_redemptions) = body.max_redemptions {
+        update.insert("maxRedemptions", max_redemptions);
+    }
+    if let Some(valid_from) = body.valid_from {
+        update.insert("validFrom", valid_from as i64);
+    }
+    if let Some(valid_till) = body.valid_till {
+        update.insert("validTill", valid_till as i64);
+    }
+    if let Some(target_segment) = body.target_segment {
+        update.insert("targetSegment", to_bson(&target_segment)?);
+    }
+    if let Some(is_active) = body.is_active {
+        update.insert("isActive", is_active);
+    }
+    let update = doc! {"$set": update};
+    let window_checked = filter.contains_key("validFrom");
+    let result = state
+        .db()
+        .update_one(DB_NAME, COLL_SPECIAL_REFERRAL_CODES, filter, update, None)
+        .await?;
+    if result.matched_count == 0 && window_checked {
+        let err = "Special referral code is modified, please try again";
+        return Err(AppError::Conflict(err.into()));
+    }
+    if result.matched_count == 0 {
+        let err = format!("Special referral code {referral_code} not found");
+        return Err(AppError::NotFound(err));
+    }
+    Ok(GenericResponse::json_response(
+        true,
+        "Special referral code updated successfully",
+    ))
+}
+
+/// List special referral codes
+///
+/// Get the list of all special referral codes
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/specialReferralCodes",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "List of special referral codes", body = SpecialReferralCodesRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn list_special_referral_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SpecialReferralCodesRes>, AppError> {
+    let options = FindOptions::builder()
+        .sort(doc! {"createdTs": -1})
+        .limit(DEFAULT_QUERY_LIMIT as i64)
+        .build();
+    let data = state
+        .db()
+        .find::<SpecialReferralCode>(DB_NAME, COLL_SPECIAL_REFERRAL_CODES, None, Some(options))
+        .await?;
+    let res = SpecialReferralCodesRes {
+        success: true,
+        data,
+    };
+    Ok(Json(res))
+}
+
+/// Special referral code report
+///
+/// Get the redemption report of a special referral code
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/specialReferralCode/{referral_code}/report",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("referral_code" = String, Path, description = "special referral code"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Redemption report of special referral code", body = SpecialReferralReportRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn special_referral_report_handler(
+    State(state): State<Arc<AppState>>,
+    Path(referral_code): Path<String>,
+) -> Result<Json<SpecialReferralReportRes>, AppError> {
+    let db = state.db();
+    let special_referral_code = state
+        .helpers()
+        .user_helpers()
+        .get_special_referral_code(db, &referral_code)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Special referral code {referral_code} not found"
+        )))?;
+    let filter = doc! {"usedReferralCode": &referral_code};
+    let options = FindOptions::builder()
+        .sort(doc! {"usedReferralCodeTs": 1})
+        .limit(DEFAULT_QUERY_LIMIT as i64)
+        .build();
+    let users = db
+        .find::<User>(DB_NAME, COLL_USERS, Some(filter), Some(options))
+        .await?;
+    let redemptions = users
+        .into_iter()
+        .map(ReferralRedemption::from)
+        .collect::<Vec<_>>();
+    let total_bonus = special_referral_code.bonus * special_referral_code.redemption_count as u64;
+    let res = SpecialReferralReportRes {
+        success: true,
+        referral_code: special_referral_code,
+        total_bonus,
+        redemptions,
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+    use serde_json::json;
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::test_helper::{build_get_request, build_post_request, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_special_referral_report_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let admin_id = 1;
+        let referral_code = "PARTNER50";
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(admin_id, None, true, ts as usize)));
+        state
+            .get_mut_helpers()
+            .mut_user_helpers()
+            .expect_get_special_referral_code()
+            .once()
+            .with(function(|_: &DbClient| true), eq(referral_code))
+            .returning(|_, _| {
+                let special_referral_code = SpecialReferralCode {
+                    referral_code: "PARTNER50".into(),
+                    bonus: 50,
+                    redemption_count: 2,
+                    ..Default::default()
+                };
+                Ok(Some(special_referral_code))
+            });
+        state
+            .get_mut_db()
+            .expect_find::<User>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_USERS),
+                eq(Some(doc! {"usedReferralCode": referral_code})),
+                function(|options: &Option<FindOptions>| options.is_some()),
+            )
+            .returning(|_, _, _, _| {
+                let users = (1..=2)
+                    .map(|id| User {
+                        id,
+                        used_referral_code_ts: Some(100),
+                        ..Default::default()
+                    })
+                    .collect();
+                Ok(users)
+            });
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/specialReferralCode/{referral_code}/report");
+        let req = build_get_request(&path, Some(token));
+        let res = oneshot_request::<SpecialReferralReportRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.total_bonus, 100);
+        assert_eq!(res.redemptions.len(), 2);
+        assert_eq!(res.redemptions[0].redeemed_ts, Some(100));
+    }
+
+    #[tokio::test]
+    async fn test_special_referral_report_handler_non_admin() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .returning(move |_| Ok(JwtClaims::new(2, None, false, ts as usize)));
+        state
+            .get_mut_helpers()
+            .mut_user_helpers()
+            .expect_get_special_referral_code()
+            .never();
+        let app = build_app_routes(Arc::new(state));
+        let path = "/api/v1/admin/specialReferralCode/PARTNER50/report";
+        let req = build_get_request(path, Some(token));
+        let res =
+            oneshot_request::<GenericResponse>(app, req, Some(StatusCode::UNAUTHORIZED)).await;
+        assert!(!res.success);
+    }
+
+    #[tokio::test]
+    async fn test_update_special_referral_handler_valid_till_only() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let referral_code = "PARTNER50";
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .returning(move |_| Ok(JwtClaims::new(1, None, true, ts as usize)));
+        state
+            .get_mut_helpers()
+            .mut_user_helpers()
+            .expect_get_special_referral_code()
+            .once()
+            .with(function(|_: &DbClient| true), eq(referral_code))
+            .returning(|_, _| {
+                let special_referral_code = SpecialReferralCode {
+                    referral_code: "PARTNER50".into(),
+                    valid_from: 100,
+                    valid_till: 200,
+                    ..Default::default()
+                };
+                Ok(Some(special_referral_code))
+            });
+        state.get_mut_db().expect_update_one().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/admin/specialReferralCode/{referral_code}");
+        let body = json!({ "validTill": 50 });
+        let req = build_post_request(&path, body.to_string().as_str(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(!res.success);
+        assert_eq!(res.message, "validTill must be greater than validFrom");
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+
+use crate::{
+    config::{AppError, AppState},
+    models::{CreateSpecialReferralReq, UpdateSpecialReferralReq},
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for CreateSpecialReferralReq {
+    async fn validate_extra(
+        &self,
+        state: Arc<AppState>,
+        _user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        if self.valid_till <= self.valid_from {
+            let err = "validTill must be greater than validFrom";
+            return Err(AppError::BadRequest(err.into()));
+        }
+        // special referral code must not clash with referral code of an user
+        let user = state
+            .helpers()
+            .user_helpers()
+            .get_user_by_referral_code(state.db(), &self.referral_code)
+            .await?;
+        if user.is_some() {
+            let err = "referralCode already exists";
+            return Err(AppError::BadRequest(err.into()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ValidateExtra for UpdateSpecialReferralReq {
+    async fn validate_extra(
+        &self,
+        _state: Arc<AppState>,
+        _user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        if let (Some(valid_from), Some(valid_till)) = (self.valid_from, self.valid_till) {
+            if valid_till <= valid_from {
+                let err = "validTill must be greater than validFrom";
+                return Err(AppError::BadRequest(err.into()));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+
+    use crate::{import_double, models::User};
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn create_special_referral_req_validate_extra() {
+        let mut req = CreateSpecialReferralReq {
+            referral_code: "PARTNER50".into(),
+            campaign_name: "Partner campaign".into(),
+            bonus: 50,
+            max_redemptions: Some(100),
+            valid_from: 20,
+            valid_till: 10,
+            target_segment: None,
+        };
+        let state = Arc::new(AppState::mock());
+        match req.validate_extra(state, Some(1)).await {
+            Err(AppError::BadRequest(e)) => {
+                assert_eq!(e, "validTill must be greater than validFrom")
+            }
+            _ => panic!(),
+        };
+
+        req.valid_till = 30;
+        let mut state = AppState::mock();
+        state
+            .get_mut_helpers()
+            .mut_user_helpers()
+            .expect_get_user_by_referral_code()
+            .once()
+            .with(function(|_: &DbClient| true), eq("PARTNER50"))
+            .returning(|_, _| Ok(Some(User::default())));
+        match req.validate_extra(Arc::new(state), Some(1)).await {
+            Err(AppError::BadRequest(e)) => assert_eq!(e, "referralCode already exists"),
+            _ => panic!(),
+        };
+
+        let mut state = AppState::mock();
+        state
+            .get_mut_helpers()
+            .mut_user_helpers()
+            .expect_get_user_by_referral_code()
+            .once()
+            .returning(|_, _| Ok(None));
+        let result = req.validate_extra(Arc::new(state), Some(1)).await;
+        assert!(result.is_ok());
+    }
+}
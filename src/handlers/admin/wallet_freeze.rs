@@ -0,0 +1,325 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use futures::FutureExt;
+use mongodb::{
+    bson::{doc, to_bson},
+    options::FindOptions,
+};
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody},
+    constants::*,
+    import_double,
+    models::*,
+    utils::get_epoch_ts,
+};
+
+import_double!(DbSession);
+
+/// Freeze wallet
+///
+/// Freeze the wallet of an user completely or just withdrawals till the expiry
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/wallet/{user_id}/freeze",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("user_id" = u32, Path, description = "user id"),
+    ),
+    security(("authorization" = [])),
+    request_body = FreezeWalletReq,
+    responses(
+        (status = StatusCode::OK, description = "Wallet frozen", body = GenericResponse),
+    ),
+    tag = "Admin API"
+)]
+pub async fn freeze_wallet_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(user_id): Path<u32>,
+    ValidatedBody(body): ValidatedBody<FreezeWalletReq>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let freeze = WalletFreeze {
+        scope: body.scope,
+        reason: body.reason,
+        block_credits: body.block_credits.unwrap_or_default(),
+        expires_ts: body.expires_ts,
+        frozen_by: claims.id,
+        frozen_ts: get_epoch_ts(),
+    };
+    state
+        .db()
+        .execute_transaction(None, None, move |session| {
+            let freeze = freeze.clone();
+            async move {
+                freeze_wallet(session, user_id, &freeze).await?;
+                Ok(())
+            }
+            .boxed()
+        })
+        .await
+        .map_err(AppError::from_db_error)?;
+    Ok(GenericResponse::json_response(
+        true,
+        "Wallet frozen successfully",
+    ))
+}
+
+/// Unfreeze wallet
+///
+/// Remove the freeze from the wallet of an user
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/wallet/{user_id}/unfreeze",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("user_id" = u32, Path, description = "user id"),
+    ),
+    security(("authorization" = [])),
+    request_body = UnfreezeWalletReq,
+    responses(
+        (status = StatusCode::OK, description = "Wallet unfrozen", body = GenericResponse),
+    ),
+    tag = "Admin API"
+)]
+pub async fn unfreeze_wallet_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(user_id): Path<u32>,
+    ValidatedBody(body): ValidatedBody<UnfreezeWalletReq>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let admin_id = claims.id;
+    let reason = body.reason;
+    state
+        .db()
+        .execute_transaction(None, None, move |session| {
+            let reason = reason.clone();
+            async move {
+                unfreeze_wallet(session, user_id, &reason, admin_id).await?;
+                Ok(())
+            }
+            .boxed()
+        })
+        .await
+        .map_err(AppError::from_db_error)?;
+    Ok(GenericResponse::json_response(
+        true,
+        "Wallet unfrozen successfully",
+    ))
+}
+
+/// Wallet freeze audits
+///
+/// Get the freeze and unfreeze history of the wallet of an user, latest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/wallet/{user_id}/freezeAudits",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("user_id" = u32, Path, description = "user id"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Wallet freeze audits", body = WalletFreezeAuditsRes),
+    ),
+    tag = "Admin API"
+)]
+pub async fn wallet_freeze_audits_handler(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<u32>,
+) -> Result<Json<WalletFreezeAuditsRes>, AppError> {
+    let filter = doc! {"userId": user_id};
+    let options = FindOptions::builder()
+        .sort(doc! {"createdTs": -1})
+        .limit(DEFAULT_QUERY_LIMIT as i64)
+        .build();
+    let audits = state
+        .db()
+        .find::<WalletFreezeAudit>(
+            DB_NAME,
+            COLL_WALLET_FREEZE_AUDITS,
+            Some(filter),
+            Some(options),
+        )
+        .await?;
+    let res = WalletFreezeAuditsRes {
+        success: true,
+        audits,
+    };
+    Ok(Json(res))
+}
+
+async fn freeze_wallet(
+    session: &mut DbSession,
+    user_id: u32,
+    freeze: &WalletFreeze,
+) -> Result<(), AppError> {
+    let filter = doc! {"userId": user_id};
+    let update = doc! {
+        "$set": {
+            "freeze": to_bson(freeze)?,
+            "updatedTs": freeze.frozen_ts as i64,
+            "updatedBy": freeze.frozen_by
+        }
+    };
+    let result = session
+        .update_one_with_session(DB_NAME, COLL_WALLETS, filter, update, None)
+        .await?;
+    if result.matched_count == 0 {
+        let err = format!("Wallet of user {user_id} not found");
+        return Err(AppError::NotFound(err));
+    }
+    let audit = WalletFreezeAudit {
+        user_id,
+        action: WalletFreezeAction::Freeze,
+        reason: freeze.reason.clone(),
+        freeze: Some(freeze.clone()),
+        created_by: freeze.frozen_by,
+        created_ts: freeze.frozen_ts,
+        ..Default::default()
+    };
+    session
+        .insert_one_with_session(DB_NAME, COLL_WALLET_FREEZE_AUDITS, &audit, None)
+        .await?;
+    Ok(())
+}
+
+async fn unfreeze_wallet(
+    session: &mut DbSession,
+    user_id: u32,
+    reason: &str,
+    admin_id: u32,
+) -> Result<(), AppError> {
+    let ts = get_epoch_ts();
+    let filter = doc! {"userId": user_id, "freeze": {"$ne": null}};
+    let update = doc! {
+        "$unset": {"freeze": ""},
+        "$set": {"updatedTs": ts as i64, "updatedBy": admin_id}
+    };
+    let result = session
+        .update_one_with_session(DB_NAME, COLL_WALLETS, filter, update, None)
+        .await?;
+    if result.matched_count == 0 {
+        let err = format!("Wallet of user {user_id} is not frozen");
+        return Err(AppError::BadRequest(err));
+    }
+    let audit = WalletFreezeAudit {
+        user_id,
+        action: WalletFreezeAction::Unfreeze,
+        reason: reason.to_owned(),
+        created_by: admin_id,
+        created_ts: ts,
+        ..Default::default()
+    };
+    session
+        .insert_one_with_session(DB_NAME, COLL_WALLET_FREEZE_AUDITS, &audit, None)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{always, eq, function};
+    use serde_json::json;
+
+    use crate::{
+        config::build_app_routes,
+        utils::test_helper::{build_get_request, build_post_request, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_freeze_wallet_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .times(2)
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(1, None, true, ts as usize)));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .with(
+                function(Option::is_none),
+                function(Option::is_none),
+                always(),
+            )
+            .return_once(|_, _, _| {
+                let err = AppError::NotFound("Wallet of user 10 not found".into());
+                Err(err.into())
+            });
+        let state = Arc::new(state);
+
+        // expiry in past
+        let app = build_app_routes(state.clone());
+        let body = json!({"scope": "FULL", "reason": "fraud", "expiresTs": ts - 10});
+        let req = build_post_request(
+            "/api/v1/admin/wallet/10/freeze",
+            &body.to_string(),
+            Some(token),
+        );
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(res.message, "expiresTs must be in future");
+
+        // error raised inside the transaction is returned as is
+        let app = build_app_routes(state);
+        let body = json!({"scope": "WITHDRAWALS", "reason": "fraud", "expiresTs": ts + 100});
+        let req = build_post_request(
+            "/api/v1/admin/wallet/10/freeze",
+            &body.to_string(),
+            Some(token),
+        );
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::NOT_FOUND)).await;
+        assert_eq!(res.message, "Wallet of user 10 not found");
+    }
+
+    #[tokio::test]
+    async fn test_wallet_freeze_audits_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(1, None, true, ts as usize)));
+        state
+            .get_mut_db()
+            .expect_find::<WalletFreezeAudit>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_WALLET_FREEZE_AUDITS),
+                eq(Some(doc! {"userId": 10})),
+                always(),
+            )
+            .returning(|_, _, _, _| {
+                let audit = WalletFreezeAudit {
+                    user_id: 10,
+                    action: WalletFreezeAction::Unfreeze,
+                    reason: "cleared".into(),
+                    ..Default::default()
+                };
+                Ok(vec![audit])
+            });
+        let app = build_app_routes(Arc::new(state));
+        let req = build_get_request("/api/v1/admin/wallet/10/freezeAudits", Some(token));
+        let res = oneshot_request::<WalletFreezeAuditsRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.audits.len(), 1);
+        assert_eq!(res.audits[0].action, WalletFreezeAction::Unfreeze);
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+
+use crate::{
+    config::{AppError, AppState},
+    models::FreezeWalletReq,
+    utils::get_epoch_ts,
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for FreezeWalletReq {
+    async fn validate_extra(
+        &self,
+        _state: Arc<AppState>,
+        _user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        if self.expires_ts <= get_epoch_ts() {
+            let err = "expiresTs must be in future";
+            return Err(AppError::BadRequest(err.into()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::models::WalletFreezeScope;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn freeze_wallet_req_validate_extra() {
+        let mut req = FreezeWalletReq {
+            scope: WalletFreezeScope::Full,
+            reason: "fraud investigation".into(),
+            block_credits: None,
+            expires_ts: get_epoch_ts() - 1,
+        };
+        let state = Arc::new(AppState::mock());
+        match req.validate_extra(state.clone(), Some(1)).await {
+            Err(AppError::BadRequest(e)) => assert_eq!(e, "expiresTs must be in future"),
+            _ => panic!(),
+        };
+        req.expires_ts = get_epoch_ts() + 100;
+        assert!(req.validate_extra(state, Some(1)).await.is_ok());
+    }
+}
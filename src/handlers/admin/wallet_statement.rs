@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::State,
+    http::{HeaderName, HeaderValue},
+    response::Response,
+};
+
+use crate::{
+    config::{AppError, AppState, ValidatedParams},
+    constants::*,
+    handlers::wallet::statement::statement_response,
+    models::*,
+    utils::month_range_ts,
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for BulkStatementParams {
+    async fn validate_extra(
+        &self,
+        _state: Arc<AppState>,
+        _user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        month_range_ts(&self.month).map_err(|e| AppError::BadRequest(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Export wallet statements
+///
+/// Export wallet statements of the users having transactions in a month, a page of users
+/// at a time. Pass `x-next-cursor` header of the response as `cursor` to get the next page,
+/// the header is missing on the last page.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/walletStatements",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        BulkStatementParams
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Wallet statements as CSV", content_type = "text/csv", body = String,
+            headers(("x-next-cursor" = u32, description = "cursor for the next page"))),
+        (status = StatusCode::OK, description = "Wallet statements as PDF", content_type = "application/pdf", body = Vec<u8>,
+            headers(("x-next-cursor" = u32, description = "cursor for the next page"))),
+    ),
+    tag = "Admin API"
+)]
+pub async fn export_wallet_statements_handler(
+    State(state): State<Arc<AppState>>,
+    ValidatedParams(params): ValidatedParams<BulkStatementParams>,
+) -> Result<Response, AppError> {
+    let (from_ts, to_ts) = month_range_ts(&params.month)?;
+    let db = state.db();
+    let wallet_helpers = state.helpers().wallet_helpers();
+    let user_ids = wallet_helpers
+        .get_statement_user_ids(
+            db,
+            from_ts,
+            to_ts,
+            params.cursor,
+            STATEMENT_EXPORT_PAGE_SIZE,
+        )
+        .await?;
+    let transactions = match user_ids.is_empty() {
+        true => vec![],
+        false => {
+            wallet_helpers
+                .get_statement_transactions(db, &user_ids, from_ts, to_ts)
+                .await?
+        }
+    };
+    let statements = WalletStatement::from_transactions(from_ts, to_ts, transactions);
+    let format = params.format.unwrap_or_default();
+    let body = WalletStatement::render(&statements, format);
+    let filename = match params.cursor {
+        Some(cursor) => format!("statements_{}_{}", params.month, cursor),
+        None => format!("statements_{}", params.month),
+    };
+    let mut res = statement_response(format, &filename, body);
+    if user_ids.len() as i64 == STATEMENT_EXPORT_PAGE_SIZE {
+        if let Some(last) = user_ids.last() {
+            res.headers_mut().insert(
+                HeaderName::from_static("x-next-cursor"),
+                HeaderValue::from(*last),
+            );
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+    use tower::ServiceExt;
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::{get_epoch_ts, test_helper::build_get_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_export_wallet_statements_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let (from_ts, to_ts) = month_range_ts("2023-07").unwrap();
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(1, None, true, ts as usize)));
+        let wallet_helpers = state.get_mut_helpers().mut_wallet_helpers();
+        // a full page of users, so the next cursor is set
+        wallet_helpers
+            .expect_get_statement_user_ids()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(from_ts),
+                eq(to_ts),
+                eq(Some(5)),
+                eq(STATEMENT_EXPORT_PAGE_SIZE),
+            )
+            .returning(|_, _, _, _, limit| Ok((6..6 + limit as u32).collect()));
+        wallet_helpers
+            .expect_get_statement_transactions()
+            .once()
+            .withf(|_, user_ids, _, _| user_ids.len() as i64 == STATEMENT_EXPORT_PAGE_SIZE)
+            .returning(|_, _, _, _| Ok(vec![]));
+        let app = build_app_routes(Arc::new(state));
+        let path = "/api/v1/admin/walletStatements?month=2023-07&cursor=5";
+        let req = build_get_request(path, Some(token));
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let next_cursor = 5 + STATEMENT_EXPORT_PAGE_SIZE;
+        let header = res.headers().get("x-next-cursor").unwrap();
+        assert_eq!(header.to_str().unwrap(), next_cursor.to_string());
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    config::{AppError, AppState},
+    models::*,
+    utils::get_epoch_ts,
+};
+
+/// Contest detail
+///
+/// Get the contest along with the prize pool and the payout table
+/// projected for the players joined till now.
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/{contest_id}",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest detail", body = ContestDetailRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn contest_detail_handler(
+    State(state): State<Arc<AppState>>,
+    Path(contest_id): Path<ObjectId>,
+) -> Result<Json<ContestDetailRes>, AppError> {
+    let contest = state
+        .helpers()
+        .contest_helpers()
+        .get_contest(state.db(), &contest_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )))?;
+    if contest.status == ContestStatus::Draft {
+        return Err(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )));
+    }
+    let prizes = contest.prize_structure().preview(contest.joined_players);
+    let res = ContestDetailRes {
+        success: true,
+        contest: ContestSummary::from(contest).with_computed(get_epoch_ts()),
+        prizes,
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::test_helper::{build_get_request, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn mock_state(token: &'static str, contest: Contest) -> AppState {
+        let ts = get_epoch_ts();
+        let contest_id = contest.id.unwrap();
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(10, None, false, ts as usize)));
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(move |_, _| Ok(Some(contest.clone())));
+        state
+    }
+
+    #[tokio::test]
+    async fn test_contest_detail_handler() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let contest = Contest {
+            id: Some(contest_id),
+            entry_fee: 10,
+            joined_players: 20,
+            max_players: 100,
+            prize_pool: 500,
+            commission_percent: 10,
+            prize_pool_type: PrizePoolType::Guaranteed,
+            prizes: vec![RankPrize {
+                rank_from: 1,
+                rank_to: 1,
+                prize_type: PrizeType::Percent,
+                amount: 100,
+            }],
+            start_ts: get_epoch_ts() + 3600,
+            status: ContestStatus::Published,
+            ..Default::default()
+        };
+        let state = mock_state(token, contest);
+        let app = build_app_routes(Arc::new(state));
+        let req = build_get_request(&format!("/api/v1/contest/{contest_id}"), Some(token));
+        let res = oneshot_request::<ContestDetailRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.contest.current_prize_pool, 500);
+        assert_eq!(res.contest.fill_percent, 20);
+        assert_eq!(res.prizes.len(), 1);
+        assert_eq!(res.prizes[0].amount, 500);
+    }
+
+    #[tokio::test]
+    async fn test_contest_detail_handler_draft() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let contest = Contest {
+            id: Some(contest_id),
+            status: ContestStatus::Draft,
+            ..Default::default()
+        };
+        let state = mock_state(token, contest);
+        let app = build_app_routes(Arc::new(state));
+        let req = build_get_request(&format!("/api/v1/contest/{contest_id}"), Some(token));
+        oneshot_request::<GenericResponse>(app, req, Some(StatusCode::NOT_FOUND)).await;
+    }
+}
@@ -0,0 +1,777 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use futures::FutureExt;
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    config::{AppError, AppState},
+    constants::*,
+    import_double,
+    models::*,
+    utils::get_epoch_ts,
+};
+
+import_double!(DbSession);
+
+/// Join contest
+///
+/// Join a contest by paying the entry fee, bonus balance is used as allowed by the contest.
+/// Each call adds a new entry till the maximum entries per user of the contest.
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/{contest_id}/join",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest joined", body = JoinContestRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn join_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+) -> Result<Json<JoinContestRes>, AppError> {
+    let contest = state
+        .helpers()
+        .contest_helpers()
+        .get_contest(state.db(), &contest_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )))?;
+    if contest.visibility == ContestVisibility::Private {
+        let err = "Private contest can be joined only with the invite code";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    join(state, claims.id, contest).await
+}
+
+/// Join private contest
+///
+/// Join a private contest with the invite code shared by the creator
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/invite/{invite_code}/join",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("invite_code" = String, Path, description = "invite code of the private contest"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest joined", body = JoinContestRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn join_private_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(invite_code): Path<String>,
+) -> Result<Json<JoinContestRes>, AppError> {
+    let contest = state
+        .helpers()
+        .contest_helpers()
+        .get_contest_by_invite_code(state.db(), &invite_code)
+        .await?
+        .ok_or(AppError::NotFound("Invalid invite code".into()))?;
+    join(state, claims.id, contest).await
+}
+
+async fn join(
+    state: Arc<AppState>,
+    user_id: u32,
+    contest: Contest,
+) -> Result<Json<JoinContestRes>, AppError> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    let contest_id = contest.id.ok_or(AppError::unknown_error())?;
+    validate_joinable(&contest, get_epoch_ts())?;
+    let (entry_no, entry_fee) = join_entry(&state, user_id, &contest).await?;
+    let (contest, play_tracker, balance) = tokio::join!(
+        contest_helpers.get_contest(db, &contest_id),
+        contest_helpers.get_play_tracker(db, &contest_id, user_id, entry_no),
+        state
+            .helpers()
+            .wallet_helpers()
+            .get_user_balance(db, user_id)
+    );
+    if let Some(contest) = contest? {
+        publish_participant_count(&state, &contest);
+    }
+    let res = JoinContestRes {
+        success: true,
+        entry_no,
+        entry_fee: play_tracker?.map(|p| p.entry_fee).unwrap_or(entry_fee),
+        balance: balance?,
+    };
+    Ok(Json(res))
+}
+
+/// Pay the entry fee and add the next entry of the user to the contest,
+/// returns the entry number and the entry fee
+pub(crate) async fn join_entry(
+    state: &Arc<AppState>,
+    user_id: u32,
+    contest: &Contest,
+) -> Result<(u32, Money), AppError> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    let wallet_helpers = state.helpers().wallet_helpers();
+    let contest_id = contest.id.ok_or(AppError::unknown_error())?;
+    let play_trackers = contest_helpers
+        .get_user_play_trackers(db, &contest_id, user_id)
+        .await?;
+    let entry_no = next_entry_no(contest, &play_trackers)?;
+    // self exclusion applies to every mode, only the spend limits are skipped for practice
+    state
+        .validators()
+        .validate_not_self_excluded(db, state.helpers(), user_id)
+        .await?;
+    let entry_fee = if contest.is_practice() {
+        validate_practice_plays(state, user_id).await?;
+        Money::default()
+    } else {
+        let balance = wallet_helpers.get_user_balance(db, user_id).await?;
+        let entry_fee = contest
+            .entry_fee_split(balance)
+            .ok_or(insufficient_balance_error())?;
+        state
+            .validators()
+            .validate_contest_spend_limits(db, state.helpers(), user_id, entry_fee.real())
+            .await?;
+        entry_fee
+    };
+    let cloned_state = state.clone();
+    let contest = contest.clone();
+    db.execute_transaction(None, None, move |session| {
+        let cloned_state = cloned_state.clone();
+        let contest = contest.clone();
+        async move {
+            join_contest(&cloned_state, session, user_id, entry_no, &contest).await?;
+            Ok(())
+        }
+        .boxed()
+    })
+    .await
+    .map_err(AppError::from_db_error)?;
+    Ok((entry_no, entry_fee))
+}
+
+/// Entry numbers are not reused, so the withdrawn entries are skipped
+/// for the next number but not counted for the maximum entries
+pub(crate) fn next_entry_no(
+    contest: &Contest,
+    play_trackers: &[PlayTracker],
+) -> Result<u32, AppError> {
+    let active = play_trackers
+        .iter()
+        .filter(|p| p.status != PlayTrackerStatus::Withdrawn)
+        .count() as u32;
+    let max_entries = contest.entries_per_user();
+    if active >= max_entries {
+        let err = match max_entries {
+            1 => "Contest already joined".to_string(),
+            n => format!("Only {n} entries are allowed per user"),
+        };
+        return Err(AppError::BadRequest(err));
+    }
+    let last = play_trackers.iter().map(|p| p.entry_no).max();
+    Ok(last.unwrap_or_default() + 1)
+}
+
+pub(crate) fn publish_participant_count(state: &AppState, contest: &Contest) {
+    let Some(contest_id) = contest.id else {
+        return;
+    };
+    let joined_players = contest.joined_players;
+    let update = ContestUpdate::ParticipantCount(ParticipantCount { joined_players });
+    state.publish_contest_event(contest_id, update);
+}
+
+fn validate_joinable(contest: &Contest, ts: u64) -> Result<(), AppError> {
+    if !contest.is_joinable(ts) {
+        let err = "Contest is not open for joining";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    if contest.is_full() {
+        return Err(AppError::BadRequest("Contest is full".into()));
+    }
+    Ok(())
+}
+
+/// Practice contests are free, the number of plays in a day is limited to avoid abuse
+async fn validate_practice_plays(state: &AppState, user_id: u32) -> Result<(), AppError> {
+    let from_ts = get_epoch_ts().saturating_sub(24 * 60 * 60);
+    let plays = state
+        .helpers()
+        .contest_helpers()
+        .count_practice_plays(state.db(), user_id, from_ts)
+        .await?;
+    if plays >= PRACTICE_PLAYS_DAILY_LIMIT {
+        let err =
+            format!("Only {PRACTICE_PLAYS_DAILY_LIMIT} practice contests can be joined in a day");
+        return Err(AppError::BadRequest(err));
+    }
+    Ok(())
+}
+
+fn insufficient_balance_error() -> AppError {
+    AppError::BadRequest("Insufficient balance to pay the entry fee".into())
+}
+
+/// Debit the entry fee from the wallet, record the PayForContest transaction
+/// and create the play tracker of the entry for the contest.
+/// Wallet is not touched for the practice contests.
+async fn join_contest(
+    state: &AppState,
+    session: &mut DbSession,
+    user_id: u32,
+    entry_no: u32,
+    contest: &Contest,
+) -> Result<(), AppError> {
+    let contest_id = contest.id.ok_or(AppError::unknown_error())?;
+    let contest_helpers = state.helpers().contest_helpers();
+    let wallet_helpers = state.helpers().wallet_helpers();
+    let entry_fee = if contest.is_practice() {
+        Money::default()
+    } else {
+        // split is calculated again from the balance read in the transaction
+        let balance = wallet_helpers
+            .get_user_balance_session(session, user_id)
+            .await?;
+        contest
+            .entry_fee_split(balance)
+            .ok_or(insufficient_balance_error())?
+    };
+    contest_helpers
+        .join_contest_session(session, &contest_id)
+        .await?;
+    let mut play_tracker = PlayTracker::new(contest_id, user_id, entry_fee);
+    play_tracker.entry_no = entry_no;
+    play_tracker.practice = contest.is_practice();
+    contest_helpers
+        .insert_play_tracker_session(session, &play_tracker)
+        .await?;
+    if contest.is_practice() {
+        return Ok(());
+    }
+    let (balance_before, balance_after) = wallet_helpers
+        .update_wallet_with_session(
+            session,
+            user_id,
+            entry_fee.real(),
+            entry_fee.bonus(),
+            true,
+            false,
+        )
+        .await?;
+    let transaction = WalletTransaction::pay_for_contest_trans(
+        user_id,
+        &contest_id.to_hex(),
+        entry_fee.real(),
+        entry_fee.bonus(),
+        balance_before,
+        balance_after,
+    );
+    session
+        .insert_one_with_session(DB_NAME, COLL_WALLET_TRANSACTIONS, &transaction, None)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{always, eq, function};
+
+    use crate::{
+        config::build_app_routes,
+        helpers::Helpers,
+        utils::test_helper::{build_post_request, mock_user_token, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn open_contest(contest_id: ObjectId) -> Contest {
+        Contest {
+            id: Some(contest_id),
+            entry_fee: 50,
+            bonus_usage_percent: 20,
+            max_players: 10,
+            start_ts: get_epoch_ts() + 100,
+            status: ContestStatus::Published,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_join_contest_handler() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        let mut seq = mockall::Sequence::new();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .in_sequence(&mut seq)
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(move |_, _| Ok(Some(open_contest(contest_id))));
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| {
+                let mut contest = open_contest(contest_id);
+                contest.joined_players = 1;
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id), eq(user_id))
+            .returning(|_, _, _| Ok(vec![]));
+        contest_helpers
+            .expect_get_play_tracker()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(user_id),
+                eq(1),
+            )
+            .returning(move |_, _, _, _| {
+                let play_tracker = PlayTracker::new(contest_id, user_id, Money::new(40, 10));
+                Ok(Some(play_tracker))
+            });
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_user_balance()
+            .times(2)
+            .with(function(|_: &DbClient| true), eq(user_id))
+            .returning(|_, _| Ok(Money::new(100, 100)));
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_validators()
+            .expect_validate_contest_spend_limits()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(|_: &Helpers| true),
+                eq(user_id),
+                eq(40),
+            )
+            .returning(|_, _, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .with(
+                function(Option::is_none),
+                function(Option::is_none),
+                always(),
+            )
+            .return_once(|_, _, _| Ok(()));
+        let state = Arc::new(state);
+        let mut events = state.contest_events().subscribe();
+        let app = build_app_routes(state);
+        let path = format!("/api/v1/contest/{contest_id}/join");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<JoinContestRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.entry_no, 1);
+        assert_eq!(res.entry_fee, Money::new(40, 10));
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.contest_id, contest_id);
+        assert_eq!(
+            event.update,
+            ContestUpdate::ParticipantCount(ParticipantCount { joined_players: 1 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_join_contest_handler_full_contest() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| {
+                let mut contest = open_contest(contest_id);
+                contest.joined_players = contest.max_players;
+                Ok(Some(contest))
+            });
+        state.get_mut_db().expect_execute_transaction().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/join");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(!res.success);
+        assert_eq!(res.message, "Contest is full");
+    }
+
+    #[tokio::test]
+    async fn test_join_contest_handler_insufficient_balance() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| Ok(Some(open_contest(contest_id))));
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_user_balance()
+            .once()
+            .returning(|_, _| Ok(Money::new(30, 100)));
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_validators()
+            .expect_validate_contest_spend_limits()
+            .never();
+        state.get_mut_db().expect_execute_transaction().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/join");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(res.message, "Insufficient balance to pay the entry fee");
+    }
+
+    #[tokio::test]
+    async fn test_join_contest_handler_private_contest() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 10);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| {
+                let mut contest = open_contest(contest_id);
+                contest.visibility = ContestVisibility::Private;
+                Ok(Some(contest))
+            });
+        contest_helpers.expect_get_user_play_trackers().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/join");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(
+            res.message,
+            "Private contest can be joined only with the invite code"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_join_private_contest_handler() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest_by_invite_code()
+            .once()
+            .with(function(|_: &DbClient| true), eq("AB12CD34"))
+            .returning(move |_, _| {
+                let mut contest = open_contest(contest_id);
+                contest.visibility = ContestVisibility::Private;
+                contest.invite_code = Some("AB12CD34".into());
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(move |_, _| Ok(Some(open_contest(contest_id))));
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        contest_helpers
+            .expect_get_play_tracker()
+            .once()
+            .returning(move |_, _, _, _| {
+                let play_tracker = PlayTracker::new(contest_id, user_id, Money::new(40, 10));
+                Ok(Some(play_tracker))
+            });
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_user_balance()
+            .times(2)
+            .returning(|_, _| Ok(Money::new(100, 100)));
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_validators()
+            .expect_validate_contest_spend_limits()
+            .once()
+            .returning(|_, _, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .return_once(|_, _, _| Ok(()));
+        let app = build_app_routes(Arc::new(state));
+        let req = build_post_request("/api/v1/contest/invite/AB12CD34/join", "", Some(token));
+        let res = oneshot_request::<JoinContestRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+    }
+
+    #[tokio::test]
+    async fn test_join_private_contest_handler_invalid_code() {
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 10);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest_by_invite_code()
+            .once()
+            .returning(|_, _| Ok(None));
+        let app = build_app_routes(Arc::new(state));
+        let req = build_post_request("/api/v1/contest/invite/INVALID/join", "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::NOT_FOUND)).await;
+        assert_eq!(res.message, "Invalid invite code");
+    }
+
+    #[tokio::test]
+    async fn test_join_contest_handler_practice_contest() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let practice_contest = move || {
+            let mut contest = open_contest(contest_id);
+            contest.entry_fee = 0;
+            contest.mode = ContestMode::Practice;
+            contest
+        };
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .times(2)
+            .returning(move |_, _| Ok(Some(practice_contest())));
+        contest_helpers
+            .expect_count_practice_plays()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(user_id),
+                function(|from_ts: &u64| *from_ts < get_epoch_ts()),
+            )
+            .returning(|_, _, _| Ok(PRACTICE_PLAYS_DAILY_LIMIT - 1));
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        contest_helpers
+            .expect_get_play_tracker()
+            .once()
+            .returning(|_, _, _, _| Ok(None));
+        // balance is fetched only for the response
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_user_balance()
+            .once()
+            .returning(|_, _| Ok(Money::new(0, 0)));
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_validators()
+            .expect_validate_contest_spend_limits()
+            .never();
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .return_once(|_, _, _| Ok(()));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/join");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<JoinContestRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.entry_fee, Money::new(0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_join_contest_handler_practice_limit() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 10);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| {
+                let mut contest = open_contest(contest_id);
+                contest.mode = ContestMode::Practice;
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        contest_helpers
+            .expect_count_practice_plays()
+            .once()
+            .returning(|_, _, _| Ok(PRACTICE_PLAYS_DAILY_LIMIT));
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        state.get_mut_db().expect_execute_transaction().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/join");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        let expected =
+            format!("Only {PRACTICE_PLAYS_DAILY_LIMIT} practice contests can be joined in a day");
+        assert_eq!(res.message, expected);
+    }
+
+    #[tokio::test]
+    async fn test_join_contest_handler_practice_self_excluded() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| {
+                let mut contest = open_contest(contest_id);
+                contest.entry_fee = 0;
+                contest.mode = ContestMode::Practice;
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        contest_helpers.expect_count_practice_plays().never();
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .with(function(|_: &DbClient| true), always(), eq(user_id))
+            .returning(|_, _, _| {
+                let err = "User is self excluded till 01-01-2030";
+                Err(AppError::BadRequest(err.into()))
+            });
+        state.get_mut_db().expect_execute_transaction().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/join");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(res.message.starts_with("User is self excluded till"));
+    }
+
+    #[tokio::test]
+    async fn test_join_contest_handler_max_entries() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| {
+                let mut contest = open_contest(contest_id);
+                contest.max_entries = 2;
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(move |_, _, _| {
+                let play_trackers = (1..=2)
+                    .map(|entry_no| PlayTracker {
+                        entry_no,
+                        ..PlayTracker::new(contest_id, user_id, Money::new(50, 0))
+                    })
+                    .collect();
+                Ok(play_trackers)
+            });
+        state.get_mut_db().expect_execute_transaction().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/join");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(res.message, "Only 2 entries are allowed per user");
+    }
+
+    #[test]
+    fn test_next_entry_no() {
+        let contest_id = ObjectId::new();
+        let mut contest = open_contest(contest_id);
+        let entry = |entry_no, status| PlayTracker {
+            entry_no,
+            status,
+            ..PlayTracker::new(contest_id, 10, Money::default())
+        };
+        assert_eq!(next_entry_no(&contest, &[]).unwrap(), 1);
+        let joined = [entry(1, PlayTrackerStatus::Joined)];
+        match next_entry_no(&contest, &joined) {
+            Err(AppError::BadRequest(e)) => assert_eq!(e, "Contest already joined"),
+            _ => panic!(),
+        }
+        let withdrawn = [entry(1, PlayTrackerStatus::Withdrawn)];
+        assert_eq!(next_entry_no(&contest, &withdrawn).unwrap(), 2);
+        contest.max_entries = 3;
+        let entries = [
+            entry(1, PlayTrackerStatus::Joined),
+            entry(2, PlayTrackerStatus::Withdrawn),
+        ];
+        assert_eq!(next_entry_no(&contest, &entries).unwrap(), 3);
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    config::{AppError, AppState, ValidatedParams},
+    models::*,
+    utils::get_epoch_ts,
+};
+
+/// Contest leaderboard
+///
+/// Get a page of the contest leaderboard along with the rank of the user.
+/// Players are ranked by score, then time taken and then finish time.
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/{contest_id}/leaderboard",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+        LeaderboardParams,
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Leaderboard of the contest", body = LeaderboardRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn leaderboard_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+    ValidatedParams(params): ValidatedParams<LeaderboardParams>,
+) -> Result<Json<LeaderboardRes>, AppError> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    let contest = contest_helpers
+        .get_contest(db, &contest_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )))?;
+    let (skip, limit) = params.skip_limit();
+    let page = if contest.is_live(get_epoch_ts()) {
+        // ranks change with every answer while live, the complete leaderboard
+        // is cached for a short time instead of aggregating on every request
+        let cache = state.leaderboard_cache();
+        let ranked = match cache.get(&contest_id) {
+            Some(ranked) => ranked,
+            None => {
+                let ranked = Arc::new(contest_helpers.get_leaderboard(db, &contest_id).await?);
+                cache.insert(contest_id, ranked.clone());
+                ranked
+            }
+        };
+        LeaderboardPage::from_ranked(&ranked, skip, limit, claims.id)
+    } else {
+        contest_helpers
+            .get_leaderboard_page(db, &contest_id, skip, limit, claims.id)
+            .await?
+    };
+    let res = LeaderboardRes {
+        success: true,
+        total: page.total,
+        entries: page.entries,
+        own_rank: page.own_rank,
+        prize_pool_type: contest.prize_pool_type,
+        current_prize_pool: contest.prize_structure().pool,
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::test_helper::{build_get_request, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn mock_state(token: &'static str, user_id: u32, contest: Contest) -> AppState {
+        let ts = get_epoch_ts();
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .returning(move |_, _| Ok(Some(contest.clone())));
+        state
+    }
+
+    fn ranked(count: u32) -> Vec<LeaderboardEntry> {
+        (1..=count)
+            .map(|rank| LeaderboardEntry {
+                rank,
+                user_id: rank,
+                score: 100 - rank as i32,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_handler_live_contest() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 30;
+        let contest_id = ObjectId::new();
+        let ts = get_epoch_ts();
+        let contest = Contest {
+            id: Some(contest_id),
+            start_ts: ts - 100,
+            end_ts: ts + 100,
+            status: ContestStatus::Live,
+            entry_fee: 10,
+            joined_players: 50,
+            commission_percent: 10,
+            prize_pool_type: PrizePoolType::Dynamic,
+            ..Default::default()
+        };
+        let mut state = mock_state(token, user_id, contest);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_leaderboard()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(|_, _| Ok(ranked(50)));
+        contest_helpers.expect_get_leaderboard_page().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/leaderboard?page=2&limit=10");
+        for _ in 0..2 {
+            let req = build_get_request(&path, Some(token));
+            let res =
+                oneshot_request::<LeaderboardRes>(app.clone(), req, Some(StatusCode::OK)).await;
+            assert_eq!(res.total, 50);
+            assert_eq!(res.entries.len(), 10);
+            assert_eq!(res.entries[0].rank, 11);
+            assert_eq!(res.own_rank.map(|e| e.rank), Some(30));
+            assert_eq!(res.current_prize_pool, 450);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_handler_ended_contest() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 5;
+        let contest_id = ObjectId::new();
+        let ts = get_epoch_ts();
+        let contest = Contest {
+            id: Some(contest_id),
+            start_ts: ts - 200,
+            end_ts: ts - 100,
+            status: ContestStatus::Finalized,
+            ..Default::default()
+        };
+        let mut state = mock_state(token, user_id, contest);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers.expect_get_leaderboard().never();
+        contest_helpers
+            .expect_get_leaderboard_page()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(0),
+                eq(20),
+                eq(user_id),
+            )
+            .returning(|_, _, _, _, _| {
+                let entries = ranked(3);
+                let page = LeaderboardPage {
+                    total: 3,
+                    own_rank: None,
+                    entries,
+                };
+                Ok(page)
+            });
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/leaderboard");
+        let req = build_get_request(&path, Some(token));
+        let res = oneshot_request::<LeaderboardRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.entries.len(), 3);
+        assert!(res.own_rank.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_handler_invalid_limit() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .never();
+        let ts = get_epoch_ts();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .returning(move |_| Ok(JwtClaims::new(1, None, false, ts as usize)));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/leaderboard?limit=1000");
+        let req = build_get_request(&path, Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(!res.success);
+    }
+}
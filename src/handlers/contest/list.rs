@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Extension, Json};
+use mongodb::{
+    bson::{doc, to_bson, Document},
+    options::FindOptions,
+};
+
+use crate::{
+    config::{AppError, AppState, ValidatedParams},
+    constants::*,
+    models::*,
+    utils::get_epoch_ts,
+};
+
+/// List contests
+///
+/// Get a page of the public contests matching the filters.
+/// Pass `nextCursor` of the response as `cursor` to get the next page.
+#[utoipa::path(
+    get,
+    path = "/api/v1/contests",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        PublicContestListParams,
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Page of contests", body = ContestListRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn list_public_contests_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedParams(params): ValidatedParams<PublicContestListParams>,
+) -> Result<Json<ContestListRes>, AppError> {
+    let db = state.db();
+    let sort = params.sort.unwrap_or_default();
+    let order = params.order.unwrap_or_default();
+    let limit = params.limit.unwrap_or(CONTEST_LIST_DEFAULT_PAGE_SIZE);
+    let statuses = match &params.status {
+        Some(phase) => phase.statuses(),
+        None => ContestPhase::all_statuses(),
+    };
+    let mut filter = doc! {
+        "status": {"$in": to_bson(&statuses)?},
+        "visibility": {"$ne": to_bson(&ContestVisibility::Private)?},
+    };
+    if let Some(category) = params.category {
+        filter.insert("category", category);
+    }
+    // contests created before the practice mode don't have the field
+    match params.mode {
+        Some(ContestMode::Practice) => {
+            filter.insert("mode", to_bson(&ContestMode::Practice)?);
+        }
+        Some(ContestMode::Paid) => {
+            filter.insert("mode", doc! {"$ne": to_bson(&ContestMode::Practice)?});
+        }
+        None => {}
+    }
+    let mut entry_fee = Document::new();
+    if let Some(min_entry_fee) = params.min_entry_fee {
+        entry_fee.insert("$gte", min_entry_fee as i64);
+    }
+    if let Some(max_entry_fee) = params.max_entry_fee {
+        entry_fee.insert("$lte", max_entry_fee as i64);
+    }
+    if !entry_fee.is_empty() {
+        filter.insert("entryFee", entry_fee);
+    }
+    if let Some(joined) = params.joined {
+        let contest_ids = state
+            .helpers()
+            .contest_helpers()
+            .get_joined_contest_ids(db, claims.id)
+            .await?;
+        let op = if joined { "$in" } else { "$nin" };
+        filter.insert("_id", doc! {op: contest_ids});
+    }
+    if let Some(cursor) = params.cursor {
+        let cursor: ContestCursor = cursor.parse().map_err(AppError::BadRequest)?;
+        filter.extend(cursor.filter(sort, order));
+    }
+    // one extra contest is fetched to know if there is a next page
+    let options = FindOptions::builder()
+        .sort(doc! {sort.field(): order.value(), "_id": order.value()})
+        .projection(ContestSummary::projection())
+        .limit(limit as i64 + 1)
+        .build();
+    let mut contests = db
+        .find::<ContestSummary>(DB_NAME, COLL_CONTESTS, Some(filter), Some(options))
+        .await?;
+    let mut next_cursor = None;
+    if contests.len() as u64 > limit {
+        contests.truncate(limit as usize);
+        next_cursor = contests.last().map(|c| c.cursor(sort).to_string());
+    }
+    let ts = get_epoch_ts();
+    let res = ContestListRes {
+        success: true,
+        contests: contests.into_iter().map(|c| c.with_computed(ts)).collect(),
+        next_cursor,
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+    use mongodb::bson::oid::ObjectId;
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::test_helper::{build_get_request, mock_user_token, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn contest_summary(entry_fee: u64) -> ContestSummary {
+        ContestSummary {
+            id: ObjectId::new(),
+            title: format!("Contest {entry_fee}"),
+            category: "Movies".into(),
+            banner: "".into(),
+            entry_fee,
+            prize_pool: 100,
+            prize_pool_type: PrizePoolType::Fixed,
+            commission_percent: 0,
+            min_players: 2,
+            max_players: 10,
+            joined_players: 5,
+            start_ts: get_epoch_ts() + 3600,
+            end_ts: get_epoch_ts() + 7200,
+            media: ContestMedia::default(),
+            status: ContestStatus::Published,
+            mode: ContestMode::Paid,
+            fill_percent: 0,
+            starts_in_secs: 0,
+            current_prize_pool: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_public_contests_handler() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let joined_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_joined_contest_ids()
+            .once()
+            .with(function(|_: &DbClient| true), eq(user_id))
+            .returning(move |_, _| Ok(vec![joined_id]));
+        state
+            .get_mut_db()
+            .expect_find::<ContestSummary>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTESTS),
+                eq(Some(doc! {
+                    "status": {"$in": ["published"]},
+                    "visibility": {"$ne": "private"},
+                    "category": "Movies",
+                    "entryFee": {"$gte": 10_i64, "$lte": 50_i64},
+                    "_id": {"$nin": [joined_id]},
+                })),
+                function(|options: &Option<FindOptions>| {
+                    let options = options.as_ref().unwrap();
+                    let projection = options.projection.as_ref().unwrap();
+                    options.limit == Some(3)
+                        && options.sort == Some(doc! {"entryFee": -1, "_id": -1})
+                        && !projection.contains_key("questions")
+                }),
+            )
+            .returning(|_, _, _, _| {
+                Ok(vec![
+                    contest_summary(50),
+                    contest_summary(20),
+                    contest_summary(10),
+                ])
+            });
+        let app = build_app_routes(Arc::new(state));
+        let path = "/api/v1/contests?category=Movies&status=upcoming&minEntryFee=10\
+            &maxEntryFee=50&joined=false&sort=entryFee&order=desc&limit=2";
+        let req = build_get_request(path, Some(token));
+        let res = oneshot_request::<ContestListRes>(app, req, Some(StatusCode::OK)).await;
+        assert_eq!(res.contests.len(), 2);
+        assert_eq!(res.contests[0].fill_percent, 50);
+        assert!(res.contests[0].starts_in_secs > 3500);
+        let cursor: ContestCursor = res.next_cursor.unwrap().parse().unwrap();
+        assert_eq!(cursor.value, 20);
+        assert_eq!(cursor.id, res.contests[1].id);
+    }
+
+    #[tokio::test]
+    async fn test_list_public_contests_handler_cursor() {
+        let token = "DUMMY_TOKEN";
+        let cursor = ContestCursor {
+            value: 1000,
+            id: ObjectId::new(),
+        };
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 10);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_joined_contest_ids()
+            .never();
+        let expected_cursor = cursor.clone();
+        state
+            .get_mut_db()
+            .expect_find::<ContestSummary>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTESTS),
+                function(move |filter: &Option<Document>| {
+                    let filter = filter.as_ref().unwrap();
+                    let expected = expected_cursor.filter(ContestSort::StartTs, SortOrder::Asc);
+                    filter.get("$or") == expected.get("$or") && !filter.contains_key("category")
+                }),
+                function(|options: &Option<FindOptions>| {
+                    options.as_ref().unwrap().limit
+                        == Some(CONTEST_LIST_DEFAULT_PAGE_SIZE as i64 + 1)
+                }),
+            )
+            .returning(|_, _, _, _| Ok(vec![contest_summary(10)]));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contests?cursor={cursor}");
+        let req = build_get_request(&path, Some(token));
+        let res = oneshot_request::<ContestListRes>(app, req, Some(StatusCode::OK)).await;
+        assert_eq!(res.contests.len(), 1);
+        assert!(res.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_public_contests_handler_invalid_cursor() {
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 10);
+        state.get_mut_db().expect_find::<ContestSummary>().never();
+        let app = build_app_routes(Arc::new(state));
+        let req = build_get_request("/api/v1/contests?cursor=invalid", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(res.message, "Invalid cursor");
+    }
+}
@@ -0,0 +1,293 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures::{stream, Stream, StreamExt};
+use mongodb::bson::oid::ObjectId;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::{
+    config::{AppError, AppState},
+    models::*,
+};
+
+/// Live contest updates
+///
+/// Server-sent events of the contest till it's finalized or cancelled.
+/// `participantCount` event has `ParticipantCount`, `leaderboard` event has `LeaderboardSnapshot`
+/// while the contest is live and `status` event has `StatusUpdate` as the data.
+/// The current status and participant count are sent first on connecting.
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/{contest_id}/live",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Stream of the contest updates", content_type = "text/event-stream", body = String),
+    ),
+    tag = "App User API"
+)]
+pub async fn live_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+) -> Result<Sse<impl Stream<Item = Result<Event, serde_json::Error>>>, AppError> {
+    // subscribed before reading the contest so that no update is missed in between
+    let receiver = state.contest_events().subscribe();
+    let contest = state
+        .helpers()
+        .contest_helpers()
+        .get_contest(state.db(), &contest_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )))?;
+    if contest.status == ContestStatus::Draft {
+        return Err(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )));
+    }
+    tracing::debug!("User {} following contest {}", claims.id, contest_id);
+    let current = current_updates(&contest);
+    let receiver = (!current[0].is_last()).then_some(receiver);
+    let updates = stream::iter(current)
+        .chain(contest_updates(state, receiver, contest_id))
+        .map(|update| to_event(&update));
+    Ok(Sse::new(updates).keep_alive(KeepAlive::default()))
+}
+
+/// Current status and participant count of the contest, the status comes first
+fn current_updates(contest: &Contest) -> Vec<ContestUpdate> {
+    let joined_players = contest.joined_players;
+    vec![
+        ContestUpdate::status(contest.status.clone()),
+        ContestUpdate::ParticipantCount(ParticipantCount { joined_players }),
+    ]
+}
+
+/// Updates of the contest from the broadcast till the last one.
+/// A subscriber lagging behind skips the missed updates, so a slow client never holds back
+/// the others, and gets the current status and participant count read again from the db
+/// in place of them. Leaderboard is caught up with the next snapshot.
+fn contest_updates(
+    state: Arc<AppState>,
+    receiver: Option<Receiver<ContestEvent>>,
+    contest_id: ObjectId,
+) -> impl Stream<Item = ContestUpdate> {
+    stream::unfold(receiver, move |receiver| {
+        let state = state.clone();
+        async move {
+            let mut receiver = receiver?;
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.contest_id == contest_id => {
+                        let receiver = (!event.update.is_last()).then_some(receiver);
+                        return Some((vec![event.update], receiver));
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::debug!(
+                            "Contest {} subscriber missed {} updates",
+                            contest_id,
+                            missed
+                        );
+                        // buffered updates are older than the state read below
+                        receiver = receiver.resubscribe();
+                        let contest = state
+                            .helpers()
+                            .contest_helpers()
+                            .get_contest(state.db(), &contest_id)
+                            .await;
+                        match contest {
+                            Ok(Some(contest)) => {
+                                let current = current_updates(&contest);
+                                let receiver = (!current[0].is_last()).then_some(receiver);
+                                return Some((current, receiver));
+                            }
+                            Ok(None) => return None,
+                            Err(e) => {
+                                tracing::error!("Not able to get contest {}: {:?}", contest_id, e);
+                            }
+                        }
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    })
+    .flat_map(stream::iter)
+}
+
+fn to_event(update: &ContestUpdate) -> Result<Event, serde_json::Error> {
+    let event = Event::default().event(update.name());
+    match update {
+        ContestUpdate::ParticipantCount(data) => event.json_data(data),
+        ContestUpdate::Leaderboard(data) => event.json_data(data),
+        ContestUpdate::Status(data) => event.json_data(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+    use tokio::sync::broadcast;
+    use tower::ServiceExt;
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::{get_epoch_ts, test_helper::build_get_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn mock_state(token: &'static str, contest: Contest) -> AppState {
+        let ts = get_epoch_ts();
+        let contest_id = contest.id.unwrap();
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(1, None, false, ts as usize)));
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(move |_, _| Ok(Some(contest.clone())));
+        state
+    }
+
+    fn count(joined_players: u32) -> ContestUpdate {
+        ContestUpdate::ParticipantCount(ParticipantCount { joined_players })
+    }
+
+    #[tokio::test]
+    async fn test_live_contest_handler() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let contest = Contest {
+            id: Some(contest_id),
+            joined_players: 4,
+            status: ContestStatus::Live,
+            ..Default::default()
+        };
+        let state = Arc::new(mock_state(token, contest));
+        let app = build_app_routes(state.clone());
+        let path = format!("/api/v1/contest/{contest_id}/live");
+        let req = build_get_request(&path, Some(token));
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let snapshot = LeaderboardSnapshot {
+            total: 0,
+            entries: vec![],
+        };
+        state.publish_contest_event(ObjectId::new(), count(99));
+        state.publish_contest_event(contest_id, ContestUpdate::Leaderboard(snapshot));
+        state.publish_contest_event(contest_id, ContestUpdate::status(ContestStatus::Ended));
+        state.publish_contest_event(contest_id, ContestUpdate::status(ContestStatus::Finalized));
+        // the stream ends after the finalized status
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = std::str::from_utf8(&body).unwrap();
+        let events: Vec<_> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("event:"))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                "status",
+                "participantCount",
+                "leaderboard",
+                "status",
+                "status"
+            ]
+        );
+        assert!(body.contains(r#"data:{"joinedPlayers":4}"#));
+        assert!(!body.contains(r#""joinedPlayers":99"#));
+        assert!(body.contains(r#""status":"finalized""#));
+    }
+
+    #[tokio::test]
+    async fn test_live_contest_handler_finalized_contest() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let contest = Contest {
+            id: Some(contest_id),
+            status: ContestStatus::Finalized,
+            ..Default::default()
+        };
+        let app = build_app_routes(Arc::new(mock_state(token, contest)));
+        let path = format!("/api/v1/contest/{contest_id}/live");
+        let req = build_get_request(&path, Some(token));
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        let body = std::str::from_utf8(&body).unwrap();
+        assert_eq!(body.matches("event:").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_live_contest_handler_draft_contest() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let contest = Contest {
+            id: Some(contest_id),
+            ..Default::default()
+        };
+        let app = build_app_routes(Arc::new(mock_state(token, contest)));
+        let path = format!("/api/v1/contest/{contest_id}/live");
+        let req = build_get_request(&path, Some(token));
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_contest_updates_lagged() {
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        // contest is cancelled by the time the subscriber catches up
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_contest()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(move |_, _| {
+                let contest = Contest {
+                    id: Some(contest_id),
+                    joined_players: 5,
+                    status: ContestStatus::Cancelled,
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        let (sender, receiver) = broadcast::channel(2);
+        for joined_players in 1..=5 {
+            let update = count(joined_players);
+            sender.send(ContestEvent { contest_id, update }).unwrap();
+        }
+        let update = ContestUpdate::status(ContestStatus::Cancelled);
+        sender.send(ContestEvent { contest_id, update }).unwrap();
+        let updates: Vec<_> = contest_updates(Arc::new(state), Some(receiver), contest_id)
+            .collect()
+            .await;
+        // missed updates are replaced with the current state
+        assert_eq!(
+            updates,
+            vec![ContestUpdate::status(ContestStatus::Cancelled), count(5)]
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    routing::{get, post},
+    Router,
+};
+
+use crate::config::AppState;
+
+pub(crate) mod detail;
+pub(crate) mod join;
+pub(crate) mod leaderboard;
+pub(crate) mod list;
+pub(crate) mod live;
+pub(crate) mod play;
+pub(crate) mod private;
+pub(crate) mod waitlist;
+
+use detail::*;
+use join::*;
+use leaderboard::*;
+use live::*;
+use play::*;
+use private::*;
+use waitlist::*;
+
+pub fn contest_routes() -> Router<Arc<AppState>, Body> {
+    Router::new()
+        .route("/questionSets", get(question_sets_handler))
+        .route("/private", post(create_private_contest_handler))
+        .route(
+            "/invite/:invite_code/join",
+            post(join_private_contest_handler),
+        )
+        .route("/:contest_id", get(contest_detail_handler))
+        .route("/:contest_id/join", post(join_contest_handler))
+        .route("/:contest_id/leave", post(leave_contest_handler))
+        .route("/:contest_id/waitlist", post(join_waitlist_handler))
+        .route("/:contest_id/start", post(start_play_handler))
+        .route(
+            "/:contest_id/question/:question_no",
+            get(get_question_handler),
+        )
+        .route("/:contest_id/answer", post(submit_answer_handler))
+        .route("/:contest_id/finish", post(finish_play_handler))
+        .route("/:contest_id/leaderboard", get(leaderboard_handler))
+        .route("/:contest_id/live", get(live_contest_handler))
+}
@@ -0,0 +1,503 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody, ValidatedParams},
+    models::*,
+    utils::get_epoch_ts,
+};
+
+/// Start play
+///
+/// Start playing a joined contest, allowed only while the contest is live
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/{contest_id}/start",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+        PlayEntryParams,
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Play started", body = PlayTrackerRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn start_play_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+    ValidatedParams(params): ValidatedParams<PlayEntryParams>,
+) -> Result<Json<PlayTrackerRes>, AppError> {
+    let entry_no = params.entry_no();
+    let (contest, play_tracker) =
+        get_contest_and_tracker(&state, &contest_id, claims.id, entry_no).await?;
+    validate_live(&contest, get_epoch_ts())?;
+    if play_tracker.status != PlayTrackerStatus::Joined {
+        return Err(AppError::BadRequest("Play already started".into()));
+    }
+    let play_tracker = state
+        .helpers()
+        .contest_helpers()
+        .start_play(state.db(), &contest_id, claims.id, entry_no)
+        .await?
+        .ok_or(AppError::BadRequest("Play already started".into()))?;
+    let res = PlayTrackerRes {
+        success: true,
+        play_tracker,
+    };
+    Ok(Json(res))
+}
+
+/// Get question
+///
+/// Get a question of the contest without the answer, time limit starts from the first fetch
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/{contest_id}/question/{question_no}",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+        ("question_no" = u32, Path, description = "index of the question"),
+        PlayEntryParams,
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Question of the contest", body = QuestionRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn get_question_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path((contest_id, question_no)): Path<(ObjectId, u32)>,
+    ValidatedParams(params): ValidatedParams<PlayEntryParams>,
+) -> Result<Json<QuestionRes>, AppError> {
+    let entry_no = params.entry_no();
+    let (contest, play_tracker) =
+        get_contest_and_tracker(&state, &contest_id, claims.id, entry_no).await?;
+    let ts = get_epoch_ts();
+    validate_live(&contest, ts)?;
+    if play_tracker.status != PlayTrackerStatus::Started {
+        return Err(AppError::BadRequest("Play is not in progress".into()));
+    }
+    let question = contest
+        .questions
+        .get(question_no as usize)
+        .ok_or(AppError::BadRequest("Invalid questionNo".into()))?;
+    if play_tracker.served_ts(question_no).is_none() {
+        state
+            .helpers()
+            .contest_helpers()
+            .serve_question(
+                state.db(),
+                &contest_id,
+                claims.id,
+                entry_no,
+                question_no,
+                ts,
+            )
+            .await?;
+    }
+    let res = QuestionRes {
+        success: true,
+        question: QuestionView::new(question_no, question),
+    };
+    Ok(Json(res))
+}
+
+/// Submit answer
+///
+/// Submit answer of a question, each question can be answered only once
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/{contest_id}/answer",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+        PlayEntryParams,
+    ),
+    security(("authorization" = [])),
+    request_body = SubmitAnswerReq,
+    responses(
+        (status = StatusCode::OK, description = "Answer submitted", body = GenericResponse),
+    ),
+    tag = "App User API"
+)]
+pub async fn submit_answer_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+    ValidatedParams(params): ValidatedParams<PlayEntryParams>,
+    ValidatedBody(body): ValidatedBody<SubmitAnswerReq>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let entry_no = params.entry_no();
+    let (contest, play_tracker) =
+        get_contest_and_tracker(&state, &contest_id, claims.id, entry_no).await?;
+    let ts = get_epoch_ts();
+    validate_live(&contest, ts)?;
+    if play_tracker.status != PlayTrackerStatus::Started {
+        return Err(AppError::BadRequest("Play is not in progress".into()));
+    }
+    let question = contest
+        .questions
+        .get(body.question_no as usize)
+        .ok_or(AppError::BadRequest("Invalid questionNo".into()))?;
+    if play_tracker.is_answered(body.question_no) {
+        return Err(answered_error());
+    }
+    let served_ts = play_tracker
+        .served_ts(body.question_no)
+        .ok_or(AppError::BadRequest("Question is not served yet".into()))?;
+    let points = contest
+        .scoring
+        .score(question, body.answer, ts.saturating_sub(served_ts))
+        .ok_or(AppError::BadRequest(
+            "Time limit of the question is over".into(),
+        ))?;
+    let answer = PlayAnswer {
+        question_no: body.question_no,
+        answer: body.answer,
+        is_correct: question.answer == body.answer,
+        points,
+        answered_ts: ts,
+    };
+    let submitted = state
+        .helpers()
+        .contest_helpers()
+        .submit_answer(state.db(), &contest_id, claims.id, entry_no, &answer)
+        .await?;
+    if !submitted {
+        return Err(answered_error());
+    }
+    Ok(GenericResponse::json_response(true, "Answer submitted"))
+}
+
+/// Finish play
+///
+/// Finish playing the contest, time taken is counted till the end of the contest.
+/// Answers are not returned till the contest is ended, only the score and the time taken.
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/{contest_id}/finish",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+        PlayEntryParams,
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Play finished", body = PlayTrackerRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn finish_play_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+    ValidatedParams(params): ValidatedParams<PlayEntryParams>,
+) -> Result<Json<PlayTrackerRes>, AppError> {
+    let entry_no = params.entry_no();
+    let (contest, play_tracker) =
+        get_contest_and_tracker(&state, &contest_id, claims.id, entry_no).await?;
+    // play can be finished after the end time till the contest is finalized
+    if !matches!(contest.status, ContestStatus::Live | ContestStatus::Ended) {
+        return Err(AppError::BadRequest("Contest is not live".into()));
+    }
+    if play_tracker.status != PlayTrackerStatus::Started {
+        return Err(AppError::BadRequest("Play is not in progress".into()));
+    }
+    let ts = get_epoch_ts();
+    let time_taken = play_tracker.time_taken_till(ts, contest.end_ts);
+    let play_tracker = state
+        .helpers()
+        .contest_helpers()
+        .finish_play(
+            state.db(),
+            &contest_id,
+            claims.id,
+            entry_no,
+            ts.min(contest.end_ts),
+            time_taken,
+        )
+        .await?
+        .ok_or(AppError::BadRequest("Play is not in progress".into()))?;
+    // correct answers of a live contest are not revealed, the user may have other entries
+    let play_tracker = if contest.status == ContestStatus::Live {
+        play_tracker.without_answers()
+    } else {
+        play_tracker
+    };
+    let res = PlayTrackerRes {
+        success: true,
+        play_tracker,
+    };
+    Ok(Json(res))
+}
+
+async fn get_contest_and_tracker(
+    state: &Arc<AppState>,
+    contest_id: &ObjectId,
+    user_id: u32,
+    entry_no: u32,
+) -> Result<(Contest, PlayTracker), AppError> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    let (contest, play_tracker) = tokio::join!(
+        contest_helpers.get_contest(db, contest_id),
+        contest_helpers.get_play_tracker(db, contest_id, user_id, entry_no)
+    );
+    let contest = contest?.ok_or(AppError::NotFound(format!(
+        "Contest {contest_id} not found"
+    )))?;
+    let play_tracker = play_tracker?.ok_or(AppError::NotFound("Contest not joined".into()))?;
+    if play_tracker.status == PlayTrackerStatus::Withdrawn {
+        return Err(AppError::BadRequest("Entry is withdrawn".into()));
+    }
+    Ok((contest, play_tracker))
+}
+
+fn validate_live(contest: &Contest, ts: u64) -> Result<(), AppError> {
+    if !contest.is_live(ts) {
+        return Err(AppError::BadRequest("Contest is not live".into()));
+    }
+    Ok(())
+}
+
+fn answered_error() -> AppError {
+    AppError::BadRequest("Question already answered".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+    use serde_json::json;
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::test_helper::{build_get_request, build_post_request, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn mock_state(token: &'static str, user_id: u32, play_tracker: PlayTracker) -> AppState {
+        let ts = get_epoch_ts();
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| {
+                let contest = Contest {
+                    start_ts: ts - 100,
+                    end_ts: ts + 100,
+                    status: ContestStatus::Live,
+                    questions: vec![Question {
+                        question: "Question 1".into(),
+                        options: vec!["A".into(), "B".into()],
+                        answer: 1,
+                        time_limit_secs: Some(30),
+                        ..Default::default()
+                    }],
+                    scoring: ScoringRule {
+                        points: 10,
+                        negative_points: 2,
+                        max_time_bonus: 0,
+                    },
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_get_play_tracker()
+            .once()
+            .returning(move |_, _, _, _| Ok(Some(play_tracker.clone())));
+        state
+    }
+
+    #[tokio::test]
+    async fn test_submit_answer_handler() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let play_tracker = PlayTracker {
+            status: PlayTrackerStatus::Started,
+            served: vec![ServedQuestion {
+                question_no: 0,
+                served_ts: get_epoch_ts() - 10,
+            }],
+            ..Default::default()
+        };
+        let mut state = mock_state(token, user_id, play_tracker);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_submit_answer()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(user_id),
+                eq(1),
+                function(|answer: &PlayAnswer| {
+                    answer.question_no == 0 && !answer.is_correct && answer.points == -2
+                }),
+            )
+            .returning(|_, _, _, _, _| Ok(true));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/answer");
+        let body = json!({"questionNo": 0, "answer": 0});
+        let req = build_post_request(&path, &body.to_string(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+    }
+
+    #[tokio::test]
+    async fn test_submit_answer_handler_time_limit_over() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let play_tracker = PlayTracker {
+            status: PlayTrackerStatus::Started,
+            served: vec![ServedQuestion {
+                question_no: 0,
+                served_ts: get_epoch_ts() - 60,
+            }],
+            ..Default::default()
+        };
+        let mut state = mock_state(token, 10, play_tracker);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_submit_answer()
+            .never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/answer");
+        let body = json!({"questionNo": 0, "answer": 1});
+        let req = build_post_request(&path, &body.to_string(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(res.message, "Time limit of the question is over");
+    }
+
+    #[tokio::test]
+    async fn test_get_question_handler() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let play_tracker = PlayTracker {
+            status: PlayTrackerStatus::Started,
+            ..Default::default()
+        };
+        let mut state = mock_state(token, user_id, play_tracker);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_serve_question()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(user_id),
+                eq(2),
+                eq(0),
+                function(|_: &u64| true),
+            )
+            .returning(|_, _, _, _, _, _| Ok(()));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/question/0?entryNo=2");
+        let req = build_get_request(&path, Some(token));
+        let res = oneshot_request::<serde_json::Value>(app, req, Some(StatusCode::OK)).await;
+        assert_eq!(res["question"]["question"], "Question 1");
+        assert_eq!(res["question"]["timeLimitSecs"], 30);
+        assert!(res["question"].get("answer").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_submit_answer_handler_already_answered() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let play_tracker = PlayTracker {
+            status: PlayTrackerStatus::Started,
+            answers: vec![PlayAnswer::default()],
+            ..Default::default()
+        };
+        let mut state = mock_state(token, 10, play_tracker);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_submit_answer()
+            .never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/answer");
+        let body = json!({"questionNo": 0, "answer": 0});
+        let req = build_post_request(&path, &body.to_string(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(res.message, "Question already answered");
+    }
+
+    #[tokio::test]
+    async fn test_finish_play_handler() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let started_ts = get_epoch_ts() - 30;
+        let play_tracker = PlayTracker {
+            status: PlayTrackerStatus::Started,
+            started_ts: Some(started_ts),
+            ..Default::default()
+        };
+        let mut state = mock_state(token, user_id, play_tracker);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_finish_play()
+            .once()
+            .withf(move |_, id, uid, entry_no, _, time_taken| {
+                id == &contest_id
+                    && *uid == user_id
+                    && *entry_no == 1
+                    && (30..=31).contains(time_taken)
+            })
+            .returning(|_, _, _, _, finished_ts, time_taken| {
+                let play_tracker = PlayTracker {
+                    status: PlayTrackerStatus::Finished,
+                    answers: vec![PlayAnswer {
+                        question_no: 0,
+                        answer: 1,
+                        is_correct: true,
+                        points: 10,
+                        answered_ts: finished_ts - 5,
+                    }],
+                    score: 10,
+                    finished_ts: Some(finished_ts),
+                    time_taken: Some(time_taken),
+                    ..Default::default()
+                };
+                Ok(Some(play_tracker))
+            });
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/finish");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<PlayTrackerRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.play_tracker.status, PlayTrackerStatus::Finished);
+        // the contest is live, correctness of the answers is not exposed
+        assert!(res.play_tracker.answers.is_empty());
+        assert_eq!(res.play_tracker.score, 10);
+    }
+}
@@ -0,0 +1,325 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Extension, Json};
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody},
+    constants::*,
+    models::*,
+    utils::{get_epoch_ts, is_duplicate_key_error},
+};
+
+/// Question sets
+///
+/// Get the list of question sets which can be used to create private contests
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/questionSets",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "List of question sets", body = QuestionSetsRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn question_sets_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<QuestionSetsRes>, AppError> {
+    let question_sets = state
+        .helpers()
+        .contest_helpers()
+        .get_approved_question_sets(state.db())
+        .await?;
+    let res = QuestionSetsRes {
+        success: true,
+        question_sets: question_sets
+            .into_iter()
+            .map(QuestionSetView::from)
+            .collect(),
+    };
+    Ok(Json(res))
+}
+
+/// Create private contest
+///
+/// Create a private contest from an approved question set. Friends join the contest
+/// with the invite code, the winner takes the entry fees collected after the house commission.
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/private",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = CreatePrivateContestReq,
+    responses(
+        (status = StatusCode::OK, description = "Private contest created", body = PrivateContestRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn create_private_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedBody(body): ValidatedBody<CreatePrivateContestReq>,
+) -> Result<Json<PrivateContestRes>, AppError> {
+    let db = state.db();
+    let question_set = state
+        .helpers()
+        .contest_helpers()
+        .get_question_set(db, &body.question_set_id)
+        .await?
+        .filter(|set| set.approved)
+        .ok_or(AppError::NotFound("Question set not found".into()))?;
+    let mut contest = private_contest(&body, question_set, claims.id, commission_percent());
+    state
+        .validators()
+        .validate_contest(db, state.helpers(), &contest)
+        .await?;
+    // title and invite code are unique, both are generated again on conflict
+    for _ in 0..INVITE_CODE_MAX_RETRY {
+        let invite_code = state.utility().generate_referral_code();
+        contest.title = format!("{} #{}", body.title, invite_code);
+        contest.invite_code = Some(invite_code.clone());
+        let result = db
+            .insert_one::<Contest>(DB_NAME, COLL_CONTESTS, &contest, None)
+            .await;
+        match result {
+            Err(e) if is_duplicate_key_error(&e) => {
+                tracing::debug!("invite code {invite_code} already exists, retrying");
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+            Ok(inserted_id) => {
+                let res = PrivateContestRes {
+                    success: true,
+                    contest_id: ObjectId::parse_str(inserted_id.0)?,
+                    title: contest.title,
+                    invite_code,
+                    entry_fee: contest.entry_fee,
+                    max_players: contest.max_players,
+                    commission_percent: contest.commission_percent,
+                    start_ts: contest.start_ts,
+                    end_ts: contest.end_ts,
+                };
+                return Ok(Json(res));
+            }
+        }
+    }
+    let err =
+        format!("Not able to generate unique invite code in {INVITE_CODE_MAX_RETRY} attempts");
+    Err(anyhow::anyhow!(err).into())
+}
+
+/// House commission on the entry fees of the private contests.
+/// Configurable with `PRIVATE_CONTEST_COMMISSION_PERCENT` env variable.
+fn commission_percent() -> u32 {
+    commission_percent_from_env(
+        "PRIVATE_CONTEST_COMMISSION_PERCENT",
+        PRIVATE_CONTEST_DEFAULT_COMMISSION_PERCENT,
+    )
+}
+
+/// Published private contest where the top rank wins the complete prize pool
+fn private_contest(
+    req: &CreatePrivateContestReq,
+    question_set: QuestionSet,
+    user_id: u32,
+    commission_percent: u32,
+) -> Contest {
+    let ts = get_epoch_ts();
+    let status_change = ContestStatusChange {
+        from: ContestStatus::Draft,
+        to: ContestStatus::Published,
+        actor: None,
+        ts,
+    };
+    let prize = RankPrize {
+        rank_from: 1,
+        rank_to: 1,
+        prize_type: PrizeType::Percent,
+        amount: 100,
+    };
+    Contest {
+        title: req.title.clone(),
+        category: question_set.category,
+        entry_fee: req.entry_fee,
+        prize_pool_type: PrizePoolType::Dynamic,
+        prizes: vec![prize],
+        min_players: 2,
+        max_players: req.max_players,
+        start_ts: req.start_ts,
+        end_ts: req.start_ts + req.duration_secs,
+        media: question_set.media,
+        questions: question_set.questions,
+        status: ContestStatus::Published,
+        status_history: vec![status_change],
+        visibility: ContestVisibility::Private,
+        commission_percent,
+        created_ts: Some(ts),
+        created_by: Some(user_id),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+    use serde_json::json;
+
+    use crate::{
+        config::{build_app_routes, database::InsertedId},
+        helpers::Helpers,
+        import_double,
+        utils::test_helper::{
+            build_get_request, build_post_request, mock_user_token, oneshot_request,
+        },
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn question_set(question_set_id: ObjectId, approved: bool) -> QuestionSet {
+        QuestionSet {
+            id: Some(question_set_id),
+            name: "Movie trivia".into(),
+            category: "Movies".into(),
+            questions: vec![Question::default(); 3],
+            approved,
+            ..Default::default()
+        }
+    }
+
+    fn create_req_body(question_set_id: ObjectId) -> String {
+        json!({
+            "title": "Friday night",
+            "questionSetId": question_set_id.to_hex(),
+            "entryFee": 20,
+            "maxPlayers": 5,
+            "startTs": get_epoch_ts() + 3600,
+            "durationSecs": 600
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_question_sets_handler() {
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 1);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_approved_question_sets()
+            .once()
+            .returning(|_| Ok(vec![question_set(ObjectId::new(), true)]));
+        let app = build_app_routes(Arc::new(state));
+        let req = build_get_request("/api/v1/contest/questionSets", Some(token));
+        let res = oneshot_request::<QuestionSetsRes>(app, req, Some(StatusCode::OK)).await;
+        assert_eq!(res.question_sets.len(), 1);
+        assert_eq!(res.question_sets[0].question_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_create_private_contest_handler() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 7;
+        let question_set_id = ObjectId::new();
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_question_set()
+            .once()
+            .with(function(|_: &DbClient| true), eq(question_set_id))
+            .returning(move |_, _| Ok(Some(question_set(question_set_id, true))));
+        state
+            .get_mut_validators()
+            .expect_validate_contest()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(|_: &Helpers| true),
+                function(|contest: &Contest| contest.questions.len() == 3),
+            )
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_utility()
+            .expect_generate_referral_code()
+            .once()
+            .returning(|| "FREE1234".into());
+        state
+            .get_mut_db()
+            .expect_insert_one::<Contest>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTESTS),
+                function(move |contest: &Contest| {
+                    contest.title == "Friday night #FREE1234"
+                        && contest.invite_code.as_deref() == Some("FREE1234")
+                        && contest.visibility == ContestVisibility::Private
+                        && contest.status == ContestStatus::Published
+                        && contest.created_by == Some(user_id)
+                        && contest.commission_percent == PRIVATE_CONTEST_DEFAULT_COMMISSION_PERCENT
+                }),
+                function(Option::is_none),
+            )
+            .returning(move |_, _, _, _| Ok(InsertedId(contest_id.to_hex())));
+        let app = build_app_routes(Arc::new(state));
+        let body = create_req_body(question_set_id);
+        let req = build_post_request("/api/v1/contest/private", &body, Some(token));
+        let res = oneshot_request::<PrivateContestRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.contest_id, contest_id);
+        assert_eq!(res.invite_code, "FREE1234");
+        assert_eq!(res.end_ts, res.start_ts + 600);
+    }
+
+    #[tokio::test]
+    async fn test_create_private_contest_handler_unapproved_set() {
+        let token = "DUMMY_TOKEN";
+        let question_set_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 7);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_question_set()
+            .once()
+            .returning(move |_, _| Ok(Some(question_set(question_set_id, false))));
+        state.get_mut_validators().expect_validate_contest().never();
+        state.get_mut_db().expect_insert_one::<Contest>().never();
+        let app = build_app_routes(Arc::new(state));
+        let body = create_req_body(question_set_id);
+        let req = build_post_request("/api/v1/contest/private", &body, Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::NOT_FOUND)).await;
+        assert!(!res.success);
+    }
+
+    #[tokio::test]
+    async fn test_create_private_contest_handler_entry_fee_cap() {
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 7);
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_get_question_set()
+            .never();
+        let app = build_app_routes(Arc::new(state));
+        let body = json!({
+            "title": "Friday night",
+            "questionSetId": ObjectId::new().to_hex(),
+            "entryFee": PRIVATE_CONTEST_MAX_ENTRY_FEE + 1,
+            "maxPlayers": 5,
+            "startTs": get_epoch_ts() + 3600,
+            "durationSecs": 600
+        });
+        let req = build_post_request("/api/v1/contest/private", &body.to_string(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(!res.success);
+    }
+}
@@ -0,0 +1,635 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use futures::FutureExt;
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    config::{AppError, AppState, ValidatedParams},
+    constants::*,
+    import_double,
+    models::*,
+    utils::get_epoch_ts,
+};
+
+use super::join::{join_entry, next_entry_no, publish_participant_count};
+
+import_double!(DbSession);
+
+/// Join waitlist
+///
+/// Wait for a spot in a full contest. When an entry is withdrawn, the waiting users
+/// are joined in the order of joining the waitlist by paying the entry fee.
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/{contest_id}/waitlist",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Waitlist joined", body = WaitlistRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn join_waitlist_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+) -> Result<Json<WaitlistRes>, AppError> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    let contest = get_contest(&state, &contest_id).await?;
+    if !contest.waitlist {
+        let err = "Waitlist is not enabled for the contest";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    if !contest.is_joinable(get_epoch_ts()) {
+        let err = "Contest is not open for joining";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    if !contest.is_full() {
+        let err = "Contest is not full, join the contest instead";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    let play_trackers = contest_helpers
+        .get_user_play_trackers(db, &contest_id, claims.id)
+        .await?;
+    next_entry_no(&contest, &play_trackers)?;
+    let waiting = contest_helpers
+        .get_waitlist_entry(db, &contest_id, claims.id)
+        .await?;
+    let entry = WaitlistEntry::new(contest_id, claims.id);
+    if waiting.is_some() || !contest_helpers.insert_waitlist_entry(db, &entry).await? {
+        return Err(AppError::BadRequest("Already in the waitlist".into()));
+    }
+    let position = contest_helpers
+        .count_waiting_till(db, &contest_id, entry.created_ts.unwrap_or_default())
+        .await?;
+    let res = WaitlistRes {
+        success: true,
+        position,
+    };
+    Ok(Json(res))
+}
+
+/// Leave contest
+///
+/// Withdraw an entry before the contest starts, the entry fee is refunded
+/// and the spot is offered to the users in the waitlist
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/{contest_id}/leave",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        ("contest_id" = String, Path, description = "contest id"),
+        PlayEntryParams,
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Entry withdrawn", body = GenericResponse),
+    ),
+    tag = "App User API"
+)]
+pub async fn leave_contest_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    Path(contest_id): Path<ObjectId>,
+    ValidatedParams(params): ValidatedParams<PlayEntryParams>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    let contest = get_contest(&state, &contest_id).await?;
+    if !contest.is_joinable(get_epoch_ts()) {
+        let err = "Entry can be withdrawn only before the contest starts";
+        return Err(AppError::BadRequest(err.into()));
+    }
+    let play_tracker = contest_helpers
+        .get_play_tracker(db, &contest_id, claims.id, params.entry_no())
+        .await?
+        .ok_or(AppError::NotFound("Contest not joined".into()))?;
+    if play_tracker.status == PlayTrackerStatus::Withdrawn {
+        return Err(withdrawn_error());
+    }
+    let cloned_state = state.clone();
+    let cloned_contest = contest.clone();
+    db.execute_transaction(None, None, move |session| {
+        let cloned_state = cloned_state.clone();
+        let contest = cloned_contest.clone();
+        let play_tracker = play_tracker.clone();
+        async move {
+            withdraw_entry(&cloned_state, session, &contest, &play_tracker).await?;
+            Ok(())
+        }
+        .boxed()
+    })
+    .await
+    .map_err(AppError::from_db_error)?;
+    if contest.waitlist {
+        if let Err(e) = promote_waitlist(&state, &contest_id).await {
+            tracing::error!(
+                "Not able to promote waitlist of contest {}: {:?}",
+                contest_id,
+                e
+            );
+        }
+    }
+    if let Some(contest) = contest_helpers.get_contest(db, &contest_id).await? {
+        publish_participant_count(&state, &contest);
+    }
+    Ok(GenericResponse::json_response(true, "Entry withdrawn"))
+}
+
+async fn get_contest(state: &AppState, contest_id: &ObjectId) -> Result<Contest, AppError> {
+    state
+        .helpers()
+        .contest_helpers()
+        .get_contest(state.db(), contest_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Contest {contest_id} not found"
+        )))
+}
+
+fn withdrawn_error() -> AppError {
+    AppError::BadRequest("Entry is already withdrawn".into())
+}
+
+/// Mark the entry withdrawn, free its spot in the contest and credit back the real
+/// and bonus split of the entry fee with the RefundContestEntryFee transaction.
+/// Wallet is not touched for the practice contests.
+async fn withdraw_entry(
+    state: &AppState,
+    session: &mut DbSession,
+    contest: &Contest,
+    play_tracker: &PlayTracker,
+) -> Result<(), AppError> {
+    let contest_id = play_tracker.contest_id;
+    let user_id = play_tracker.user_id;
+    let contest_helpers = state.helpers().contest_helpers();
+    let withdrawn = contest_helpers
+        .withdraw_play_session(session, &contest_id, user_id, play_tracker.entry_no)
+        .await?;
+    if !withdrawn {
+        return Err(withdrawn_error());
+    }
+    contest_helpers
+        .leave_contest_session(session, &contest_id)
+        .await?;
+    if contest.is_practice() {
+        return Ok(());
+    }
+    let entry_fee = play_tracker.entry_fee;
+    let (balance_before, balance_after) = state
+        .helpers()
+        .wallet_helpers()
+        .refund_wallet_with_session(session, user_id, entry_fee.real(), entry_fee.bonus())
+        .await?;
+    let transaction = WalletTransaction::refund_contest_entry_fee_trans(
+        user_id,
+        &contest_id.to_hex(),
+        entry_fee,
+        balance_before,
+        balance_after,
+    );
+    session
+        .insert_one_with_session(DB_NAME, COLL_WALLET_TRANSACTIONS, &transaction, None)
+        .await?;
+    Ok(())
+}
+
+/// Join the waiting users in the order of joining the waitlist while the contest has spots.
+/// Users who can't join, e.g. for insufficient balance, are marked failed and skipped.
+/// If the spot is taken by another join meanwhile or the join fails for a conflict or
+/// a database error, the user is put back to wait and retried by the contest status job.
+pub(crate) async fn promote_waitlist(
+    state: &Arc<AppState>,
+    contest_id: &ObjectId,
+) -> anyhow::Result<()> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    loop {
+        let Some(contest) = open_contest(state, contest_id).await? else {
+            return Ok(());
+        };
+        let Some(entry) = contest_helpers.claim_waitlist_entry(db, contest_id).await? else {
+            return Ok(());
+        };
+        let entry_id = entry
+            .id
+            .ok_or(anyhow::anyhow!("Waitlist entry id is missing"))?;
+        let (status, reason) = match join_entry(state, entry.user_id, &contest).await {
+            Ok((entry_no, _)) => {
+                if let Err(e) = notify_promoted(state, &contest, entry.user_id, entry_no).await {
+                    tracing::error!("Not able to notify user {}: {:?}", entry.user_id, e);
+                }
+                (WaitlistStatus::Promoted, None)
+            }
+            Err(_) if open_contest(state, contest_id).await?.is_none() => {
+                (WaitlistStatus::Waiting, None)
+            }
+            Err(AppError::BadRequest(msg)) => (WaitlistStatus::Failed, Some(msg)),
+            Err(e) => {
+                tracing::error!(
+                    "Not able to promote user {} in contest {}: {:?}",
+                    entry.user_id,
+                    contest_id,
+                    e
+                );
+                (WaitlistStatus::Waiting, None)
+            }
+        };
+        contest_helpers
+            .set_waitlist_status(db, &entry_id, &status, reason)
+            .await?;
+        if status == WaitlistStatus::Waiting {
+            return Ok(());
+        }
+    }
+}
+
+/// Contest if it's still open for joining and has a spot
+async fn open_contest(state: &AppState, contest_id: &ObjectId) -> anyhow::Result<Option<Contest>> {
+    let contest = state
+        .helpers()
+        .contest_helpers()
+        .get_contest(state.db(), contest_id)
+        .await?
+        .filter(|c| c.is_joinable(get_epoch_ts()) && !c.is_full());
+    Ok(contest)
+}
+
+async fn notify_promoted(
+    state: &AppState,
+    contest: &Contest,
+    user_id: u32,
+    entry_no: u32,
+) -> anyhow::Result<()> {
+    let data = HashMap::from([
+        ("contestTitle".to_string(), contest.title.clone()),
+        ("entryNo".to_string(), entry_no.to_string()),
+    ]);
+    let notification = NotificationRequest::new(user_id, EVENT_WAITLIST_PROMOTED, data);
+    state
+        .db()
+        .insert_one(DB_NAME, COLL_NOTIFICATION_REQUESTS, &notification, None)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        config::{build_app_routes, database::InsertedId},
+        utils::test_helper::{build_post_request, mock_user_token, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn waitlist_contest(contest_id: ObjectId, joined_players: u32) -> Contest {
+        Contest {
+            id: Some(contest_id),
+            entry_fee: 50,
+            max_players: 10,
+            joined_players,
+            waitlist: true,
+            start_ts: get_epoch_ts() + 100,
+            status: ContestStatus::Published,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_join_waitlist_handler() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| Ok(Some(waitlist_contest(contest_id, 10))));
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        contest_helpers
+            .expect_get_waitlist_entry()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id), eq(user_id))
+            .returning(|_, _, _| Ok(None));
+        contest_helpers
+            .expect_insert_waitlist_entry()
+            .once()
+            .withf(move |_, entry| {
+                entry.contest_id == contest_id
+                    && entry.user_id == user_id
+                    && entry.status == WaitlistStatus::Waiting
+            })
+            .returning(|_, _| Ok(true));
+        contest_helpers
+            .expect_count_waiting_till()
+            .once()
+            .returning(|_, _, _| Ok(3));
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/waitlist");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<WaitlistRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.position, 3);
+    }
+
+    #[tokio::test]
+    async fn test_join_waitlist_handler_not_full() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 10);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| Ok(Some(waitlist_contest(contest_id, 9))));
+        contest_helpers.expect_insert_waitlist_entry().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/waitlist");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(res.message, "Contest is not full, join the contest instead");
+    }
+
+    #[tokio::test]
+    async fn test_leave_contest_handler() {
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, user_id);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        let mut seq = mockall::Sequence::new();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move |_, _| {
+                let mut contest = waitlist_contest(contest_id, 5);
+                contest.waitlist = false;
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_get_play_tracker()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(user_id),
+                eq(2),
+            )
+            .returning(move |_, _, _, _| {
+                let mut play_tracker = PlayTracker::new(contest_id, user_id, Money::new(50, 0));
+                play_tracker.entry_no = 2;
+                Ok(Some(play_tracker))
+            });
+        contest_helpers.expect_claim_waitlist_entry().never();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move |_, _| Ok(Some(waitlist_contest(contest_id, 4))));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .return_once(|_, _, _| Ok(()));
+        let state = Arc::new(state);
+        let mut events = state.contest_events().subscribe();
+        let app = build_app_routes(state);
+        let path = format!("/api/v1/contest/{contest_id}/leave?entryNo=2");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        let event = events.try_recv().unwrap();
+        assert_eq!(
+            event.update,
+            ContestUpdate::ParticipantCount(ParticipantCount { joined_players: 4 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_leave_contest_handler_withdrawn() {
+        let token = "DUMMY_TOKEN";
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        mock_user_token(&mut state, token, 10);
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .returning(move |_, _| Ok(Some(waitlist_contest(contest_id, 5))));
+        contest_helpers
+            .expect_get_play_tracker()
+            .once()
+            .returning(move |_, _, _, _| {
+                let mut play_tracker = PlayTracker::new(contest_id, 10, Money::new(50, 0));
+                play_tracker.status = PlayTrackerStatus::Withdrawn;
+                Ok(Some(play_tracker))
+            });
+        state.get_mut_db().expect_execute_transaction().never();
+        let app = build_app_routes(Arc::new(state));
+        let path = format!("/api/v1/contest/{contest_id}/leave");
+        let req = build_post_request(&path, "", Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(res.message, "Entry is already withdrawn");
+    }
+
+    #[tokio::test]
+    async fn test_promote_waitlist() {
+        let contest_id = ObjectId::new();
+        let entry_id = ObjectId::new();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        let mut seq = mockall::Sequence::new();
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move |_, _| Ok(Some(waitlist_contest(contest_id, 9))));
+        contest_helpers
+            .expect_get_contest()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move |_, _| Ok(Some(waitlist_contest(contest_id, 10))));
+        contest_helpers
+            .expect_claim_waitlist_entry()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(move |_, _| {
+                let mut entry = WaitlistEntry::new(contest_id, 20);
+                entry.id = Some(entry_id);
+                entry.status = WaitlistStatus::Promoting;
+                Ok(Some(entry))
+            });
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        contest_helpers
+            .expect_set_waitlist_status()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(entry_id),
+                eq(WaitlistStatus::Promoted),
+                eq(None),
+            )
+            .returning(|_, _, _, _| Ok(()));
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_user_balance()
+            .once()
+            .with(function(|_: &DbClient| true), eq(20))
+            .returning(|_, _| Ok(Money::new(100, 0)));
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_validators()
+            .expect_validate_contest_spend_limits()
+            .once()
+            .returning(|_, _, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .return_once(|_, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_insert_one::<NotificationRequest>()
+            .once()
+            .withf(|_, coll, notification, _| {
+                coll == COLL_NOTIFICATION_REQUESTS && notification.user_id == 20
+            })
+            .returning(|_, _, _, _| Ok(InsertedId(ObjectId::new().to_hex())));
+        let result = promote_waitlist(&Arc::new(state), &contest_id).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_promote_waitlist_join_failed() {
+        let contest_id = ObjectId::new();
+        let entry_id = ObjectId::new();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .times(3)
+            .returning(move |_, _| Ok(Some(waitlist_contest(contest_id, 9))));
+        let mut seq = mockall::Sequence::new();
+        contest_helpers
+            .expect_claim_waitlist_entry()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move |_, _| {
+                let mut entry = WaitlistEntry::new(contest_id, 20);
+                entry.id = Some(entry_id);
+                Ok(Some(entry))
+            });
+        contest_helpers
+            .expect_claim_waitlist_entry()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(None));
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        contest_helpers
+            .expect_set_waitlist_status()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(entry_id),
+                eq(WaitlistStatus::Failed),
+                eq(Some(
+                    "Insufficient balance to pay the entry fee".to_string(),
+                )),
+            )
+            .returning(|_, _, _, _| Ok(()));
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_user_balance()
+            .once()
+            .returning(|_, _| Ok(Money::new(10, 0)));
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        state.get_mut_db().expect_execute_transaction().never();
+        let result = promote_waitlist(&Arc::new(state), &contest_id).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_promote_waitlist_join_error() {
+        let contest_id = ObjectId::new();
+        let entry_id = ObjectId::new();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contest()
+            .times(2)
+            .returning(move |_, _| Ok(Some(waitlist_contest(contest_id, 9))));
+        contest_helpers
+            .expect_claim_waitlist_entry()
+            .once()
+            .returning(move |_, _| {
+                let mut entry = WaitlistEntry::new(contest_id, 20);
+                entry.id = Some(entry_id);
+                Ok(Some(entry))
+            });
+        contest_helpers
+            .expect_get_user_play_trackers()
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        contest_helpers
+            .expect_set_waitlist_status()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(entry_id),
+                eq(WaitlistStatus::Waiting),
+                eq(None),
+            )
+            .returning(|_, _, _, _| Ok(()));
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_user_balance()
+            .once()
+            .returning(|_, _| Err(anyhow::anyhow!("connection reset")));
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        state.get_mut_db().expect_execute_transaction().never();
+        let result = promote_waitlist(&Arc::new(state), &contest_id).await;
+        assert!(result.is_ok());
+    }
+}
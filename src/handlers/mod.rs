@@ -1,14 +1,20 @@
+pub(crate) mod admin;
+pub(crate) mod contest;
 pub(crate) mod default_route;
 pub(crate) mod global_404;
 pub(crate) mod ping;
+pub(crate) mod user;
 pub(crate) mod wallet;
 
 #[cfg(test)]
 mod extra_test_routes;
 
+pub use admin::admin_routes;
+pub use contest::{contest_routes, list::list_public_contests_handler};
 pub use default_route::default_route_handler;
 pub use global_404::global_404_handler;
 pub use ping::*;
+pub use user::user_routes;
 pub use wallet::wallet_routes;
 
 #[cfg(test)]
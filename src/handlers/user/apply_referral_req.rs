@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+
+use crate::{
+    config::{AppError, AppState},
+    models::ApplyReferralReq,
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for ApplyReferralReq {
+    async fn validate_extra(
+        &self,
+        state: Arc<AppState>,
+        user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        let user_id = user_id.unwrap_or_default();
+        state
+            .validators()
+            .validate_referral_code(state.db(), state.helpers(), user_id, &self.referral_code)
+            .await?;
+        Ok(())
+    }
+}
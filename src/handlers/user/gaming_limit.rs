@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Extension, Json};
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody},
+    models::*,
+    utils::get_epoch_ts,
+};
+
+/// Get gaming limits
+///
+/// Get deposit and contest spend limits of the user along with self exclusion
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/gamingLimits",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Gaming limits of the user", body = GamingLimitsRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn get_gaming_limits_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+) -> Result<Json<GamingLimitsRes>, AppError> {
+    let gaming_limits = state
+        .helpers()
+        .gaming_limit_helpers()
+        .get_gaming_limits(state.db(), claims.id)
+        .await?;
+    let res = GamingLimitsRes {
+        success: true,
+        gaming_limits,
+    };
+    Ok(Json(res))
+}
+
+/// Update gaming limits
+///
+/// Update daily, weekly and monthly deposit and contest spend limits.
+/// A lowered limit applies immediately whereas a raised or removed limit
+/// applies after the cooling off period.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/gamingLimits",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = UpdateGamingLimitsReq,
+    responses(
+        (status = StatusCode::OK, description = "Gaming limits updated", body = GamingLimitsRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn update_gaming_limits_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedBody(body): ValidatedBody<UpdateGamingLimitsReq>,
+) -> Result<Json<GamingLimitsRes>, AppError> {
+    let gaming_limit_helpers = state.helpers().gaming_limit_helpers();
+    let mut gaming_limits = gaming_limit_helpers
+        .get_gaming_limits(state.db(), claims.id)
+        .await?;
+    let ts = get_epoch_ts();
+    if let Some(deposit) = &body.deposit {
+        gaming_limits.deposit.update(deposit, ts);
+    }
+    if let Some(contest_spend) = &body.contest_spend {
+        gaming_limits.contest_spend.update(contest_spend, ts);
+    }
+    let saved = gaming_limit_helpers
+        .save_gaming_limits(state.db(), &gaming_limits)
+        .await?;
+    if !saved {
+        let msg = "Gaming limits are modified by another request, please retry";
+        return Err(AppError::Conflict(msg.into()));
+    }
+    gaming_limits.version += 1;
+    let res = GamingLimitsRes {
+        success: true,
+        gaming_limits,
+    };
+    Ok(Json(res))
+}
+
+/// Self exclusion
+///
+/// Exclude self from joining contests and adding balance for the given number of days.
+/// An existing self exclusion can only be extended.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/selfExclusion",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = SelfExclusionReq,
+    responses(
+        (status = StatusCode::OK, description = "Self exclusion applied", body = GenericResponse),
+    ),
+    tag = "App User API"
+)]
+pub async fn self_exclusion_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedBody(body): ValidatedBody<SelfExclusionReq>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let till = get_epoch_ts() + body.days * 24 * 60 * 60;
+    state
+        .helpers()
+        .gaming_limit_helpers()
+        .self_exclude(state.db(), claims.id, till)
+        .await?;
+    Ok(GenericResponse::json_response(
+        true,
+        "Self exclusion applied",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        config::build_app_routes,
+        constants::*,
+        import_double,
+        utils::test_helper::{build_post_request, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update_gaming_limits_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        let gaming_limit_helpers = state.get_mut_helpers().mut_gaming_limit_helpers();
+        gaming_limit_helpers
+            .expect_get_gaming_limits()
+            .once()
+            .with(function(|_: &DbClient| true), eq(user_id))
+            .returning(move |_, _| {
+                let mut gaming_limits = GamingLimits {
+                    user_id,
+                    version: 2,
+                    ..Default::default()
+                };
+                gaming_limits.deposit.daily.amount = Some(100);
+                gaming_limits.deposit.weekly.amount = Some(500);
+                Ok(gaming_limits)
+            });
+        gaming_limit_helpers
+            .expect_save_gaming_limits()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(|l: &GamingLimits| l.user_id == 10 && l.version == 2),
+            )
+            .returning(|_, _| Ok(true));
+        let app = build_app_routes(Arc::new(state));
+        let body =
+            r#"{"deposit": {"daily": 50, "weekly": 1000}, "contestSpend": {"monthly": 200}}"#;
+        let req = build_post_request("/api/v1/user/gamingLimits", body, Some(token));
+        let res = oneshot_request::<GamingLimitsRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        let deposit = res.gaming_limits.deposit;
+        assert_eq!(deposit.daily.amount, Some(50));
+        assert_eq!(deposit.daily.pending, None);
+        assert_eq!(deposit.weekly.amount, Some(500));
+        let pending = deposit.weekly.pending.unwrap();
+        assert_eq!(pending.amount, Some(1000));
+        assert!(pending.effective_ts >= ts + LIMIT_RAISE_COOLING_OFF_SECS);
+        assert_eq!(res.gaming_limits.contest_spend.monthly.amount, Some(200));
+        assert_eq!(res.gaming_limits.version, 3);
+    }
+
+    #[tokio::test]
+    async fn test_update_gaming_limits_handler_conflict() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        let gaming_limit_helpers = state.get_mut_helpers().mut_gaming_limit_helpers();
+        gaming_limit_helpers
+            .expect_get_gaming_limits()
+            .once()
+            .returning(move |_, _| {
+                let mut gaming_limits = GamingLimits {
+                    user_id,
+                    ..Default::default()
+                };
+                gaming_limits.deposit.daily.amount = Some(100);
+                Ok(gaming_limits)
+            });
+        // limits updated by another request after read, the raise is not saved
+        gaming_limit_helpers
+            .expect_save_gaming_limits()
+            .once()
+            .returning(|_, _| Ok(false));
+        let app = build_app_routes(Arc::new(state));
+        let body = r#"{"deposit": {"daily": 1000}}"#;
+        let req = build_post_request("/api/v1/user/gamingLimits", body, Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::CONFLICT)).await;
+        assert!(!res.success);
+    }
+
+    #[tokio::test]
+    async fn test_self_exclusion_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .times(2)
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        state
+            .get_mut_helpers()
+            .mut_gaming_limit_helpers()
+            .expect_self_exclude()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(user_id),
+                function(move |till: &u64| *till >= ts + 7 * 24 * 60 * 60),
+            )
+            .returning(|_, _, _| Ok(()));
+        let state = Arc::new(state);
+
+        // invalid number of days
+        let app = build_app_routes(state.clone());
+        let req = build_post_request("/api/v1/user/selfExclusion", r#"{"days": 0}"#, Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(!res.success);
+
+        let app = build_app_routes(state);
+        let req = build_post_request("/api/v1/user/selfExclusion", r#"{"days": 7}"#, Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+    }
+}
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    routing::{get, post},
+    Router,
+};
+
+use crate::config::AppState;
+
+mod apply_referral_req;
+pub(crate) mod gaming_limit;
+pub(crate) mod referral;
+
+use gaming_limit::*;
+use referral::*;
+
+pub fn user_routes() -> Router<Arc<AppState>, Body> {
+    Router::new()
+        .route("/referralCode", get(get_referral_code_handler))
+        .route("/applyReferralCode", post(apply_referral_code_handler))
+        .route(
+            "/gamingLimits",
+            get(get_gaming_limits_handler).post(update_gaming_limits_handler),
+        )
+        .route("/selfExclusion", post(self_exclusion_handler))
+}
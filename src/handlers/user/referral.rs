@@ -0,0 +1,402 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Extension, Json};
+use futures::FutureExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+};
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody},
+    constants::*,
+    import_double,
+    models::*,
+    utils::get_epoch_ts,
+};
+
+import_double!(DbSession);
+
+/// Get referral code
+///
+/// Get referral code of the user, a new code is generated if not exists
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/referralCode",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Referral code of the user", body = ReferralCodeRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn get_referral_code_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+) -> Result<Json<ReferralCodeRes>, AppError> {
+    let referral_code = state
+        .helpers()
+        .user_helpers()
+        .get_or_create_referral_code(state.db(), claims.id)
+        .await?;
+    let res = ReferralCodeRes {
+        success: true,
+        referral_code,
+    };
+    Ok(Json(res))
+}
+
+/// Apply referral code
+///
+/// Apply referral code of another user and credit referral bonus to both users
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/applyReferralCode",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = ApplyReferralReq,
+    responses(
+        (status = StatusCode::OK, description = "Referral code applied", body = GenericResponse),
+    ),
+    tag = "App User API"
+)]
+pub async fn apply_referral_code_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedBody(body): ValidatedBody<ApplyReferralReq>,
+) -> Result<Json<GenericResponse>, AppError> {
+    let db = state.db();
+    let cloned_state = state.clone();
+    let user_id = claims.id;
+    let referral_code = body.referral_code;
+    db.execute_transaction(None, None, move |session| {
+        let cloned_state = cloned_state.clone();
+        let referral_code = referral_code.clone();
+        async move {
+            apply_referral_code(&cloned_state, session, user_id, &referral_code).await?;
+            Ok(())
+        }
+        .boxed()
+    })
+    .await
+    .map_err(AppError::from_db_error)?;
+    Ok(GenericResponse::json_response(
+        true,
+        "Referral code applied successfully",
+    ))
+}
+
+async fn apply_referral_code(
+    state: &AppState,
+    session: &mut DbSession,
+    user_id: u32,
+    referral_code: &str,
+) -> Result<(), AppError> {
+    let filter = doc! {"referralCode": referral_code};
+    let referrer = session
+        .find_one_with_session::<User>(DB_NAME, COLL_USERS, Some(filter), None)
+        .await?;
+    match referrer {
+        Some(referrer) => apply_user_referral_code(state, session, user_id, &referrer).await,
+        None => apply_special_referral_code(state, session, user_id, referral_code).await,
+    }
+}
+
+async fn apply_user_referral_code(
+    state: &AppState,
+    session: &mut DbSession,
+    user_id: u32,
+    referrer: &User,
+) -> Result<(), AppError> {
+    if referrer.id == user_id {
+        let err = AppError::BadRequest("User can not use own referral code".into());
+        return Err(err);
+    }
+    let referral_code = referrer.referral_code.as_deref().unwrap_or_default();
+    mark_referral_code_used(session, user_id, referral_code, Some(referrer.id)).await?;
+    credit_referral_bonus(state, session, user_id, REFERRAL_BONUS).await?;
+    let wallet_helpers = state.helpers().wallet_helpers();
+    let (balance_before, balance_after) = wallet_helpers
+        .update_wallet_with_session(session, referrer.id, 0, REFERRER_BONUS, false, false)
+        .await?;
+    let transaction = WalletTransaction::referrer_bonus_trans(
+        referrer.id,
+        balance_before,
+        balance_after,
+        user_id,
+    );
+    session
+        .insert_one_with_session(DB_NAME, COLL_WALLET_TRANSACTIONS, &transaction, None)
+        .await?;
+    Ok(())
+}
+
+async fn apply_special_referral_code(
+    state: &AppState,
+    session: &mut DbSession,
+    user_id: u32,
+    referral_code: &str,
+) -> Result<(), AppError> {
+    let ts = get_epoch_ts() as i64;
+    // count the redemption only if the code is still valid and
+    // maximum redemptions limit is not reached yet
+    let filter = doc! {
+        "referralCode": referral_code,
+        "isActive": true,
+        "validFrom": {"$lte": ts},
+        "validTill": {"$gte": ts},
+        "$or": [
+            {"maxRedemptions": null},
+            {"$expr": {"$lt": ["$redemptionCount", "$maxRedemptions"]}}
+        ]
+    };
+    let update = doc! {
+        "$inc": {"redemptionCount": 1},
+        "$set": {"updatedTs": ts}
+    };
+    let options = FindOneAndUpdateOptions::builder()
+        .return_document(Some(ReturnDocument::After))
+        .build();
+    let special_referral_code = session
+        .find_one_and_update_with_session::<SpecialReferralCode, Document>(
+            DB_NAME,
+            COLL_SPECIAL_REFERRAL_CODES,
+            filter,
+            update,
+            Some(options),
+        )
+        .await?
+        .ok_or(AppError::BadRequest(
+            "Referral code is either invalid or fully redeemed".into(),
+        ))?;
+    // the target segment may have been changed after the validation, check the
+    // eligibility again so the redemption is rolled back for the ineligible user
+    let filter = doc! {"id": user_id};
+    let user = session
+        .find_one_with_session::<User>(DB_NAME, COLL_USERS, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("User not found".into()))?;
+    if !special_referral_code.is_eligible(&user) {
+        let err = AppError::BadRequest("User is not eligible for this referral code".into());
+        return Err(err);
+    }
+    mark_referral_code_used(session, user_id, referral_code, None).await?;
+    credit_referral_bonus(state, session, user_id, special_referral_code.bonus).await?;
+    Ok(())
+}
+
+/// Mark the referral code as used only if the user has not used any code yet,
+/// this way the same user can not get referral bonus twice
+async fn mark_referral_code_used(
+    session: &mut DbSession,
+    user_id: u32,
+    referral_code: &str,
+    referred_by: Option<u32>,
+) -> Result<(), AppError> {
+    let ts = get_epoch_ts() as i64;
+    let filter = doc! {"id": user_id, "hasUsedReferralCode": {"$ne": true}};
+    let update = doc! {
+        "$set": {
+            "hasUsedReferralCode": true,
+            "usedReferralCode": referral_code,
+            "usedReferralCodeTs": ts,
+            "referredBy": referred_by,
+            "updatedTs": ts
+        }
+    };
+    let result = session
+        .update_one_with_session(DB_NAME, COLL_USERS, filter, update, None)
+        .await?;
+    if result.matched_count == 0 {
+        let err = AppError::BadRequest("User has already used a referral code".into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+async fn credit_referral_bonus(
+    state: &AppState,
+    session: &mut DbSession,
+    user_id: u32,
+    bonus: u64,
+) -> Result<(), AppError> {
+    let (balance_before, balance_after) = state
+        .helpers()
+        .wallet_helpers()
+        .update_wallet_with_session(session, user_id, 0, bonus, false, false)
+        .await?;
+    let transaction =
+        WalletTransaction::referral_bonus_trans(user_id, bonus, balance_before, balance_after);
+    session
+        .insert_one_with_session(DB_NAME, COLL_WALLET_TRANSACTIONS, &transaction, None)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{always, eq, function};
+    use serde_json::json;
+
+    use crate::{
+        config::build_app_routes,
+        helpers::Helpers,
+        import_double,
+        utils::{
+            get_epoch_ts,
+            test_helper::{build_get_request, build_post_request, oneshot_request},
+        },
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_referral_code_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        state
+            .get_mut_helpers()
+            .mut_user_helpers()
+            .expect_get_or_create_referral_code()
+            .once()
+            .with(function(|_: &DbClient| true), eq(user_id))
+            .returning(|_, _| Ok("ABCD1234".to_owned()));
+        let app = build_app_routes(Arc::new(state));
+        let req = build_get_request("/api/v1/user/referralCode", Some(token));
+        let res = oneshot_request::<ReferralCodeRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.referral_code, "ABCD1234".to_owned());
+    }
+
+    #[tokio::test]
+    async fn test_apply_referral_code_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let referral_code = "ABCD1234";
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        state
+            .get_mut_validators()
+            .expect_validate_referral_code()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(|_: &Helpers| true),
+                eq(user_id),
+                eq(referral_code),
+            )
+            .return_once(|_, _, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .with(
+                function(Option::is_none),
+                function(Option::is_none),
+                always(),
+            )
+            .return_once(|_, _, _| Ok(()));
+        let app = build_app_routes(Arc::new(state));
+        let path = "/api/v1/user/applyReferralCode";
+        let body = json!({ "referralCode": referral_code });
+        let req = build_post_request(path, body.to_string().as_str(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+
+        // error raised inside the transaction is returned as is
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        state
+            .get_mut_validators()
+            .expect_validate_referral_code()
+            .once()
+            .return_once(|_, _, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .return_once(|_, _, _| {
+                let err = AppError::BadRequest("User has already used a referral code".into());
+                Err(err.into())
+            });
+        let app = build_app_routes(Arc::new(state));
+        let req = build_post_request(path, body.to_string().as_str(), Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(!res.success);
+        assert_eq!(
+            res.message,
+            "User has already used a referral code".to_owned()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_special_referral_code_segment_changed() {
+        let user_id = 10;
+        let referral_code = "PARTNER50";
+        let state = AppState::mock();
+        let mut session = DbSession::default();
+        // target segment is changed to facebook users after the validation
+        session
+            .expect_find_one_and_update_with_session::<SpecialReferralCode, Document>()
+            .once()
+            .withf(move |_, _, filter, _, _| filter.get_str("referralCode") == Ok(referral_code))
+            .returning(|_, _, _, _, _| {
+                let special_referral_code = SpecialReferralCode {
+                    referral_code: "PARTNER50".into(),
+                    bonus: 50,
+                    target_segment: Some(ReferralTargetSegment {
+                        login_scheme: Some(LoginScheme::Facebook),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                };
+                Ok(Some(special_referral_code))
+            });
+        session
+            .expect_find_one_with_session::<User>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_USERS),
+                eq(Some(doc! {"id": user_id})),
+                always(),
+            )
+            .returning(move |_, _, _, _| {
+                let user = User {
+                    id: user_id,
+                    login_scheme: LoginScheme::Google,
+                    ..Default::default()
+                };
+                Ok(Some(user))
+            });
+        session.expect_update_one_with_session().never();
+        let result = apply_special_referral_code(&state, &mut session, user_id, referral_code);
+        match result.await {
+            Err(AppError::BadRequest(e)) => {
+                assert_eq!(e, "User is not eligible for this referral code")
+            }
+            _ => panic!(),
+        };
+    }
+}
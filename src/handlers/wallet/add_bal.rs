@@ -2,14 +2,20 @@ use std::sync::Arc;
 
 use axum::{async_trait, extract::State, Extension, Json};
 use futures::FutureExt;
-use mongodb::bson::doc;
+use mongodb::{
+    bson::{doc, oid::ObjectId, to_bson},
+    error::Result as MongoResult,
+};
 
 use crate::{
     config::{AppError, AppState, ValidatedBody},
+    import_double,
     models::*,
     validators::ValidateExtra,
 };
 
+import_double!(DbSession);
+
 /// Add balance initialize
 ///
 /// Initialize add balance transaction
@@ -31,9 +37,8 @@ pub async fn add_bal_init_handler(
 ) -> Result<Json<AddBalInitRes>, AppError> {
     let app_upi_id = std::env::var("APP_UPI_ID")?;
     let wallet_helpers = state.helpers().wallet_helpers();
-    let balance_before = wallet_helpers
-        .get_user_balance(state.db(), claims.id)
-        .await?;
+    let wallet = wallet_helpers.get_wallet(state.db(), claims.id).await?;
+    let balance_before = wallet.map(|w| w.balance()).unwrap_or_default();
     let transaction = WalletTransaction::add_bal_init_trans(claims.id, body.amount, balance_before);
     let transaction_id = wallet_helpers
         .insert_wallet_transaction(state.db(), &transaction)
@@ -79,6 +84,25 @@ async fn handle_success_transaction(
     body: &AddBalEndReq,
 ) -> Result<(), AppError> {
     let db = state.db();
+    // user may self exclude while paying, the payment is not credited then
+    let excluded = state
+        .validators()
+        .validate_not_self_excluded(db, state.helpers(), user_id)
+        .await;
+    if let Err(AppError::BadRequest(reason)) = &excluded {
+        state
+            .helpers()
+            .wallet_helpers()
+            .update_failed_transaction(
+                db,
+                user_id,
+                &body.transaction_id,
+                &Some(reason.to_owned()),
+                &body.tracking_id,
+            )
+            .await?;
+    }
+    excluded?;
     let cloned_state = state.clone();
     let transaction_id = body.transaction_id.clone();
     let tracking_id = body.tracking_id.clone();
@@ -90,25 +114,52 @@ async fn handle_success_transaction(
         let transaction_id = transaction_id.clone();
         let tracking_id = tracking_id.clone();
         async move {
-            let wallet_helpers = cloned_state.helpers().wallet_helpers();
-            let (_, balance_after) = wallet_helpers
-                .update_wallet_with_session(session, user_id, amount, 0, false, false)
-                .await?;
-            wallet_helpers
-                .update_wallet_transaction_session(
-                    session,
-                    &transaction_id,
-                    balance_after,
-                    &tracking_id,
-                )
-                .await?;
-
-            Ok(())
+            credit_add_balance(
+                &cloned_state,
+                session,
+                user_id,
+                &transaction_id,
+                &tracking_id,
+                amount,
+            )
+            .await
         }
         .boxed()
     })
-    .await?;
+    .await
+    .map_err(AppError::from_db_error)?;
+
+    Ok(())
+}
 
+/// Credit the paid amount to the wallet and complete the pending transaction.
+/// The wallet may be modified while the user is paying, so the credit is applied
+/// to the wallet as read in the session and not to the one at initialization.
+async fn credit_add_balance(
+    state: &AppState,
+    session: &mut DbSession,
+    user_id: u32,
+    transaction_id: &ObjectId,
+    tracking_id: &Option<String>,
+    amount: u64,
+) -> MongoResult<()> {
+    let wallet_helpers = state.helpers().wallet_helpers();
+    let pending_status = to_bson(&WalletTransactionStatus::Pending)?;
+    let filter = doc! {
+        "_id": transaction_id,
+        "userId": user_id,
+        "status": pending_status,
+    };
+    wallet_helpers
+        .get_wallet_transaction_session(session, filter)
+        .await?
+        .ok_or(AppError::NotFound("transaction not found".into()))?;
+    let (_, balance_after) = wallet_helpers
+        .update_wallet_with_session(session, user_id, amount, 0, false, false)
+        .await?;
+    wallet_helpers
+        .update_wallet_transaction_session(session, transaction_id, balance_after, tracking_id)
+        .await?;
     Ok(())
 }
 
@@ -193,6 +244,16 @@ mod tests {
                 }),
             )
             .return_once(|_, _, _, _| Ok(()));
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(|_: &Helpers| true),
+                eq(user_id),
+            )
+            .returning(|_, _, _| Ok(()));
         let state = Arc::new(state);
         let app = build_app_routes(state);
         let path = "/api/v1/wallet/addBalanceEnd";
@@ -203,6 +264,55 @@ mod tests {
         assert_eq!(res.message, "Updated successfully".to_owned());
     }
 
+    #[tokio::test]
+    async fn test_add_balance_end_handler_self_excluded() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let transaction_id = "6494fdba5155b267cb139995";
+        let reason = "User is self excluded till 01-01-2030 00:00";
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        state
+            .get_mut_validators()
+            .expect_validate_add_bal_transaction()
+            .once()
+            .return_once(|_, _, _, _| Ok(()));
+        // user self excluded after initializing, payment is not credited
+        state
+            .get_mut_validators()
+            .expect_validate_not_self_excluded()
+            .once()
+            .returning(move |_, _, _| Err(AppError::BadRequest(reason.into())));
+        state.get_mut_db().expect_execute_transaction().never();
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_update_failed_transaction()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(user_id),
+                eq(ObjectId::parse_str(transaction_id).unwrap()),
+                eq(Some(reason.to_owned())),
+                eq(None),
+            )
+            .return_once(|_, _, _, _, _| Ok(()));
+        let app = build_app_routes(Arc::new(state));
+        let path = "/api/v1/wallet/addBalanceEnd";
+        let body = json!({"amount": 10, "isSuccessful": true, "transactionId": transaction_id});
+        let body = build_post_request(path, body.to_string().as_str(), Some(token));
+        let res =
+            oneshot_request::<GenericResponse>(app, body, Some(StatusCode::BAD_REQUEST)).await;
+        assert!(!res.success);
+        assert_eq!(res.message, reason);
+    }
+
     #[tokio::test]
     async fn test_add_balance_end_handler_failed_transaction() {
         let ts = get_epoch_ts();
@@ -265,4 +375,52 @@ mod tests {
         assert_eq!(res.success, true);
         assert_eq!(res.message, "Updated successfully".to_owned());
     }
+    #[tokio::test]
+    async fn test_credit_add_balance_wallet_modified_after_init() {
+        let user_id = 10;
+        let transaction_id = ObjectId::new();
+        let tracking_id = Some("test tracking id".to_owned());
+        let mut state = AppState::mock();
+        let wallet_helpers = state.get_mut_helpers().mut_wallet_helpers();
+        // balance is 50 at initialization, the wallet is debited for a contest join
+        // while the user is paying
+        wallet_helpers
+            .expect_get_wallet_transaction_session()
+            .once()
+            .withf(move |_, filter| filter.get_object_id("_id") == Ok(transaction_id))
+            .returning(move |_, _| {
+                let transaction =
+                    WalletTransaction::add_bal_init_trans(user_id, 10, Money::new(50, 0));
+                Ok(Some(transaction))
+            });
+        wallet_helpers
+            .expect_update_wallet_version_session()
+            .never();
+        wallet_helpers
+            .expect_update_wallet_with_session()
+            .once()
+            .with(always(), eq(user_id), eq(10), eq(0), eq(false), eq(false))
+            .returning(|_, _, _, _, _, _| Ok((Money::new(30, 0), Money::new(40, 0))));
+        wallet_helpers
+            .expect_update_wallet_transaction_session()
+            .once()
+            .with(
+                always(),
+                eq(transaction_id),
+                eq(Money::new(40, 0)),
+                eq(tracking_id.clone()),
+            )
+            .returning(|_, _, _, _| Ok(()));
+        let mut session = DbSession::default();
+        let result = credit_add_balance(
+            &state,
+            &mut session,
+            user_id,
+            &transaction_id,
+            &tracking_id,
+            10,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
 }
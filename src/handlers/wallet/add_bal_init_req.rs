@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+
+use crate::{
+    config::{AppError, AppState},
+    models::AddBalInitReq,
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for AddBalInitReq {
+    async fn validate_extra(
+        &self,
+        state: Arc<AppState>,
+        user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        let user_id = user_id.unwrap_or_default();
+        state
+            .validators()
+            .validate_deposit_limits(state.db(), state.helpers(), user_id, self.amount)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+
+    use crate::{helpers::Helpers, import_double};
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn add_bal_init_req_validate_extra() {
+        let add_bal_init_req = AddBalInitReq { amount: 100 };
+        let user_id = 10;
+
+        // test validator return Ok
+        let mut state = AppState::mock();
+        state
+            .get_mut_validators()
+            .expect_validate_deposit_limits()
+            .with(
+                function(|_: &DbClient| true),
+                function(|_: &Helpers| true),
+                eq(user_id),
+                eq(100),
+            )
+            .once()
+            .return_once(|_, _, _, _| Ok(()));
+        let state = Arc::new(state);
+        let result = add_bal_init_req.validate_extra(state, Some(user_id)).await;
+        assert!(result.is_ok());
+
+        // test validator return Err
+        let mut state = AppState::mock();
+        state
+            .get_mut_validators()
+            .expect_validate_deposit_limits()
+            .once()
+            .return_once(|_, _, _, _| {
+                let err = "Deposit exceeds the daily limit of 50";
+                Err(AppError::BadRequest(err.into()))
+            });
+        let state = Arc::new(state);
+        let result = add_bal_init_req.validate_extra(state, Some(user_id)).await;
+        match result {
+            Err(AppError::BadRequest(s)) => assert_eq!(s, "Deposit exceeds the daily limit of 50"),
+            _ => panic!(),
+        };
+    }
+}
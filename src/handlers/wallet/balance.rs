@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Extension, Json};
+
+use crate::{
+    config::{AppError, AppState},
+    models::*,
+};
+
+/// Get wallet balance
+///
+/// Get wallet balance of the user along with upcoming bonus expiry
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/balance",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Wallet balance of the user", body = WalletBalanceRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn get_balance_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+) -> Result<Json<WalletBalanceRes>, AppError> {
+    let wallet_helpers = state.helpers().wallet_helpers();
+    let (balance, lots) = tokio::join!(
+        wallet_helpers.get_user_balance(state.db(), claims.id),
+        wallet_helpers.get_upcoming_bonus_expiry(state.db(), claims.id)
+    );
+    let upcoming_bonus_expiry = lots?.into_iter().map(BonusExpiry::from).collect();
+    let res = WalletBalanceRes {
+        success: true,
+        balance: balance?,
+        upcoming_bonus_expiry,
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::{
+            get_epoch_ts,
+            test_helper::{build_get_request, oneshot_request},
+        },
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_balance_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        let wallet_helpers = state.get_mut_helpers().mut_wallet_helpers();
+        wallet_helpers
+            .expect_get_user_balance()
+            .once()
+            .with(function(|_: &DbClient| true), eq(user_id))
+            .returning(|_, _| Ok(Money::new(20, 150)));
+        wallet_helpers
+            .expect_get_upcoming_bonus_expiry()
+            .once()
+            .with(function(|_: &DbClient| true), eq(user_id))
+            .returning(move |_, _| {
+                let lot = BonusLot {
+                    remaining: 100,
+                    expires_ts: ts + 10,
+                    ..Default::default()
+                };
+                Ok(vec![lot])
+            });
+        let app = build_app_routes(Arc::new(state));
+        let req = build_get_request("/api/v1/wallet/balance", Some(token));
+        let res = oneshot_request::<WalletBalanceRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.balance, Money::new(20, 150));
+        assert_eq!(res.upcoming_bonus_expiry.len(), 1);
+        assert_eq!(res.upcoming_bonus_expiry[0].amount, 100);
+        assert_eq!(res.upcoming_bonus_expiry[0].expires_ts, ts + 10);
+    }
+}
@@ -1,16 +1,34 @@
 use std::sync::Arc;
 
-use axum::{body::Body, routing::post, Router};
+use axum::{
+    body::Body,
+    routing::{get, post},
+    Router,
+};
 
 use crate::config::AppState;
 
 pub(crate) mod add_bal;
 mod add_bal_end_req;
+mod add_bal_init_req;
+pub(crate) mod balance;
+pub(crate) mod statement;
+pub(crate) mod tax_certificate;
+pub(crate) mod withdraw;
+mod withdraw_req;
 
 use add_bal::*;
+use balance::*;
+use statement::*;
+use tax_certificate::*;
+use withdraw::*;
 
 pub fn wallet_routes() -> Router<Arc<AppState>, Body> {
     Router::new()
+        .route("/balance", get(get_balance_handler))
+        .route("/statement", get(get_statement_handler))
         .route("/addBalanceInit", post(add_bal_init_handler))
         .route("/addBalanceEnd", post(add_bal_end_handler))
+        .route("/withdraw", post(withdraw_handler))
+        .route("/taxCertificate", get(get_tax_certificate_handler))
 }
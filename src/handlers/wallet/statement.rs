@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+    Extension,
+};
+
+use crate::{
+    config::{AppError, AppState, ValidatedParams},
+    constants::*,
+    models::*,
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for StatementParams {
+    async fn validate_extra(
+        &self,
+        _state: Arc<AppState>,
+        _user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        if self.to_ts <= self.from_ts {
+            let err = "toTs must be greater than fromTs";
+            return Err(AppError::BadRequest(err.into()));
+        }
+        if self.to_ts - self.from_ts > STATEMENT_MAX_RANGE_SECS {
+            let err = "Statement period can not be more than a year";
+            return Err(AppError::BadRequest(err.into()));
+        }
+        Ok(())
+    }
+}
+
+/// Get wallet statement
+///
+/// Get wallet statement of the user for a period as CSV or PDF
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/statement",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        StatementParams
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Wallet statement as CSV", content_type = "text/csv", body = String),
+        (status = StatusCode::OK, description = "Wallet statement as PDF", content_type = "application/pdf", body = Vec<u8>),
+    ),
+    tag = "App User API"
+)]
+pub async fn get_statement_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedParams(params): ValidatedParams<StatementParams>,
+) -> Result<Response, AppError> {
+    let db = state.db();
+    let wallet_helpers = state.helpers().wallet_helpers();
+    let user_ids = [claims.id];
+    let (last_balance, transactions) = tokio::join!(
+        wallet_helpers.get_last_balance_before(db, claims.id, params.from_ts),
+        wallet_helpers.get_statement_transactions(db, &user_ids, params.from_ts, params.to_ts)
+    );
+    let statement = WalletStatement::new(
+        claims.id,
+        params.from_ts,
+        params.to_ts,
+        last_balance?,
+        transactions?,
+    );
+    let format = params.format.unwrap_or_default();
+    let body = WalletStatement::render(&[statement], format);
+    let filename = format!(
+        "statement_{}_{}_{}",
+        claims.id, params.from_ts, params.to_ts
+    );
+    Ok(statement_response(format, &filename, body))
+}
+
+/// Build a downloadable file response for the statement
+pub fn statement_response(format: StatementFormat, filename: &str, body: Vec<u8>) -> Response {
+    let content_disposition = format!(
+        "attachment; filename=\"{}.{}\"",
+        filename,
+        format.extension()
+    );
+    let headers = [
+        (header::CONTENT_TYPE, format.content_type().to_owned()),
+        (header::CONTENT_DISPOSITION, content_disposition),
+    ];
+    (headers, body).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::{
+            get_epoch_ts,
+            test_helper::{build_get_request, oneshot_req_plain, oneshot_request},
+        },
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_statement_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        let wallet_helpers = state.get_mut_helpers().mut_wallet_helpers();
+        wallet_helpers
+            .expect_get_last_balance_before()
+            .once()
+            .with(function(|_: &DbClient| true), eq(user_id), eq(100))
+            .returning(|_, _, _| Ok(Money::new(5, 5)));
+        wallet_helpers
+            .expect_get_statement_transactions()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(move |user_ids: &[u32]| user_ids == [user_id]),
+                eq(100),
+                eq(200),
+            )
+            .returning(|_, _, _, _| Ok(vec![]));
+        let app = build_app_routes(Arc::new(state));
+        let path = "/api/v1/wallet/statement?fromTs=100&toTs=200";
+        let req = build_get_request(path, Some(token));
+        let res = oneshot_req_plain(app, req, Some(StatusCode::OK)).await;
+        let rows = res.lines().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].contains("OPENING_BALANCE,,,,,5,5,"));
+        assert!(rows[2].contains("CLOSING_BALANCE,,,,,5,5,"));
+    }
+
+    #[tokio::test]
+    async fn test_get_statement_handler_invalid_period() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .returning(move |_| Ok(JwtClaims::new(10, None, false, ts as usize)));
+        let app = build_app_routes(Arc::new(state));
+        let path = "/api/v1/wallet/statement?fromTs=200&toTs=100&format=pdf";
+        let req = build_get_request(path, Some(token));
+        let res = oneshot_request::<GenericResponse>(app, req, Some(StatusCode::BAD_REQUEST)).await;
+        assert_eq!(res.message, "toTs must be greater than fromTs");
+    }
+}
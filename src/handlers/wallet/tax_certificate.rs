@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::Response, Extension};
+
+use crate::{
+    config::{AppError, AppState, ValidatedParams},
+    models::*,
+    utils::financial_year_range_ts,
+};
+
+use super::statement::statement_response;
+
+/// Get tax certificate
+///
+/// Get annual tax certificate of the user with the TDS deducted in a financial year as CSV or PDF
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/taxCertificate",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        TaxCertificateParams
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Tax certificate as CSV", content_type = "text/csv", body = String),
+        (status = StatusCode::OK, description = "Tax certificate as PDF", content_type = "application/pdf", body = Vec<u8>),
+    ),
+    tag = "App User API"
+)]
+pub async fn get_tax_certificate_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedParams(params): ValidatedParams<TaxCertificateParams>,
+) -> Result<Response, AppError> {
+    let db = state.db();
+    let wallet_helpers = state.helpers().wallet_helpers();
+    let (from_ts, to_ts) = financial_year_range_ts(params.financial_year)?;
+    let (summary, deductions) = tokio::join!(
+        wallet_helpers.get_tax_summary(db, claims.id, from_ts, to_ts),
+        wallet_helpers.get_tds_transactions(db, claims.id, from_ts, to_ts)
+    );
+    let certificate = TaxCertificate {
+        user_id: claims.id,
+        financial_year: params.financial_year,
+        summary: summary?,
+        deductions: deductions?,
+    };
+    let format = params.format.unwrap_or_default();
+    let body = certificate.render(format);
+    let filename = format!(
+        "tax_certificate_{}_{}",
+        claims.id,
+        financial_year_label(params.financial_year)
+    );
+    Ok(statement_response(format, &filename, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        config::build_app_routes,
+        import_double,
+        utils::{
+            get_epoch_ts,
+            test_helper::{build_get_request, oneshot_req_plain},
+        },
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_tax_certificate_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        let (from_ts, to_ts) = financial_year_range_ts(2023).unwrap();
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        let wallet_helpers = state.get_mut_helpers().mut_wallet_helpers();
+        wallet_helpers
+            .expect_get_tax_summary()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(user_id),
+                eq(from_ts),
+                eq(to_ts),
+            )
+            .returning(|_, _, _, _| {
+                let summary = TaxSummary {
+                    total_winnings: 1000,
+                    taxed_winnings: 1000,
+                    total_tds: 300,
+                    ..Default::default()
+                };
+                Ok(summary)
+            });
+        wallet_helpers
+            .expect_get_tds_transactions()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(user_id),
+                eq(from_ts),
+                eq(to_ts),
+            )
+            .returning(|_, _, _, _| Ok(vec![]));
+        let app = build_app_routes(Arc::new(state));
+        let path = "/api/v1/wallet/taxCertificate?financialYear=2023";
+        let req = build_get_request(path, Some(token));
+        let res = oneshot_req_plain(app, req, Some(StatusCode::OK)).await;
+        let rows = res.lines().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[1], "10,2023-24,1000,0,0,1000,1000,300");
+    }
+}
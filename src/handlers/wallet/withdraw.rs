@@ -0,0 +1,247 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use axum::{extract::State, Extension, Json};
+use futures::FutureExt;
+
+use crate::{
+    config::{AppError, AppState, ValidatedBody},
+    constants::*,
+    import_double,
+    models::*,
+    utils::{financial_year_of, financial_year_range_ts, get_epoch_ts},
+};
+
+import_double!(DbSession);
+
+/// Withdraw balance
+///
+/// Withdraw balance from the withdrawable amount of the wallet.
+/// TDS on net winnings of the financial year is deducted from the withdrawal.
+#[utoipa::path(
+    post,
+    path = "/api/v1/wallet/withdraw",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = WithdrawReq,
+    responses(
+        (status = StatusCode::OK, description = "Withdrawal successful", body = WithdrawRes),
+    ),
+    tag = "App User API"
+)]
+pub async fn withdraw_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<JwtClaims>,
+    ValidatedBody(body): ValidatedBody<WithdrawReq>,
+) -> Result<Json<WithdrawRes>, AppError> {
+    let db = state.db();
+    let wallet_helpers = state.helpers().wallet_helpers();
+    let user_id = claims.id;
+    let amount = body.amount;
+    let upi_id = body.upi_id;
+    let cloned_state = state.clone();
+    let tds = Arc::new(AtomicU64::new(0));
+    let cloned_tds = tds.clone();
+    db.execute_transaction(None, None, move |session| {
+        let cloned_state = cloned_state.clone();
+        let cloned_tds = cloned_tds.clone();
+        let upi_id = upi_id.clone();
+        async move {
+            let deduction = withdraw(&cloned_state, session, user_id, amount, &upi_id).await?;
+            cloned_tds.store(deduction.tds, Ordering::Relaxed);
+            Ok(())
+        }
+        .boxed()
+    })
+    .await
+    .map_err(AppError::from_db_error)?;
+    let balance = wallet_helpers.get_user_balance(db, user_id).await?;
+    let tds = tds.load(Ordering::Relaxed);
+    let res = WithdrawRes {
+        success: true,
+        amount,
+        tds,
+        payout: amount - tds,
+        balance,
+    };
+    Ok(Json(res))
+}
+
+/// Deduct the amount from the wallet and record the TDS deduction
+/// and the withdrawal of the remaining amount as separate transactions.
+/// TDS is computed after the wallet is updated, so that the concurrent withdrawals
+/// are serialized and the same winnings are not taxed twice.
+async fn withdraw(
+    state: &AppState,
+    session: &mut DbSession,
+    user_id: u32,
+    amount: u64,
+    upi_id: &str,
+) -> Result<TdsDeduction, AppError> {
+    let wallet_helpers = state.helpers().wallet_helpers();
+    wallet_helpers
+        .check_wallet_freeze_session(session, user_id, WalletOperation::Withdraw)
+        .await?;
+    let (balance_before, balance_after) = wallet_helpers
+        .update_wallet_with_session(session, user_id, amount, 0, true, true)
+        .await?;
+    if balance_before.withdrawable() < amount {
+        let msg = format!(
+            "Insufficient withdrawable balance: {}",
+            balance_before.withdrawable()
+        );
+        return Err(AppError::BadRequest(msg));
+    }
+    let financial_year = financial_year_of(get_epoch_ts());
+    let (from_ts, to_ts) = financial_year_range_ts(financial_year)?;
+    let summary = wallet_helpers
+        .get_tax_summary_session(session, user_id, from_ts, to_ts)
+        .await?;
+    let deduction = TdsConfig::from_env().compute(financial_year, &summary, amount);
+    let mut transactions = vec![];
+    let mut payout_balance_before = balance_before;
+    if deduction.tds > 0 {
+        payout_balance_before = balance_before - Money::new(deduction.tds, 0);
+        let transaction = WalletTransaction::tds_deduction_trans(
+            user_id,
+            &deduction,
+            balance_before,
+            payout_balance_before,
+        );
+        transactions.push(transaction);
+    }
+    let transaction = WalletTransaction::withdraw_bal_trans(
+        user_id,
+        amount - deduction.tds,
+        payout_balance_before,
+        balance_after,
+        upi_id,
+    );
+    transactions.push(transaction);
+    for transaction in transactions.iter() {
+        session
+            .insert_one_with_session(DB_NAME, COLL_WALLET_TRANSACTIONS, transaction, None)
+            .await?;
+    }
+    Ok(deduction)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use mockall::predicate::{always, eq, function};
+    use serde_json::json;
+
+    use mongodb::bson::oid::ObjectId;
+
+    use crate::{
+        config::{build_app_routes, database::InsertedId},
+        helpers::Helpers,
+        utils::test_helper::{build_post_request, oneshot_request},
+    };
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_withdraw_handler() {
+        let ts = get_epoch_ts();
+        let token = "DUMMY_TOKEN";
+        let user_id = 10;
+        std::env::remove_var("TDS_RATE_PERCENT");
+        std::env::remove_var("TDS_THRESHOLD");
+        let mut state = AppState::mock();
+        state
+            .get_mut_utility()
+            .expect_decode_token()
+            .once()
+            .with(eq(token))
+            .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+        state
+            .get_mut_validators()
+            .expect_validate_withdraw()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                function(|_: &Helpers| true),
+                eq(user_id),
+                eq(500),
+            )
+            .return_once(|_, _, _, _| Ok(()));
+        let wallet_helpers = state.get_mut_helpers().mut_wallet_helpers();
+        wallet_helpers.expect_get_tax_summary().never();
+        wallet_helpers
+            .expect_get_user_balance()
+            .once()
+            .returning(|_, _| Ok(Money::new(100, 0)));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .with(
+                function(Option::is_none),
+                function(Option::is_none),
+                always(),
+            )
+            .return_once(|_, _, _| Ok(()));
+        let app = build_app_routes(Arc::new(state));
+        let body = json!({"amount": 500, "upiId": "test@upi"});
+        let req = build_post_request("/api/v1/wallet/withdraw", &body.to_string(), Some(token));
+        let res = oneshot_request::<WithdrawRes>(app, req, Some(StatusCode::OK)).await;
+        assert!(res.success);
+        assert_eq!(res.amount, 500);
+        assert_eq!(res.balance, Money::new(100, 0));
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_tds_computed_in_session() {
+        let user_id = 10;
+        std::env::remove_var("TDS_RATE_PERCENT");
+        std::env::remove_var("TDS_THRESHOLD");
+        let mut state = AppState::mock();
+        let wallet_helpers = state.get_mut_helpers().mut_wallet_helpers();
+        let mut seq = mockall::Sequence::new();
+        wallet_helpers
+            .expect_check_wallet_freeze_session()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+        wallet_helpers
+            .expect_update_wallet_with_session()
+            .once()
+            .in_sequence(&mut seq)
+            .with(always(), eq(user_id), eq(500), eq(0), eq(true), eq(true))
+            .returning(|_, _, _, _, _, _| {
+                let before = json!({"real": 600, "bonus": 0, "withdrawable": 600});
+                let before = serde_json::from_value(before).unwrap();
+                Ok((before, Money::new(100, 0)))
+            });
+        // summary is read after the wallet is updated in the same session
+        wallet_helpers.expect_get_tax_summary().never();
+        wallet_helpers
+            .expect_get_tax_summary_session()
+            .once()
+            .in_sequence(&mut seq)
+            .with(always(), eq(user_id), always(), always())
+            .returning(|_, _, _, _| {
+                let summary = TaxSummary {
+                    total_winnings: 1000,
+                    total_entry_fees: 800,
+                    ..Default::default()
+                };
+                Ok(summary)
+            });
+        let mut session = DbSession::default();
+        session
+            .expect_insert_one_with_session::<WalletTransaction>()
+            .times(2)
+            .returning(|_, _, _, _| Ok(InsertedId(ObjectId::new().to_hex())));
+        let deduction = withdraw(&state, &mut session, user_id, 500, "test@upi")
+            .await
+            .unwrap();
+        assert_eq!(deduction.tds, 60);
+    }
+}
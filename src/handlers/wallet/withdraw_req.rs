@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+
+use crate::{
+    config::{AppError, AppState},
+    models::WithdrawReq,
+    validators::ValidateExtra,
+};
+
+#[async_trait]
+impl ValidateExtra for WithdrawReq {
+    async fn validate_extra(
+        &self,
+        state: Arc<AppState>,
+        user_id: Option<u32>,
+    ) -> Result<(), AppError> {
+        let user_id = user_id.unwrap_or_default();
+        state
+            .validators()
+            .validate_withdraw(state.db(), state.helpers(), user_id, self.amount)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,1258 @@
+use mongodb::{
+    bson::{doc, from_document, oid::ObjectId, to_bson, Bson, Document},
+    error::Result as MongoResult,
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument, UpdateOptions},
+};
+
+use crate::{
+    config::AppError,
+    constants::*,
+    import_double,
+    models::*,
+    utils::{get_doc_u64, get_epoch_ts, is_duplicate_key_error},
+};
+
+import_double!(DbClient, DbSession);
+
+pub struct ContestHelpers;
+
+#[cfg_attr(test, mockall::automock)]
+impl ContestHelpers {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub async fn get_contest(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+    ) -> anyhow::Result<Option<Contest>> {
+        let filter = doc! {"_id": contest_id};
+        let contest = db
+            .find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+            .await?;
+        Ok(contest)
+    }
+
+    pub async fn get_play_tracker(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        user_id: u32,
+        entry_no: u32,
+    ) -> anyhow::Result<Option<PlayTracker>> {
+        let filter = doc! {"contestId": contest_id, "userId": user_id, "entryNo": entry_no};
+        let play_tracker = db
+            .find_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+            .await?;
+        Ok(play_tracker)
+    }
+
+    /// All the entries of the user in the contest including the withdrawn ones
+    pub async fn get_user_play_trackers(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        user_id: u32,
+    ) -> anyhow::Result<Vec<PlayTracker>> {
+        let filter = doc! {"contestId": contest_id, "userId": user_id};
+        let options = FindOptions::builder().sort(doc! {"entryNo": 1}).build();
+        let play_trackers = db
+            .find::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), Some(options))
+            .await?;
+        Ok(play_trackers)
+    }
+
+    /// Ids of all the contests joined by the user, withdrawn entries are not counted
+    pub async fn get_joined_contest_ids(
+        &self,
+        db: &DbClient,
+        user_id: u32,
+    ) -> anyhow::Result<Vec<ObjectId>> {
+        let filter = doc! {
+            "userId": user_id,
+            "status": {"$ne": to_bson(&PlayTrackerStatus::Withdrawn)?}
+        };
+        let contest_ids = db
+            .distinct(DB_NAME, COLL_PLAY_TRACKERS, "contestId", Some(filter), None)
+            .await?
+            .iter()
+            .filter_map(|id| id.as_object_id())
+            .collect();
+        Ok(contest_ids)
+    }
+
+    /// Number of practice contests joined by the user since the timestamp
+    pub async fn count_practice_plays(
+        &self,
+        db: &DbClient,
+        user_id: u32,
+        from_ts: u64,
+    ) -> anyhow::Result<u64> {
+        let pipeline = vec![
+            doc! {
+                "$match": {
+                    "userId": user_id,
+                    "practice": true,
+                    "createdTs": {"$gte": from_ts as i64},
+                }
+            },
+            doc! {"$count": "count"},
+        ];
+        let count = db
+            .aggregate(DB_NAME, COLL_PLAY_TRACKERS, pipeline, None)
+            .await?
+            .first()
+            .map(|doc| get_doc_u64(doc, "count"))
+            .unwrap_or_default();
+        Ok(count)
+    }
+
+    /// Mark the play as started, returns None if the play is already started
+    pub async fn start_play(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        user_id: u32,
+        entry_no: u32,
+    ) -> anyhow::Result<Option<PlayTracker>> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "contestId": contest_id,
+            "userId": user_id,
+            "entryNo": entry_no,
+            "status": to_bson(&PlayTrackerStatus::Joined)?
+        };
+        let update = doc! {
+            "$set": {
+                "status": to_bson(&PlayTrackerStatus::Started)?,
+                "startedTs": ts,
+                "updatedTs": ts
+            }
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(Some(ReturnDocument::After))
+            .build();
+        let play_tracker = db
+            .find_one_and_update::<PlayTracker>(
+                DB_NAME,
+                COLL_PLAY_TRACKERS,
+                filter,
+                update,
+                Some(options),
+            )
+            .await?;
+        Ok(play_tracker)
+    }
+
+    /// Record the answer if the question is not answered yet, returns false otherwise
+    pub async fn submit_answer(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        user_id: u32,
+        entry_no: u32,
+        answer: &PlayAnswer,
+    ) -> anyhow::Result<bool> {
+        let filter = doc! {
+            "contestId": contest_id,
+            "userId": user_id,
+            "entryNo": entry_no,
+            "status": to_bson(&PlayTrackerStatus::Started)?,
+            "answers.questionNo": {"$ne": answer.question_no}
+        };
+        let update = doc! {
+            "$push": {"answers": to_bson(answer)?},
+            "$inc": {"score": answer.points},
+            "$set": {"updatedTs": answer.answered_ts as i64}
+        };
+        let result = db
+            .update_one(DB_NAME, COLL_PLAY_TRACKERS, filter, update, None)
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Record the time the question is served first, later serves keep the first time
+    pub async fn serve_question(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        user_id: u32,
+        entry_no: u32,
+        question_no: u32,
+        served_ts: u64,
+    ) -> anyhow::Result<()> {
+        let filter = doc! {
+            "contestId": contest_id,
+            "userId": user_id,
+            "entryNo": entry_no,
+            "status": to_bson(&PlayTrackerStatus::Started)?,
+            "served.questionNo": {"$ne": question_no}
+        };
+        let served = ServedQuestion {
+            question_no,
+            served_ts,
+        };
+        let update = doc! {"$push": {"served": to_bson(&served)?}};
+        db.update_one(DB_NAME, COLL_PLAY_TRACKERS, filter, update, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Mark the started play as finished, returns None if the play is not in progress
+    pub async fn finish_play(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        user_id: u32,
+        entry_no: u32,
+        finished_ts: u64,
+        time_taken: u64,
+    ) -> anyhow::Result<Option<PlayTracker>> {
+        let filter = doc! {
+            "contestId": contest_id,
+            "userId": user_id,
+            "entryNo": entry_no,
+            "status": to_bson(&PlayTrackerStatus::Started)?
+        };
+        let update = doc! {
+            "$set": {
+                "status": to_bson(&PlayTrackerStatus::Finished)?,
+                "finishedTs": finished_ts as i64,
+                "timeTaken": time_taken as i64,
+                "updatedTs": finished_ts as i64
+            }
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(Some(ReturnDocument::After))
+            .build();
+        let play_tracker = db
+            .find_one_and_update::<PlayTracker>(
+                DB_NAME,
+                COLL_PLAY_TRACKERS,
+                filter,
+                update,
+                Some(options),
+            )
+            .await?;
+        Ok(play_tracker)
+    }
+
+    /// Complete leaderboard of the contest sorted by rank
+    pub async fn get_leaderboard(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+    ) -> anyhow::Result<Vec<LeaderboardEntry>> {
+        let mut pipeline = leaderboard_pipeline(contest_id)?;
+        pipeline.push(doc! {"$sort": {"rank": 1}});
+        let docs = db
+            .aggregate(DB_NAME, COLL_PLAY_TRACKERS, pipeline, None)
+            .await?;
+        let entries = docs
+            .into_iter()
+            .map(from_document::<LeaderboardEntry>)
+            .collect::<Result<_, _>>()?;
+        Ok(entries)
+    }
+
+    /// A page of the contest leaderboard along with the best rank of the user
+    pub async fn get_leaderboard_page(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        skip: u64,
+        limit: u64,
+        user_id: u32,
+    ) -> anyhow::Result<LeaderboardPage> {
+        let mut pipeline = leaderboard_pipeline(contest_id)?;
+        pipeline.push(doc! {
+            "$facet": {
+                "entries": [
+                    {"$sort": {"rank": 1}},
+                    {"$skip": skip as i64},
+                    {"$limit": limit as i64}
+                ],
+                "ownRank": [
+                    {"$match": {"userId": user_id}},
+                    {"$sort": {"rank": 1}},
+                    {"$limit": 1}
+                ],
+                "total": [{"$count": "count"}]
+            }
+        });
+        let docs = db
+            .aggregate(DB_NAME, COLL_PLAY_TRACKERS, pipeline, None)
+            .await?;
+        let Some(doc) = docs.first() else {
+            return Ok(LeaderboardPage::default());
+        };
+        let parse_entries = |key: &str| -> anyhow::Result<Vec<LeaderboardEntry>> {
+            let entries = doc
+                .get_array(key)?
+                .iter()
+                .filter_map(Bson::as_document)
+                .map(|d| from_document::<LeaderboardEntry>(d.clone()))
+                .collect::<Result<_, _>>()?;
+            Ok(entries)
+        };
+        let total = doc
+            .get_array("total")?
+            .first()
+            .and_then(Bson::as_document)
+            .map(|d| get_doc_u64(d, "count"))
+            .unwrap_or_default();
+        let page = LeaderboardPage {
+            total,
+            entries: parse_entries("entries")?,
+            own_rank: parse_entries("ownRank")?.pop(),
+        };
+        Ok(page)
+    }
+
+    /// Contests which are ended or left in finalizing by an earlier run, oldest first
+    pub async fn get_ended_contests(
+        &self,
+        db: &DbClient,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Contest>> {
+        let statuses = [ContestStatus::Ended, ContestStatus::Finalizing];
+        let filter = doc! {"status": {"$in": to_bson(&statuses)?}};
+        let options = FindOptions::builder()
+            .sort(doc! {"endTs": 1})
+            .limit(limit)
+            .build();
+        let contests = db
+            .find::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), Some(options))
+            .await?;
+        Ok(contests)
+    }
+
+    /// Published contests which are started with the minimum players, oldest first
+    pub async fn get_contests_to_start(
+        &self,
+        db: &DbClient,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Contest>> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "status": to_bson(&ContestStatus::Published)?,
+            "startTs": {"$lte": ts},
+            "$expr": {"$gte": ["$joinedPlayers", "$minPlayers"]}
+        };
+        let options = FindOptions::builder()
+            .sort(doc! {"startTs": 1})
+            .limit(limit)
+            .build();
+        let contests = db
+            .find::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), Some(options))
+            .await?;
+        Ok(contests)
+    }
+
+    /// Live contests which are past the end time, oldest first
+    pub async fn get_contests_to_end(
+        &self,
+        db: &DbClient,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Contest>> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "status": to_bson(&ContestStatus::Live)?,
+            "endTs": {"$lte": ts}
+        };
+        let options = FindOptions::builder()
+            .sort(doc! {"endTs": 1})
+            .limit(limit)
+            .build();
+        let contests = db
+            .find::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), Some(options))
+            .await?;
+        Ok(contests)
+    }
+
+    /// Live contests, the ones ending first come first
+    pub async fn get_live_contests(
+        &self,
+        db: &DbClient,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Contest>> {
+        let filter = doc! {"status": to_bson(&ContestStatus::Live)?};
+        let options = FindOptions::builder()
+            .sort(doc! {"endTs": 1})
+            .limit(limit)
+            .build();
+        let contests = db
+            .find::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), Some(options))
+            .await?;
+        Ok(contests)
+    }
+
+    pub async fn get_contest_by_invite_code(
+        &self,
+        db: &DbClient,
+        invite_code: &str,
+    ) -> anyhow::Result<Option<Contest>> {
+        let filter = doc! {"inviteCode": invite_code};
+        let contest = db
+            .find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+            .await?;
+        Ok(contest)
+    }
+
+    pub async fn get_question_set(
+        &self,
+        db: &DbClient,
+        question_set_id: &ObjectId,
+    ) -> anyhow::Result<Option<QuestionSet>> {
+        let filter = doc! {"_id": question_set_id};
+        let question_set = db
+            .find_one::<QuestionSet>(DB_NAME, COLL_QUESTION_SETS, Some(filter), None)
+            .await?;
+        Ok(question_set)
+    }
+
+    /// Question sets which can be used for the private contests, sorted by name
+    pub async fn get_approved_question_sets(
+        &self,
+        db: &DbClient,
+    ) -> anyhow::Result<Vec<QuestionSet>> {
+        let filter = doc! {"approved": true};
+        let options = FindOptions::builder()
+            .sort(doc! {"name": 1})
+            .limit(DEFAULT_QUERY_LIMIT as i64)
+            .build();
+        let question_sets = db
+            .find::<QuestionSet>(DB_NAME, COLL_QUESTION_SETS, Some(filter), Some(options))
+            .await?;
+        Ok(question_sets)
+    }
+
+    pub async fn get_contest_template(
+        &self,
+        db: &DbClient,
+        template_id: &ObjectId,
+    ) -> anyhow::Result<Option<ContestTemplate>> {
+        let filter = doc! {"_id": template_id};
+        let template = db
+            .find_one::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, Some(filter), None)
+            .await?;
+        Ok(template)
+    }
+
+    /// Templates which are not paused, oldest first
+    pub async fn get_active_contest_templates(
+        &self,
+        db: &DbClient,
+        limit: i64,
+    ) -> anyhow::Result<Vec<ContestTemplate>> {
+        let filter = doc! {"paused": false};
+        let options = FindOptions::builder()
+            .sort(doc! {"_id": 1})
+            .limit(limit)
+            .build();
+        let templates = db
+            .find::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, Some(filter), Some(options))
+            .await?;
+        Ok(templates)
+    }
+
+    /// Insert the contest created by the scheduler, returns false if the title already exists
+    pub async fn insert_scheduled_contest(
+        &self,
+        db: &DbClient,
+        contest: &Contest,
+    ) -> anyhow::Result<bool> {
+        let result = db
+            .insert_one::<Contest>(DB_NAME, COLL_CONTESTS, contest, None)
+            .await;
+        match result {
+            Err(e) if is_duplicate_key_error(&e) => Ok(false),
+            Err(e) => Err(e.into()),
+            Ok(_) => Ok(true),
+        }
+    }
+
+    /// Record the start time of the latest contest created from the template
+    pub async fn set_template_scheduled_till(
+        &self,
+        db: &DbClient,
+        template_id: &ObjectId,
+        ts: u64,
+    ) -> anyhow::Result<()> {
+        let filter = doc! {"_id": template_id};
+        let update = doc! {"$max": {"scheduledTill": ts as i64}};
+        db.update_one(DB_NAME, COLL_CONTEST_TEMPLATES, filter, update, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Change the status of the contest if it's still in the `from` status and matches
+    /// the condition, the change is logged in the status history with the actor.
+    /// Returns the updated contest, None if the contest is not in the `from` status.
+    pub async fn transition_status(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        from: &ContestStatus,
+        to: &ContestStatus,
+        actor: Option<u32>,
+        condition: Option<Document>,
+    ) -> anyhow::Result<Option<Contest>> {
+        if !from.can_transition_to(to) {
+            anyhow::bail!("Contest status can not be changed from {from:?} to {to:?}");
+        }
+        let ts = get_epoch_ts();
+        let mut filter = condition.unwrap_or_default();
+        filter.insert("_id", contest_id);
+        filter.insert("status", to_bson(from)?);
+        let change = ContestStatusChange {
+            from: from.clone(),
+            to: to.clone(),
+            actor,
+            ts,
+        };
+        let mut set = doc! {"status": to_bson(to)?, "updatedTs": ts as i64};
+        match to {
+            ContestStatus::Finalized => set.insert("finalizedTs", ts as i64),
+            ContestStatus::Cancelled => set.insert("cancelledTs", ts as i64),
+            _ => None,
+        };
+        if let Some(actor) = actor {
+            set.insert("updatedBy", actor);
+        }
+        let update = doc! {
+            "$set": set,
+            "$push": {"statusHistory": to_bson(&change)?}
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(Some(ReturnDocument::After))
+            .build();
+        let contest = db
+            .find_one_and_update::<Contest>(DB_NAME, COLL_CONTESTS, filter, update, Some(options))
+            .await?;
+        Ok(contest)
+    }
+
+    /// Record the rank and prize of the winning entry in the play tracker,
+    /// returns false if the prize is already credited
+    pub async fn mark_prize_credited_session(
+        &self,
+        session: &mut DbSession,
+        contest_id: &ObjectId,
+        user_id: u32,
+        entry_no: u32,
+        rank: u32,
+        prize: u64,
+    ) -> MongoResult<bool> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "contestId": contest_id,
+            "userId": user_id,
+            "entryNo": entry_no,
+            "prizeCreditedTs": {"$exists": false},
+            "creditHeldTs": {"$exists": false}
+        };
+        let update = doc! {
+            "$set": {
+                "rank": rank,
+                "prize": prize as i64,
+                "prizeCreditedTs": ts,
+                "updatedTs": ts
+            }
+        };
+        let result = session
+            .update_one_with_session(DB_NAME, COLL_PLAY_TRACKERS, filter, update, None)
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Entry fees of the contest paid from real balance, the refunded withdrawn entries
+    /// are not counted
+    pub async fn get_collected_entry_fee(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+    ) -> anyhow::Result<u64> {
+        let pipeline = vec![
+            doc! {
+                "$match": {
+                    "contestId": contest_id,
+                    "status": {"$ne": to_bson(&PlayTrackerStatus::Withdrawn)?}
+                }
+            },
+            doc! {"$group": {"_id": null, "collected": {"$sum": "$entryFee.real"}}},
+        ];
+        let collected = db
+            .aggregate(DB_NAME, COLL_PLAY_TRACKERS, pipeline, None)
+            .await?
+            .first()
+            .map(|doc| get_doc_u64(doc, "collected"))
+            .unwrap_or_default();
+        Ok(collected)
+    }
+
+    /// Put the prize or the refund of the entries of the user on hold for the manual handling,
+    /// the credit is rejected for a reason which won't go away by retrying
+    pub async fn hold_play_credit(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        user_id: u32,
+        entry_nos: &[u32],
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "contestId": contest_id,
+            "userId": user_id,
+            "entryNo": {"$in": entry_nos},
+        };
+        let update = doc! {
+            "$set": {"creditHeldReason": reason, "creditHeldTs": ts, "updatedTs": ts}
+        };
+        db.update_many(DB_NAME, COLL_PLAY_TRACKERS, filter, update, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Mark the house settlement of the finalizing contest done,
+    /// returns false if it's already settled
+    pub async fn mark_house_settled_session(
+        &self,
+        session: &mut DbSession,
+        contest_id: &ObjectId,
+    ) -> MongoResult<bool> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "_id": contest_id,
+            "status": to_bson(&ContestStatus::Finalizing)?,
+            "houseSettledTs": {"$exists": false}
+        };
+        let update = doc! {"$set": {"houseSettledTs": ts, "updatedTs": ts}};
+        let result = session
+            .update_one_with_session(DB_NAME, COLL_CONTESTS, filter, update, None)
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Record the transaction and add the amount to its total in the house account
+    pub async fn insert_house_transaction_session(
+        &self,
+        session: &mut DbSession,
+        transaction: &HouseTransaction,
+    ) -> MongoResult<()> {
+        session
+            .insert_one_with_session(DB_NAME, COLL_HOUSE_TRANSACTIONS, transaction, None)
+            .await?;
+        let filter = doc! {"_id": HOUSE_ACCOUNT_ID};
+        let update = doc! {
+            "$inc": {transaction.total_field(): transaction.amount as i64},
+            "$set": {"updatedTs": transaction.created_ts.unwrap_or_default() as i64}
+        };
+        let options = UpdateOptions::builder().upsert(true).build();
+        session
+            .update_one_with_session(DB_NAME, COLL_HOUSE_ACCOUNT, filter, update, Some(options))
+            .await?;
+        Ok(())
+    }
+
+    /// Started contests which are short of the minimum players, oldest first
+    pub async fn get_contests_to_cancel(
+        &self,
+        db: &DbClient,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Contest>> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "status": to_bson(&ContestStatus::Published)?,
+            "startTs": {"$lte": ts},
+            "$expr": {"$lt": ["$joinedPlayers", "$minPlayers"]}
+        };
+        let options = FindOptions::builder()
+            .sort(doc! {"startTs": 1})
+            .limit(limit)
+            .build();
+        let contests = db
+            .find::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), Some(options))
+            .await?;
+        Ok(contests)
+    }
+
+    /// Cancel the published contest if it's still short of the minimum players,
+    /// returns false if it's not published or has enough players
+    pub async fn cancel_contest(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        actor: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        let condition = doc! {"$expr": {"$lt": ["$joinedPlayers", "$minPlayers"]}};
+        let contest = self
+            .transition_status(
+                db,
+                contest_id,
+                &ContestStatus::Published,
+                &ContestStatus::Cancelled,
+                actor,
+                Some(condition),
+            )
+            .await?;
+        Ok(contest.is_some())
+    }
+
+    /// Cancelled contests with entry fee refunds pending
+    pub async fn get_refund_pending_contests(
+        &self,
+        db: &DbClient,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Contest>> {
+        let filter = doc! {
+            "status": to_bson(&ContestStatus::Cancelled)?,
+            "refundedTs": {"$exists": false}
+        };
+        let options = FindOptions::builder()
+            .sort(doc! {"cancelledTs": 1})
+            .limit(limit)
+            .build();
+        let contests = db
+            .find::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), Some(options))
+            .await?;
+        Ok(contests)
+    }
+
+    /// Plays of the contest with the entry fee not refunded yet, the ones on hold are skipped
+    pub async fn get_unrefunded_play_trackers(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+    ) -> anyhow::Result<Vec<PlayTracker>> {
+        let filter = doc! {
+            "contestId": contest_id,
+            "refundedTs": {"$exists": false},
+            "creditHeldTs": {"$exists": false}
+        };
+        let play_trackers = db
+            .find::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+            .await?;
+        Ok(play_trackers)
+    }
+
+    /// Mark the entry fee of the play refunded, returns false if it's already refunded
+    pub async fn mark_refunded_session(
+        &self,
+        session: &mut DbSession,
+        contest_id: &ObjectId,
+        user_id: u32,
+        entry_no: u32,
+    ) -> MongoResult<bool> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "contestId": contest_id,
+            "userId": user_id,
+            "entryNo": entry_no,
+            "refundedTs": {"$exists": false},
+            "creditHeldTs": {"$exists": false}
+        };
+        let update = doc! {"$set": {"refundedTs": ts, "updatedTs": ts}};
+        let result = session
+            .update_one_with_session(DB_NAME, COLL_PLAY_TRACKERS, filter, update, None)
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Withdraw the entry which is not started yet and mark its entry fee refunded,
+    /// returns false if the entry is started or already withdrawn
+    pub async fn withdraw_play_session(
+        &self,
+        session: &mut DbSession,
+        contest_id: &ObjectId,
+        user_id: u32,
+        entry_no: u32,
+    ) -> MongoResult<bool> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "contestId": contest_id,
+            "userId": user_id,
+            "entryNo": entry_no,
+            "status": to_bson(&PlayTrackerStatus::Joined)?,
+            "refundedTs": {"$exists": false}
+        };
+        let update = doc! {
+            "$set": {
+                "status": to_bson(&PlayTrackerStatus::Withdrawn)?,
+                "refundedTs": ts,
+                "updatedTs": ts
+            }
+        };
+        let result = session
+            .update_one_with_session(DB_NAME, COLL_PLAY_TRACKERS, filter, update, None)
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    pub async fn mark_contest_refunded(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+    ) -> anyhow::Result<()> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {"_id": contest_id, "status": to_bson(&ContestStatus::Cancelled)?};
+        let update = doc! {"$set": {"refundedTs": ts, "updatedTs": ts}};
+        db.update_one(DB_NAME, COLL_CONTESTS, filter, update, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Increment the joined players of the contest if it's still open for joining
+    pub async fn join_contest_session(
+        &self,
+        session: &mut DbSession,
+        contest_id: &ObjectId,
+    ) -> MongoResult<()> {
+        let ts = get_epoch_ts() as i64;
+        let status = to_bson(&ContestStatus::Published)?;
+        let filter = doc! {
+            "_id": contest_id,
+            "status": status,
+            "startTs": {"$gt": ts},
+            "$expr": {"$lt": ["$joinedPlayers", "$maxPlayers"]}
+        };
+        let update = doc! {
+            "$inc": {"joinedPlayers": 1},
+            "$set": {"updatedTs": ts}
+        };
+        let result = session
+            .update_one_with_session(DB_NAME, COLL_CONTESTS, filter, update, None)
+            .await?;
+        if result.matched_count == 0 {
+            let err = AppError::BadRequest("Contest is not open for joining".into());
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    /// Decrement the joined players of the contest if it's not started yet
+    pub async fn leave_contest_session(
+        &self,
+        session: &mut DbSession,
+        contest_id: &ObjectId,
+    ) -> MongoResult<()> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "_id": contest_id,
+            "status": to_bson(&ContestStatus::Published)?,
+            "startTs": {"$gt": ts},
+            "joinedPlayers": {"$gt": 0}
+        };
+        let update = doc! {
+            "$inc": {"joinedPlayers": -1},
+            "$set": {"updatedTs": ts}
+        };
+        let result = session
+            .update_one_with_session(DB_NAME, COLL_CONTESTS, filter, update, None)
+            .await?;
+        if result.matched_count == 0 {
+            let err = AppError::BadRequest("Contest is already started".into());
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    pub async fn insert_play_tracker_session(
+        &self,
+        session: &mut DbSession,
+        play_tracker: &PlayTracker,
+    ) -> MongoResult<()> {
+        let result = session
+            .insert_one_with_session(DB_NAME, COLL_PLAY_TRACKERS, play_tracker, None)
+            .await;
+        match result {
+            Err(e) if is_duplicate_key_error(&e) => {
+                let err = AppError::BadRequest("Contest already joined".into());
+                Err(err.into())
+            }
+            Err(e) => Err(e),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    /// Add the user to the waitlist of the contest, returns false if already waiting
+    pub async fn insert_waitlist_entry(
+        &self,
+        db: &DbClient,
+        entry: &WaitlistEntry,
+    ) -> anyhow::Result<bool> {
+        let result = db
+            .insert_one::<WaitlistEntry>(DB_NAME, COLL_CONTEST_WAITLIST, entry, None)
+            .await;
+        match result {
+            Err(e) if is_duplicate_key_error(&e) => Ok(false),
+            Err(e) => Err(e.into()),
+            Ok(_) => Ok(true),
+        }
+    }
+
+    /// Waitlist entry of the user which is not promoted or failed yet
+    pub async fn get_waitlist_entry(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        user_id: u32,
+    ) -> anyhow::Result<Option<WaitlistEntry>> {
+        let statuses = [WaitlistStatus::Waiting, WaitlistStatus::Promoting];
+        let filter = doc! {
+            "contestId": contest_id,
+            "userId": user_id,
+            "status": {"$in": to_bson(&statuses)?}
+        };
+        let entry = db
+            .find_one::<WaitlistEntry>(DB_NAME, COLL_CONTEST_WAITLIST, Some(filter), None)
+            .await?;
+        Ok(entry)
+    }
+
+    /// Number of users waiting for the contest since the timestamp or earlier
+    pub async fn count_waiting_till(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        created_ts: u64,
+    ) -> anyhow::Result<u64> {
+        let pipeline = vec![
+            doc! {
+                "$match": {
+                    "contestId": contest_id,
+                    "status": to_bson(&WaitlistStatus::Waiting)?,
+                    "createdTs": {"$lte": created_ts as i64},
+                }
+            },
+            doc! {"$count": "count"},
+        ];
+        let count = db
+            .aggregate(DB_NAME, COLL_CONTEST_WAITLIST, pipeline, None)
+            .await?
+            .first()
+            .map(|doc| get_doc_u64(doc, "count"))
+            .unwrap_or_default();
+        Ok(count)
+    }
+
+    /// Pick up the oldest waiting user of the contest for promotion,
+    /// returns None if nobody is waiting
+    pub async fn claim_waitlist_entry(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+    ) -> anyhow::Result<Option<WaitlistEntry>> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "contestId": contest_id,
+            "status": to_bson(&WaitlistStatus::Waiting)?
+        };
+        let update = doc! {
+            "$set": {"status": to_bson(&WaitlistStatus::Promoting)?, "updatedTs": ts}
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .sort(doc! {"createdTs": 1, "_id": 1})
+            .return_document(Some(ReturnDocument::After))
+            .build();
+        let entry = db
+            .find_one_and_update::<WaitlistEntry>(
+                DB_NAME,
+                COLL_CONTEST_WAITLIST,
+                filter,
+                update,
+                Some(options),
+            )
+            .await?;
+        Ok(entry)
+    }
+
+    /// Record the result of the promotion of the claimed waitlist entry
+    pub async fn set_waitlist_status(
+        &self,
+        db: &DbClient,
+        entry_id: &ObjectId,
+        status: &WaitlistStatus,
+        reason: Option<String>,
+    ) -> anyhow::Result<()> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {"_id": entry_id};
+        let mut set = doc! {"status": to_bson(status)?, "updatedTs": ts};
+        if let Some(reason) = reason {
+            set.insert("reason", reason);
+        }
+        let update = doc! {"$set": set};
+        db.update_one(DB_NAME, COLL_CONTEST_WAITLIST, filter, update, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Put the entries claimed for promotion before the timestamp back to wait,
+    /// the promotion was interrupted midway e.g. by a restart
+    pub async fn reclaim_waitlist_entries(
+        &self,
+        db: &DbClient,
+        promoting_before: u64,
+    ) -> anyhow::Result<u64> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "status": to_bson(&WaitlistStatus::Promoting)?,
+            "updatedTs": {"$lt": promoting_before as i64}
+        };
+        let update = doc! {
+            "$set": {"status": to_bson(&WaitlistStatus::Waiting)?, "updatedTs": ts}
+        };
+        let result = db
+            .update_many(DB_NAME, COLL_CONTEST_WAITLIST, filter, update, None)
+            .await?;
+        Ok(result.modified_count)
+    }
+
+    /// Contests having users in the waitlist which either have a spot to promote them
+    /// or are closed for joining, the ones waiting longest first
+    pub async fn get_waitlisted_contest_ids(
+        &self,
+        db: &DbClient,
+        limit: i64,
+    ) -> anyhow::Result<Vec<ObjectId>> {
+        let ts = get_epoch_ts() as i64;
+        let pipeline = vec![
+            doc! {"$match": {"status": to_bson(&WaitlistStatus::Waiting)?}},
+            doc! {"$group": {"_id": "$contestId", "createdTs": {"$min": "$createdTs"}}},
+            doc! {
+                "$lookup": {
+                    "from": COLL_CONTESTS,
+                    "localField": "_id",
+                    "foreignField": "_id",
+                    "as": "contest"
+                }
+            },
+            doc! {"$unwind": "$contest"},
+            doc! {
+                "$match": {
+                    "$or": [
+                        {"$expr": {"$lt": ["$contest.joinedPlayers", "$contest.maxPlayers"]}},
+                        {"contest.status": {"$ne": to_bson(&ContestStatus::Published)?}},
+                        {"contest.startTs": {"$lte": ts}}
+                    ]
+                }
+            },
+            doc! {"$sort": {"createdTs": 1, "_id": 1}},
+            doc! {"$limit": limit},
+            doc! {"$project": {"_id": 1}},
+        ];
+        let contest_ids = db
+            .aggregate(DB_NAME, COLL_CONTEST_WAITLIST, pipeline, None)
+            .await?
+            .iter()
+            .filter_map(|doc| doc.get_object_id("_id").ok())
+            .collect();
+        Ok(contest_ids)
+    }
+
+    /// Mark all the waiting users of the contest failed, e.g. when the contest is started
+    pub async fn close_waitlist(
+        &self,
+        db: &DbClient,
+        contest_id: &ObjectId,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "contestId": contest_id,
+            "status": to_bson(&WaitlistStatus::Waiting)?
+        };
+        let update = doc! {
+            "$set": {
+                "status": to_bson(&WaitlistStatus::Failed)?,
+                "reason": reason,
+                "updatedTs": ts
+            }
+        };
+        db.update_many(DB_NAME, COLL_CONTEST_WAITLIST, filter, update, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Check if the movie or clip with the name exists
+    pub async fn media_exists(
+        &self,
+        db: &DbClient,
+        media_type: &MediaType,
+        name: &str,
+    ) -> anyhow::Result<bool> {
+        let coll = match media_type {
+            MediaType::Movie => COLL_MOVIES,
+            MediaType::Clip => COLL_CLIPS,
+        };
+        let filter = doc! {"name": name};
+        let media = db
+            .find_one::<Document>(DB_NAME, coll, Some(filter), None)
+            .await?;
+        Ok(media.is_some())
+    }
+}
+
+/// Stages to rank the entries of the contest by score, then time taken and then finish time.
+/// Entries still in play are ranked after the finished entries with the same score and
+/// user id and entry number break the remaining ties, so a rank never changes between pages.
+fn leaderboard_pipeline(contest_id: &ObjectId) -> anyhow::Result<Vec<Document>> {
+    let statuses = [PlayTrackerStatus::Started, PlayTrackerStatus::Finished];
+    let pipeline = vec![
+        doc! {"$match": {"contestId": contest_id, "status": {"$in": to_bson(&statuses)?}}},
+        doc! {
+            "$addFields": {
+                "sortTimeTaken": {"$ifNull": ["$timeTaken", i64::MAX]},
+                "sortFinishedTs": {"$ifNull": ["$finishedTs", i64::MAX]},
+                "entryNo": {"$ifNull": ["$entryNo", 1]}
+            }
+        },
+        doc! {
+            "$setWindowFields": {
+                "sortBy": {
+                    "score": -1,
+                    "sortTimeTaken": 1,
+                    "sortFinishedTs": 1,
+                    "userId": 1,
+                    "entryNo": 1
+                },
+                "output": {"rank": {"$documentNumber": {}}}
+            }
+        },
+        doc! {
+            "$project": {
+                "_id": 0,
+                "rank": 1,
+                "userId": 1,
+                "entryNo": 1,
+                "score": 1,
+                "timeTaken": 1,
+                "finishedTs": 1
+            }
+        },
+    ];
+    Ok(pipeline)
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::{always, eq, function};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_leaderboard_page() {
+        let contest_id = ObjectId::new();
+        let mut db = DbClient::default();
+        db.expect_aggregate()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_PLAY_TRACKERS),
+                function(|pipeline: &Vec<Document>| {
+                    let facet = pipeline.last().unwrap().get_document("$facet").unwrap();
+                    let entries = facet.get_array("entries").unwrap();
+                    entries[1] == Bson::Document(doc! {"$skip": 20_i64})
+                }),
+                always(),
+            )
+            .returning(|_, _, _, _| {
+                let entry = doc! {"rank": 21_i64, "userId": 5, "score": 8, "timeTaken": 30_i64};
+                let own = doc! {"rank": 42_i64, "userId": 10, "score": 3};
+                let res = doc! {
+                    "entries": [entry],
+                    "ownRank": [own],
+                    "total": [{"count": 50}]
+                };
+                Ok(vec![res])
+            });
+        let page = ContestHelpers::new()
+            .get_leaderboard_page(&db, &contest_id, 20, 10, 10)
+            .await
+            .unwrap();
+        assert_eq!(page.total, 50);
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].rank, 21);
+        assert_eq!(page.entries[0].time_taken, Some(30));
+        let own_rank = page.own_rank.unwrap();
+        assert_eq!((own_rank.rank, own_rank.user_id), (42, 10));
+        assert_eq!(own_rank.finished_ts, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_joined_contest_ids() {
+        let contest_ids = [ObjectId::new(), ObjectId::new()];
+        let mut db = DbClient::default();
+        db.expect_distinct()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_PLAY_TRACKERS),
+                eq("contestId"),
+                eq(Some(doc! {"userId": 10, "status": {"$ne": "withdrawn"}})),
+                always(),
+            )
+            .returning(move |_, _, _, _, _| {
+                Ok(contest_ids.iter().map(|id| Bson::ObjectId(*id)).collect())
+            });
+        let result = ContestHelpers::new()
+            .get_joined_contest_ids(&db, 10)
+            .await
+            .unwrap();
+        assert_eq!(result, contest_ids.to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_get_collected_entry_fee() {
+        let contest_id = ObjectId::new();
+        let mut db = DbClient::default();
+        db.expect_aggregate()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_PLAY_TRACKERS),
+                function(move |pipeline: &Vec<Document>| {
+                    let filter = doc! {"contestId": contest_id, "status": {"$ne": "withdrawn"}};
+                    pipeline[0] == doc! {"$match": filter}
+                }),
+                always(),
+            )
+            .returning(|_, _, _, _| Ok(vec![doc! {"_id": null, "collected": 300_i64}]));
+        let collected = ContestHelpers::new()
+            .get_collected_entry_fee(&db, &contest_id)
+            .await
+            .unwrap();
+        assert_eq!(collected, 300);
+    }
+
+    #[tokio::test]
+    async fn test_get_waitlisted_contest_ids() {
+        let contest_id = ObjectId::new();
+        let mut db = DbClient::default();
+        db.expect_aggregate()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_CONTEST_WAITLIST),
+                function(|pipeline: &Vec<Document>| {
+                    // full contests still open for joining are left out
+                    let filter = pipeline[4].get_document("$match").unwrap();
+                    let spot = doc! {
+                        "$expr": {"$lt": ["$contest.joinedPlayers", "$contest.maxPlayers"]}
+                    };
+                    pipeline[2].contains_key("$lookup")
+                        && filter.get_array("$or").unwrap()[0] == Bson::Document(spot)
+                        && pipeline[6] == doc! {"$limit": 50_i64}
+                }),
+                always(),
+            )
+            .returning(move |_, _, _, _| Ok(vec![doc! {"_id": contest_id}]));
+        let contest_ids = ContestHelpers::new()
+            .get_waitlisted_contest_ids(&db, 50)
+            .await
+            .unwrap();
+        assert_eq!(contest_ids, vec![contest_id]);
+    }
+}
@@ -0,0 +1,216 @@
+use mongodb::{
+    bson::{doc, Bson},
+    options::UpdateOptions,
+};
+
+use crate::{
+    constants::*,
+    import_double,
+    models::*,
+    utils::{get_doc_u64, get_epoch_ts, is_duplicate_key_error, version_filter},
+};
+
+import_double!(DbClient);
+
+pub struct GamingLimitHelpers;
+
+#[cfg_attr(test, mockall::automock)]
+impl GamingLimitHelpers {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Returns the gaming limits of the user, default limits if not configured yet
+    pub async fn get_gaming_limits(
+        &self,
+        db: &DbClient,
+        user_id: u32,
+    ) -> anyhow::Result<GamingLimits> {
+        let filter = doc! {"userId": user_id};
+        let gaming_limits = db
+            .find_one::<GamingLimits>(DB_NAME, COLL_GAMING_LIMITS, Some(filter), None)
+            .await?
+            .unwrap_or(GamingLimits {
+                user_id,
+                ..Default::default()
+            });
+        Ok(gaming_limits)
+    }
+
+    /// Save the limits only if they are not updated since read, so that concurrent updates
+    /// can not bypass the cooling off period. Returns false if the version is changed in between.
+    pub async fn save_gaming_limits(
+        &self,
+        db: &DbClient,
+        gaming_limits: &GamingLimits,
+    ) -> anyhow::Result<bool> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {
+            "userId": gaming_limits.user_id,
+            "version": version_filter(gaming_limits.version)
+        };
+        let update = doc! {
+            "$set": {
+                "deposit": mongodb::bson::to_bson(&gaming_limits.deposit)?,
+                "contestSpend": mongodb::bson::to_bson(&gaming_limits.contest_spend)?,
+                "updatedTs": ts,
+            },
+            "$inc": {"version": 1},
+            "$setOnInsert": {"createdTs": ts}
+        };
+        let options = UpdateOptions::builder().upsert(true).build();
+        // limits inserted in between fail the upsert on the unique userId
+        match db
+            .update_one(DB_NAME, COLL_GAMING_LIMITS, filter, update, Some(options))
+            .await
+        {
+            Ok(result) => Ok(result.matched_count > 0 || result.upserted_id.is_some()),
+            Err(e) if is_duplicate_key_error(&e) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Set self exclusion of the user till the given time.
+    /// An existing self exclusion can only be extended, never shortened.
+    pub async fn self_exclude(&self, db: &DbClient, user_id: u32, till: u64) -> anyhow::Result<()> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {"userId": user_id};
+        let update = doc! {
+            "$max": {"selfExcludedTill": till as i64},
+            "$set": {"updatedTs": ts},
+            "$setOnInsert": {"createdTs": ts}
+        };
+        let options = UpdateOptions::builder().upsert(true).build();
+        db.update_one(DB_NAME, COLL_GAMING_LIMITS, filter, update, Some(options))
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the real amount of the given transaction type in the rolling
+    /// daily, weekly and monthly windows. Pending transactions are counted as well
+    /// so that multiple initiated transactions can not bypass the limits.
+    /// Refunded entry fees are deducted from the contest spend in the window of the refund.
+    pub async fn get_period_totals(
+        &self,
+        db: &DbClient,
+        user_id: u32,
+        transaction_type: WalltetTransactionType,
+    ) -> anyhow::Result<PeriodTotals> {
+        let ts = get_epoch_ts();
+        let from_ts = |period: LimitPeriod| ts.saturating_sub(period.window_secs()) as i64;
+        let sum_from = |period: LimitPeriod| {
+            doc! {
+                "$sum": {
+                    "$cond": [{"$gte": ["$createdTs", from_ts(period)]}, "$real", 0]
+                }
+            }
+        };
+        let mut transaction_types = vec![transaction_type.to_bson()?];
+        let refund_type = match transaction_type {
+            WalltetTransactionType::PayForContest => {
+                Some(WalltetTransactionType::RefundContestEntryFee.to_bson()?)
+            }
+            _ => None,
+        };
+        transaction_types.extend(refund_type.clone());
+        let pipeline = vec![
+            doc! {
+                "$match": {
+                    "userId": user_id,
+                    "transactionType": {"$in": transaction_types},
+                    "status": {"$ne": WalletTransactionStatus::Error.to_bson()?},
+                    "createdTs": {"$gte": from_ts(LimitPeriod::Monthly)},
+                }
+            },
+            doc! {
+                "$addFields": {
+                    "real": {
+                        "$cond": [
+                            {"$eq": ["$transactionType", refund_type.unwrap_or_default()]},
+                            {"$multiply": ["$amount.real", -1]},
+                            "$amount.real"
+                        ]
+                    }
+                }
+            },
+            doc! {
+                "$group": {
+                    "_id": null,
+                    "daily": sum_from(LimitPeriod::Daily),
+                    "weekly": sum_from(LimitPeriod::Weekly),
+                    "monthly": {"$sum": "$real"},
+                }
+            },
+        ];
+        let totals = db
+            .aggregate(DB_NAME, COLL_WALLET_TRANSACTIONS, pipeline, None)
+            .await?
+            .first()
+            .map(|doc| PeriodTotals {
+                daily: get_doc_u64(doc, "daily"),
+                weekly: get_doc_u64(doc, "weekly"),
+                monthly: get_doc_u64(doc, "monthly"),
+            })
+            .unwrap_or_default();
+        Ok(totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+    use mongodb::bson::Document;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_period_totals_refunded_entry_fees() {
+        let user_id = 7;
+        let gaming_limit_helper = GamingLimitHelpers::new();
+        let mut db = DbClient::default();
+        // refunds are matched along with the entry fees and deducted from the totals
+        db.expect_aggregate()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_WALLET_TRANSACTIONS),
+                function(|pipeline: &Vec<Document>| {
+                    let types = pipeline[0]
+                        .get_document("$match")
+                        .and_then(|m| m.get_document("transactionType"))
+                        .and_then(|t| t.get_array("$in"))
+                        .unwrap();
+                    types.contains(&Bson::String("PAY_FOR_CONTEST".into()))
+                        && types.contains(&Bson::String("REFUND_CONTEST_ENTRY_FEE".into()))
+                }),
+                function(Option::is_none),
+            )
+            .returning(|_, _, _, _| Ok(vec![doc! {"daily": 0, "weekly": 50, "monthly": 150}]));
+        let totals = gaming_limit_helper
+            .get_period_totals(&db, user_id, WalltetTransactionType::PayForContest)
+            .await
+            .unwrap();
+        assert_eq!(totals.daily, 0);
+        assert_eq!(totals.weekly, 50);
+        assert_eq!(totals.monthly, 150);
+
+        // no refunds for the deposits
+        db.expect_aggregate()
+            .once()
+            .withf(|_, _, pipeline, _| {
+                let types = pipeline[0]
+                    .get_document("$match")
+                    .and_then(|m| m.get_document("transactionType"))
+                    .and_then(|t| t.get_array("$in"))
+                    .unwrap();
+                types == &vec![Bson::String("ADD_BALANCE".into())]
+            })
+            .returning(|_, _, _, _| Ok(vec![]));
+        let totals = gaming_limit_helper
+            .get_period_totals(&db, user_id, WalltetTransactionType::AddBalance)
+            .await
+            .unwrap();
+        assert_eq!(totals.monthly, 0);
+    }
+}
@@ -1,30 +1,75 @@
 use crate::import_double;
 
+import_double!(self::contest::ContestHelpers);
+import_double!(self::gaming_limit::GamingLimitHelpers);
+import_double!(self::user::UserHelpers);
 import_double!(self::wallet::WalletHelpers);
 
+mod contest;
+mod gaming_limit;
+mod user;
 mod wallet;
 
+pub use wallet::check_wallet_freeze;
+
 pub struct Helpers {
+    user_helpers: UserHelpers,
     wallet_helpers: WalletHelpers,
+    gaming_limit_helpers: GamingLimitHelpers,
+    contest_helpers: ContestHelpers,
 }
 
 impl Helpers {
     pub fn new() -> Self {
+        let user_helpers = UserHelpers::new();
         let wallet_helpers = WalletHelpers::new();
-        Self { wallet_helpers }
+        let gaming_limit_helpers = GamingLimitHelpers::new();
+        let contest_helpers = ContestHelpers::new();
+        Self {
+            user_helpers,
+            wallet_helpers,
+            gaming_limit_helpers,
+            contest_helpers,
+        }
+    }
+    pub fn user_helpers(&self) -> &UserHelpers {
+        &self.user_helpers
     }
     pub fn wallet_helpers(&self) -> &WalletHelpers {
         &self.wallet_helpers
     }
+    pub fn gaming_limit_helpers(&self) -> &GamingLimitHelpers {
+        &self.gaming_limit_helpers
+    }
+    pub fn contest_helpers(&self) -> &ContestHelpers {
+        &self.contest_helpers
+    }
 }
 
 #[cfg(test)]
 impl Helpers {
     pub fn mock() -> Self {
+        let user_helpers = UserHelpers::default();
         let wallet_helpers = WalletHelpers::default();
-        Self { wallet_helpers }
+        let gaming_limit_helpers = GamingLimitHelpers::default();
+        let contest_helpers = ContestHelpers::default();
+        Self {
+            user_helpers,
+            wallet_helpers,
+            gaming_limit_helpers,
+            contest_helpers,
+        }
+    }
+    pub fn mut_user_helpers(&mut self) -> &mut UserHelpers {
+        &mut self.user_helpers
     }
     pub fn mut_wallet_helpers(&mut self) -> &mut WalletHelpers {
         &mut self.wallet_helpers
     }
+    pub fn mut_gaming_limit_helpers(&mut self) -> &mut GamingLimitHelpers {
+        &mut self.gaming_limit_helpers
+    }
+    pub fn mut_contest_helpers(&mut self) -> &mut ContestHelpers {
+        &mut self.contest_helpers
+    }
 }
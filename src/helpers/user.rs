@@ -0,0 +1,180 @@
+use mongodb::{bson::doc, error::Result as MongoResult};
+
+use crate::{
+    constants::*,
+    import_double,
+    models::*,
+    utils::{generate_referral_code, get_epoch_ts, is_duplicate_key_error},
+};
+
+import_double!(DbClient, DbSession);
+
+pub struct UserHelpers;
+
+#[cfg_attr(test, mockall::automock)]
+impl UserHelpers {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub async fn get_user(&self, db: &DbClient, user_id: u32) -> anyhow::Result<Option<User>> {
+        let filter = doc! {"id": user_id};
+        let user = db
+            .find_one::<User>(DB_NAME, COLL_USERS, Some(filter), None)
+            .await?;
+        Ok(user)
+    }
+
+    pub async fn get_user_by_referral_code(
+        &self,
+        db: &DbClient,
+        referral_code: &str,
+    ) -> anyhow::Result<Option<User>> {
+        let filter = doc! {"referralCode": referral_code};
+        let user = db
+            .find_one::<User>(DB_NAME, COLL_USERS, Some(filter), None)
+            .await?;
+        Ok(user)
+    }
+
+    pub async fn get_special_referral_code(
+        &self,
+        db: &DbClient,
+        referral_code: &str,
+    ) -> anyhow::Result<Option<SpecialReferralCode>> {
+        let filter = doc! {"referralCode": referral_code};
+        let special_referral_code = db
+            .find_one::<SpecialReferralCode>(
+                DB_NAME,
+                COLL_SPECIAL_REFERRAL_CODES,
+                Some(filter),
+                None,
+            )
+            .await?;
+        Ok(special_referral_code)
+    }
+
+    /// Returns the referral code of the user.
+    /// Generates a new code if the user does not have one yet. As `referralCode` has an
+    /// unique index, generation is retried on duplicate key error.
+    pub async fn get_or_create_referral_code(
+        &self,
+        db: &DbClient,
+        user_id: u32,
+    ) -> anyhow::Result<String> {
+        let user = self
+            .get_user(db, user_id)
+            .await?
+            .ok_or(anyhow::anyhow!("User not found"))?;
+        if let Some(referral_code) = user.referral_code {
+            return Ok(referral_code);
+        }
+        for _ in 0..REFERRAL_CODE_MAX_RETRY {
+            let referral_code = generate_referral_code();
+            let ts = get_epoch_ts() as i64;
+            let filter = doc! {"id": user_id, "referralCode": null};
+            let update = doc! {"$set": {"referralCode": &referral_code, "updatedTs": ts}};
+            match db
+                .update_one(DB_NAME, COLL_USERS, filter, update, None)
+                .await
+            {
+                Ok(result) if result.matched_count == 0 => {
+                    // referral code got assigned by a concurrent request
+                    let user = self
+                        .get_user(db, user_id)
+                        .await?
+                        .ok_or(anyhow::anyhow!("User not found"))?;
+                    return user
+                        .referral_code
+                        .ok_or(anyhow::anyhow!("Not able to assign referral code"));
+                }
+                Ok(_) => return Ok(referral_code),
+                Err(e) if is_duplicate_key_error(&e) => {
+                    tracing::debug!("referral code {referral_code} already exists, retrying");
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let err = format!(
+            "Not able to generate unique referral code in {REFERRAL_CODE_MAX_RETRY} attempts"
+        );
+        Err(anyhow::anyhow!(err))
+    }
+
+    /// Add the prize to the total earning of the user and count the contest won if
+    /// `count_win` is set, i.e. once for the contest if the user won with multiple entries
+    pub async fn add_contest_win_session(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+        prize: u64,
+        count_win: bool,
+    ) -> MongoResult<()> {
+        let filter = doc! {"id": user_id};
+        let update = doc! {
+            "$inc": {"contestWon": count_win as i32, "totalEarning.real": prize as i64},
+            "$set": {"updatedTs": get_epoch_ts() as i64}
+        };
+        session
+            .update_one_with_session(DB_NAME, COLL_USERS, filter, update, None)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+
+    use crate::config::database::UpdateResult;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_or_create_referral_code() {
+        let user_id = 7;
+        let user_helpers = UserHelpers::new();
+        let mut db = DbClient::default();
+        // user already has a referral code
+        db.expect_find_one::<User>()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_USERS),
+                eq(Some(doc! {"id": user_id})),
+                function(Option::is_none),
+            )
+            .returning(|_, _, _, _| {
+                let user = User {
+                    referral_code: Some("ABCD1234".into()),
+                    ..Default::default()
+                };
+                Ok(Some(user))
+            });
+        db.expect_update_one().never();
+        let result = user_helpers.get_or_create_referral_code(&db, user_id).await;
+        assert_eq!(result.unwrap(), "ABCD1234".to_owned());
+
+        // new referral code generated for the user
+        let mut db = DbClient::default();
+        db.expect_find_one::<User>()
+            .once()
+            .returning(|_, _, _, _| Ok(Some(User::default())));
+        db.expect_update_one()
+            .once()
+            .with(
+                eq(DB_NAME),
+                eq(COLL_USERS),
+                function(move |filter: &mongodb::bson::Document| {
+                    filter.get_i32("id") == Ok(user_id as i32)
+                }),
+                function(|_| true),
+                function(Option::is_none),
+            )
+            .returning(|_, _, _, _, _| Ok(UpdateResult::new(1, 1, None)));
+        let result = user_helpers.get_or_create_referral_code(&db, user_id).await;
+        assert_eq!(result.unwrap().len(), REFERRAL_CODE_LEN);
+    }
+}
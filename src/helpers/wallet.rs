@@ -1,10 +1,18 @@
 use mongodb::{
-    bson::{doc, oid::ObjectId, Document},
+    bson::{doc, oid::ObjectId, Bson, Document},
     error::{Error as MongoError, Result as MongoResult},
-    options::{FindOneAndUpdateOptions, ReturnDocument, UpdateModifications},
+    options::{
+        FindOneAndUpdateOptions, FindOneOptions, FindOptions, ReturnDocument, UpdateModifications,
+    },
 };
 
-use crate::{config::AppError, constants::*, import_double, models::*, utils::get_epoch_ts};
+use crate::{
+    config::AppError,
+    constants::*,
+    import_double,
+    models::*,
+    utils::{get_doc_u64, get_epoch_ts, is_duplicate_key_error, version_filter},
+};
 
 import_double!(DbClient, DbSession);
 
@@ -15,6 +23,14 @@ impl WalletHelpers {
     pub fn new() -> Self {
         Self
     }
+    pub async fn get_wallet(&self, db: &DbClient, user_id: u32) -> anyhow::Result<Option<Wallet>> {
+        let filter = doc! {"userId": user_id};
+        let wallet = db
+            .find_one::<Wallet>(DB_NAME, COLL_WALLETS, Some(filter), None)
+            .await?;
+        Ok(wallet)
+    }
+
     pub async fn get_user_balance(&self, db: &DbClient, user_id: u32) -> anyhow::Result<Money> {
         let filter = doc! {"userId": user_id};
         let wallet = db
@@ -24,6 +40,189 @@ impl WalletHelpers {
         Ok(balance.unwrap_or_default())
     }
 
+    /// Returns the completed transactions of the users in the period sorted by user and time
+    pub async fn get_statement_transactions(
+        &self,
+        db: &DbClient,
+        user_ids: &[u32],
+        from_ts: u64,
+        to_ts: u64,
+    ) -> anyhow::Result<Vec<WalletTransaction>> {
+        let filter = doc! {
+            "userId": {"$in": user_ids},
+            "status": WalletTransactionStatus::Completed.to_bson()?,
+            "createdTs": {"$gte": from_ts as i64, "$lt": to_ts as i64}
+        };
+        let options = FindOptions::builder()
+            .sort(doc! {"userId": 1, "createdTs": 1})
+            .build();
+        let transactions = db
+            .find::<WalletTransaction>(
+                DB_NAME,
+                COLL_WALLET_TRANSACTIONS,
+                Some(filter),
+                Some(options),
+            )
+            .await?;
+        Ok(transactions)
+    }
+
+    /// Returns the ids of the users having completed transactions in the period,
+    /// sorted and after the given user id, at most `limit` users
+    pub async fn get_statement_user_ids(
+        &self,
+        db: &DbClient,
+        from_ts: u64,
+        to_ts: u64,
+        after_user_id: Option<u32>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<u32>> {
+        let mut filter = doc! {
+            "status": WalletTransactionStatus::Completed.to_bson()?,
+            "createdTs": {"$gte": from_ts as i64, "$lt": to_ts as i64}
+        };
+        if let Some(after_user_id) = after_user_id {
+            filter.insert("userId", doc! {"$gt": after_user_id});
+        }
+        let pipeline = vec![
+            doc! {"$match": filter},
+            doc! {"$group": {"_id": "$userId"}},
+            doc! {"$sort": {"_id": 1}},
+            doc! {"$limit": limit},
+        ];
+        let docs = db
+            .aggregate(DB_NAME, COLL_WALLET_TRANSACTIONS, pipeline, None)
+            .await?;
+        let user_ids = docs
+            .iter()
+            .map(|doc| get_doc_u64(doc, "_id") as u32)
+            .collect();
+        Ok(user_ids)
+    }
+
+    /// Returns the totals of the completed transactions relevant for tax in the period
+    pub async fn get_tax_summary(
+        &self,
+        db: &DbClient,
+        user_id: u32,
+        from_ts: u64,
+        to_ts: u64,
+    ) -> anyhow::Result<TaxSummary> {
+        let pipeline = tax_summary_pipeline(user_id, from_ts, to_ts)?;
+        let docs = db
+            .aggregate(DB_NAME, COLL_WALLET_TRANSACTIONS, pipeline, None)
+            .await?;
+        tax_summary_of(&docs)
+    }
+
+    /// Same as `get_tax_summary` but read in the session, so that the transactions
+    /// committed by a concurrent withdrawal are included
+    pub async fn get_tax_summary_session(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+        from_ts: u64,
+        to_ts: u64,
+    ) -> MongoResult<TaxSummary> {
+        let pipeline = tax_summary_pipeline(user_id, from_ts, to_ts)
+            .map_err(|e| MongoError::custom(e.to_string()))?;
+        let docs = session
+            .aggregate_with_session(DB_NAME, COLL_WALLET_TRANSACTIONS, pipeline, None)
+            .await?;
+        tax_summary_of(&docs).map_err(|e| MongoError::custom(e.to_string()))
+    }
+
+    /// Returns the completed TDS deductions of the user in the period sorted by time
+    pub async fn get_tds_transactions(
+        &self,
+        db: &DbClient,
+        user_id: u32,
+        from_ts: u64,
+        to_ts: u64,
+    ) -> anyhow::Result<Vec<WalletTransaction>> {
+        let filter = doc! {
+            "userId": user_id,
+            "status": WalletTransactionStatus::Completed.to_bson()?,
+            "transactionType": WalltetTransactionType::TdsDeduction.to_bson()?,
+            "createdTs": {"$gte": from_ts as i64, "$lt": to_ts as i64}
+        };
+        let options = FindOptions::builder().sort(doc! {"createdTs": 1}).build();
+        let transactions = db
+            .find::<WalletTransaction>(
+                DB_NAME,
+                COLL_WALLET_TRANSACTIONS,
+                Some(filter),
+                Some(options),
+            )
+            .await?;
+        Ok(transactions)
+    }
+
+    /// Returns the balance after the last completed transaction before the given time
+    pub async fn get_last_balance_before(
+        &self,
+        db: &DbClient,
+        user_id: u32,
+        ts: u64,
+    ) -> anyhow::Result<Money> {
+        let filter = doc! {
+            "userId": user_id,
+            "status": WalletTransactionStatus::Completed.to_bson()?,
+            "createdTs": {"$lt": ts as i64}
+        };
+        let options = FindOneOptions::builder()
+            .sort(doc! {"createdTs": -1})
+            .build();
+        let transaction = db
+            .find_one::<WalletTransaction>(
+                DB_NAME,
+                COLL_WALLET_TRANSACTIONS,
+                Some(filter),
+                Some(options),
+            )
+            .await?;
+        let balance = transaction.and_then(|t| t.balance_after());
+        Ok(balance.unwrap_or_default())
+    }
+
+    /// Returns the bonus lots of the user which are not expired yet,
+    /// sorted by the earliest expiring lot first
+    pub async fn get_upcoming_bonus_expiry(
+        &self,
+        db: &DbClient,
+        user_id: u32,
+    ) -> anyhow::Result<Vec<BonusLot>> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {"userId": user_id, "remaining": {"$gt": 0}, "expiresTs": {"$gt": ts}};
+        let options = FindOptions::builder()
+            .sort(doc! {"expiresTs": 1})
+            .limit(DEFAULT_QUERY_LIMIT as i64)
+            .build();
+        let lots = db
+            .find::<BonusLot>(DB_NAME, COLL_BONUS_LOTS, Some(filter), Some(options))
+            .await?;
+        Ok(lots)
+    }
+
+    /// Returns the bonus lots which are already expired but
+    /// the remaining bonus is not yet deducted from the wallet
+    pub async fn get_expired_bonus_lots(
+        &self,
+        db: &DbClient,
+        limit: i64,
+    ) -> anyhow::Result<Vec<BonusLot>> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {"remaining": {"$gt": 0}, "expiresTs": {"$lte": ts}};
+        let options = FindOptions::builder()
+            .sort(doc! {"expiresTs": 1})
+            .limit(limit)
+            .build();
+        let lots = db
+            .find::<BonusLot>(DB_NAME, COLL_BONUS_LOTS, Some(filter), Some(options))
+            .await?;
+        Ok(lots)
+    }
+
     pub async fn get_wallet_transaction(
         &self,
         db: &DbClient,
@@ -35,6 +234,21 @@ impl WalletHelpers {
         Ok(transaction)
     }
 
+    pub async fn get_wallet_transaction_session(
+        &self,
+        session: &mut DbSession,
+        filter: Document,
+    ) -> MongoResult<Option<WalletTransaction>> {
+        session
+            .find_one_with_session::<WalletTransaction>(
+                DB_NAME,
+                COLL_WALLET_TRANSACTIONS,
+                Some(filter),
+                None,
+            )
+            .await
+    }
+
     pub async fn insert_wallet_transaction(
         &self,
         db: &DbClient,
@@ -85,6 +299,19 @@ impl WalletHelpers {
         Ok(balance)
     }
 
+    /// Returns AppError::Forbidden if the operation is blocked by a wallet freeze
+    pub async fn check_wallet_freeze_session(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+        operation: WalletOperation,
+    ) -> MongoResult<()> {
+        let wallet = self.get_wallet_session(session, user_id).await?;
+        check_wallet_freeze(wallet.as_ref(), operation)?;
+        Ok(())
+    }
+
+    /// Credit or debit the wallet, refused with AppError::Forbidden if the wallet is frozen
     pub async fn update_wallet_with_session(
         &self,
         session: &mut DbSession,
@@ -94,13 +321,110 @@ impl WalletHelpers {
         subtract: bool,
         update_withdrawable: bool,
     ) -> MongoResult<(Money, Money)> {
-        let balance_before = self.get_user_balance_session(session, user_id).await?;
+        self.update_wallet_version_session(
+            session,
+            user_id,
+            real,
+            bonus,
+            subtract,
+            update_withdrawable,
+            None,
+        )
+        .await
+    }
+
+    /// Same as `update_wallet_with_session` but the wallet is updated only if it's version
+    /// matches the expected version, otherwise AppError::Conflict is returned.
+    /// Without an expected version, the version read in the session is used.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_wallet_version_session(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+        real: u64,
+        bonus: u64,
+        subtract: bool,
+        update_withdrawable: bool,
+        expected_version: Option<u64>,
+    ) -> MongoResult<(Money, Money)> {
+        let balances = self
+            .update_balance_session(
+                session,
+                user_id,
+                real,
+                bonus,
+                subtract,
+                update_withdrawable,
+                expected_version,
+            )
+            .await?;
+        if bonus > 0 {
+            if subtract {
+                self.consume_bonus_lots(session, user_id, bonus).await?;
+            } else {
+                self.add_bonus_lot(session, user_id, bonus).await?;
+            }
+        }
+        Ok(balances)
+    }
+
+    /// Credit back a refunded entry fee. The bonus is restored to the lots it's consumed from
+    /// instead of a new lot, so that it keeps its original expiry.
+    pub async fn refund_wallet_with_session(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+        real: u64,
+        bonus: u64,
+    ) -> MongoResult<(Money, Money)> {
+        let balances = self
+            .update_balance_session(session, user_id, real, bonus, false, false, None)
+            .await?;
+        if bonus > 0 {
+            self.restore_bonus_lots(session, user_id, bonus).await?;
+        }
+        Ok(balances)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn update_balance_session(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+        real: u64,
+        bonus: u64,
+        subtract: bool,
+        update_withdrawable: bool,
+        expected_version: Option<u64>,
+    ) -> MongoResult<(Money, Money)> {
+        let mut wallet = self.get_wallet_session(session, user_id).await?;
+        let operation = if subtract {
+            WalletOperation::Debit
+        } else {
+            WalletOperation::Credit
+        };
+        check_wallet_freeze(wallet.as_ref(), operation)?;
+        let mut version = wallet.as_ref().map(|w| w.version).unwrap_or_default();
+        if expected_version.is_some_and(|v| v != version) {
+            return Err(wallet_conflict_error().into());
+        }
+        // expired bonus may not be deducted by the bonus expiry job yet,
+        // it's deducted first so that the debit is not allowed against it
+        if subtract && bonus > 0 && self.expire_due_bonus_lots(session, user_id).await? {
+            wallet = self.get_wallet_session(session, user_id).await?;
+            version = wallet.as_ref().map(|w| w.version).unwrap_or_default();
+        }
+        let balance_before = wallet.map(|w| w.balance()).unwrap_or_default();
+        if subtract && (balance_before.real() < real || balance_before.bonus() < bonus) {
+            let err = AppError::BadRequest("Insufficient wallet balance".into());
+            return Err(err.into());
+        }
         let withdrawable = if update_withdrawable { real } else { 0 };
         let wallet = if subtract {
-            self.sub_wallet(session, user_id, real, bonus, withdrawable)
+            self.sub_wallet(session, user_id, version, real, bonus, withdrawable)
                 .await?
         } else {
-            self.add_wallet(session, user_id, real, bonus, withdrawable)
+            self.add_wallet(session, user_id, version, real, bonus, withdrawable)
                 .await?
         };
         let money = Money::new(real, bonus);
@@ -121,20 +445,173 @@ impl WalletHelpers {
         Ok((balance_before, wallet.balance()))
     }
 
+    /// Deduct the remaining bonus of an expired lot from the wallet
+    /// and record it as a BonusExpired wallet transaction
+    pub async fn expire_bonus_lot_with_session(
+        &self,
+        session: &mut DbSession,
+        lot_id: &ObjectId,
+    ) -> MongoResult<()> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {"_id": lot_id, "remaining": {"$gt": 0}, "expiresTs": {"$lte": ts}};
+        let update = doc! {"$set": {"remaining": 0, "updatedTs": ts}};
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(Some(ReturnDocument::Before))
+            .build();
+        let lot = session
+            .find_one_and_update_with_session::<BonusLot, Document>(
+                DB_NAME,
+                COLL_BONUS_LOTS,
+                filter,
+                update,
+                Some(options),
+            )
+            .await?;
+        // lot is already consumed or expired
+        let Some(lot) = lot else {
+            return Ok(());
+        };
+        let wallet = self.get_wallet_session(session, lot.user_id).await?;
+        let version = wallet.as_ref().map(|w| w.version).unwrap_or_default();
+        let balance_before = wallet.map(|w| w.balance()).unwrap_or_default();
+        let bonus = lot.remaining.min(balance_before.bonus());
+        if bonus == 0 {
+            return Ok(());
+        }
+        let wallet = self
+            .sub_wallet(session, lot.user_id, version, 0, bonus, 0)
+            .await?;
+        let transaction = WalletTransaction::bonus_expired_trans(
+            lot.user_id,
+            bonus,
+            balance_before,
+            wallet.balance(),
+            lot.expires_ts,
+        );
+        session
+            .insert_one_with_session(DB_NAME, COLL_WALLET_TRANSACTIONS, &transaction, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Expire the lots of the user which are already expired but not processed by the
+    /// bonus expiry job yet. Returns true if any lot is expired.
+    async fn expire_due_bonus_lots(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+    ) -> MongoResult<bool> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {"userId": user_id, "remaining": {"$gt": 0}, "expiresTs": {"$lte": ts}};
+        let lots = session
+            .find_with_session::<BonusLot>(DB_NAME, COLL_BONUS_LOTS, Some(filter), None)
+            .await?;
+        let lot_ids = lots.iter().filter_map(|lot| lot.id).collect::<Vec<_>>();
+        for lot_id in &lot_ids {
+            self.expire_bonus_lot_with_session(session, lot_id).await?;
+        }
+        Ok(!lot_ids.is_empty())
+    }
+
+    async fn add_bonus_lot(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+        bonus: u64,
+    ) -> MongoResult<()> {
+        let lot = BonusLot::new(user_id, bonus);
+        session
+            .insert_one_with_session(DB_NAME, COLL_BONUS_LOTS, &lot, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Lots are consumed in the order of expiry, so the refunded bonus is put back to the
+    /// unexpired lots expiring the soonest first. Then to the lots expired most recently,
+    /// which are expired again by the bonus expiry job.
+    async fn restore_bonus_lots(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+        bonus: u64,
+    ) -> MongoResult<()> {
+        let ts = get_epoch_ts();
+        let filter = doc! {"userId": user_id, "$expr": {"$lt": ["$remaining", "$amount"]}};
+        let options = FindOptions::builder().sort(doc! {"expiresTs": 1}).build();
+        let lots = session
+            .find_with_session::<BonusLot>(DB_NAME, COLL_BONUS_LOTS, Some(filter), Some(options))
+            .await?;
+        let (active, expired): (Vec<_>, Vec<_>) =
+            lots.into_iter().partition(|lot| lot.expires_ts > ts);
+        let consumed = active
+            .iter()
+            .chain(expired.iter().rev())
+            .filter_map(|lot| Some((lot.id?, lot.amount - lot.remaining)))
+            .collect::<Vec<_>>();
+        for (lot_id, amount) in allocate_amount(&consumed, bonus) {
+            let filter = doc! {"_id": lot_id};
+            let update = doc! {
+                "$inc": {"remaining": amount as i64},
+                "$set": {"updatedTs": ts as i64}
+            };
+            session
+                .update_one_with_session(DB_NAME, COLL_BONUS_LOTS, filter, update, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn consume_bonus_lots(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+        bonus: u64,
+    ) -> MongoResult<()> {
+        let ts = get_epoch_ts() as i64;
+        let filter = doc! {"userId": user_id, "remaining": {"$gt": 0}, "expiresTs": {"$gt": ts}};
+        let options = FindOptions::builder().sort(doc! {"expiresTs": 1}).build();
+        let lots = session
+            .find_with_session::<BonusLot>(DB_NAME, COLL_BONUS_LOTS, Some(filter), Some(options))
+            .await?;
+        for (lot_id, amount) in allocate_bonus_lots(&lots, bonus) {
+            let amount = amount as i64;
+            let filter = doc! {"_id": lot_id, "remaining": {"$gte": amount}};
+            let update = doc! {"$inc": {"remaining": -amount}, "$set": {"updatedTs": ts}};
+            session
+                .update_one_with_session(DB_NAME, COLL_BONUS_LOTS, filter, update, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn get_wallet_session(
+        &self,
+        session: &mut DbSession,
+        user_id: u32,
+    ) -> MongoResult<Option<Wallet>> {
+        let filter = doc! {"userId": user_id};
+        session
+            .find_one_with_session::<Wallet>(DB_NAME, COLL_WALLETS, Some(filter), None)
+            .await
+    }
+
+    /// Update the wallet and return the updated wallet. Wallet is inserted if `upsert` is true.
+    /// No matching wallet or duplicate key on upsert means the version is changed in between.
     async fn find_and_modify_wallet<U>(
         &self,
         session: &mut DbSession,
         filter: Document,
         update: U,
+        upsert: bool,
     ) -> MongoResult<Wallet>
     where
         U: Into<UpdateModifications> + 'static,
     {
         let options = FindOneAndUpdateOptions::builder()
-            .upsert(Some(true))
+            .upsert(Some(upsert))
             .return_document(Some(ReturnDocument::After))
             .build();
-        let wallet = session
+        let result = session
             .find_one_and_update_with_session::<Wallet, U>(
                 DB_NAME,
                 COLL_WALLETS,
@@ -142,37 +619,45 @@ impl WalletHelpers {
                 update,
                 Some(options),
             )
-            .await?
-            .ok_or(MongoError::custom("not able to update wallet"))?;
-        Ok(wallet)
+            .await;
+        match result {
+            Ok(Some(wallet)) => Ok(wallet),
+            Ok(None) => Err(wallet_conflict_error().into()),
+            Err(e) if is_duplicate_key_error(&e) => Err(wallet_conflict_error().into()),
+            Err(e) => Err(e),
+        }
     }
 
     async fn add_wallet(
         &self,
         session: &mut DbSession,
         user_id: u32,
+        version: u64,
         real: u64,
         bonus: u64,
         withdrawable: u64,
     ) -> MongoResult<Wallet> {
-        let filter = doc! {"userId": user_id};
+        let filter = doc! {"userId": user_id, "version": version_filter(version)};
         let ts = get_epoch_ts() as i64;
         let update = doc! {
             "$inc": {
                 "balance.bonus": bonus as i64,
                 "balance.real": real as i64,
-                "balance.withdrawable": withdrawable as i64
+                "balance.withdrawable": withdrawable as i64,
+                "version": 1
             },
             "$setOnInsert": {"createdTs": ts},
             "$set": {"updatedTs": ts},
         };
-        self.find_and_modify_wallet(session, filter, update).await
+        self.find_and_modify_wallet(session, filter, update, true)
+            .await
     }
 
     async fn sub_wallet(
         &self,
         session: &mut DbSession,
         user_id: u32,
+        version: u64,
         real: u64,
         bonus: u64,
         withdrawable: u64,
@@ -183,6 +668,7 @@ impl WalletHelpers {
         let ts = get_epoch_ts() as i64;
         let filter = doc! {
             "userId": user_id,
+            "version": version_filter(version),
             "balance.real": {"$gte": real},
             "balance.bonus": {"$gte": bonus}
         };
@@ -210,6 +696,7 @@ impl WalletHelpers {
                             0
                         ]
                     },
+                    "version": {"$add": [{"$ifNull": ["$version", 0]}, 1]},
                     "updatedTs": ts
                 }
             },
@@ -225,7 +712,8 @@ impl WalletHelpers {
                 }
             },
         ];
-        self.find_and_modify_wallet(session, filter, update).await
+        self.find_and_modify_wallet(session, filter, update, false)
+            .await
     }
 
     pub async fn update_wallet_transaction_session(
@@ -258,11 +746,106 @@ impl WalletHelpers {
     }
 }
 
+fn tax_summary_pipeline(user_id: u32, from_ts: u64, to_ts: u64) -> anyhow::Result<Vec<Document>> {
+    let transaction_types = [
+        WalltetTransactionType::ContestWin,
+        WalltetTransactionType::PayForContest,
+        WalltetTransactionType::RefundContestEntryFee,
+        WalltetTransactionType::TdsDeduction,
+    ]
+    .iter()
+    .map(|t| t.to_bson())
+    .collect::<anyhow::Result<Vec<_>>>()?;
+    let pipeline = vec![
+        doc! {
+            "$match": {
+                "userId": user_id,
+                "status": WalletTransactionStatus::Completed.to_bson()?,
+                "transactionType": {"$in": transaction_types},
+                "createdTs": {"$gte": from_ts as i64, "$lt": to_ts as i64}
+            }
+        },
+        doc! {
+            "$group": {
+                "_id": "$transactionType",
+                "real": {"$sum": "$amount.real"},
+                "taxable": {"$sum": "$taxableAmount"}
+            }
+        },
+    ];
+    Ok(pipeline)
+}
+
+fn tax_summary_of(docs: &[Document]) -> anyhow::Result<TaxSummary> {
+    let mut summary = TaxSummary::default();
+    for doc in docs.iter() {
+        let real = get_doc_u64(doc, "real");
+        let transaction_type = doc.get("_id").cloned().unwrap_or_default();
+        match mongodb::bson::from_bson(transaction_type)? {
+            WalltetTransactionType::ContestWin => summary.total_winnings = real,
+            WalltetTransactionType::PayForContest => summary.total_entry_fees = real,
+            WalltetTransactionType::RefundContestEntryFee => summary.total_refunds = real,
+            WalltetTransactionType::TdsDeduction => {
+                summary.total_tds = real;
+                summary.taxed_winnings = get_doc_u64(doc, "taxable");
+            }
+            _ => {}
+        }
+    }
+    Ok(summary)
+}
+
+fn wallet_conflict_error() -> AppError {
+    AppError::Conflict("Wallet is modified by another request, please retry".into())
+}
+
+/// Returns AppError::Forbidden if the wallet has an active freeze blocking the operation
+pub fn check_wallet_freeze(
+    wallet: Option<&Wallet>,
+    operation: WalletOperation,
+) -> Result<(), AppError> {
+    let freeze_error = wallet.and_then(|w| w.freeze_error(operation, get_epoch_ts()));
+    match freeze_error {
+        Some(msg) => Err(AppError::Forbidden(msg)),
+        None => Ok(()),
+    }
+}
+
+/// Split the bonus amount among the lots in the given order.
+/// Bonus credited before the lots were introduced is not tracked in any lot,
+/// so the lots may not cover the full amount.
+fn allocate_bonus_lots(lots: &[BonusLot], bonus: u64) -> Vec<(ObjectId, u64)> {
+    let available = lots
+        .iter()
+        .filter_map(|lot| Some((lot.id?, lot.remaining)))
+        .collect::<Vec<_>>();
+    allocate_amount(&available, bonus)
+}
+
+/// Split the amount over the lots in the given order, limited by the available amount of each
+fn allocate_amount(available: &[(ObjectId, u64)], total: u64) -> Vec<(ObjectId, u64)> {
+    let mut pending = total;
+    let mut allocation = vec![];
+    for (lot_id, available) in available {
+        if pending == 0 {
+            break;
+        }
+        let amount = (*available).min(pending);
+        if amount > 0 {
+            allocation.push((*lot_id, amount));
+            pending -= amount;
+        }
+    }
+    allocation
+}
+
 #[cfg(test)]
 mod tests {
 
     use mockall::predicate::{eq, function};
 
+    use crate::config::database::{InsertedId, UpdateResult};
+
     use super::*;
 
     #[tokio::test]
@@ -303,4 +886,232 @@ mod tests {
         let balance = result.unwrap();
         assert_eq!(balance, Money::new(30, 15));
     }
+
+    #[tokio::test]
+    async fn test_update_wallet_version_session() {
+        let user_id = 7;
+        let wallet_helper = WalletHelpers::new();
+        let mut session = DbSession::default();
+        session
+            .expect_find_one_with_session::<Wallet>()
+            .times(4)
+            .returning(move |_, _, _, _| {
+                let wallet = Wallet {
+                    user_id,
+                    balance: Money::new(30, 15),
+                    version: 3,
+                    ..Default::default()
+                };
+                Ok(Some(wallet))
+            });
+        // expected version does not match, wallet is not updated
+        let result = wallet_helper
+            .update_wallet_version_session(&mut session, user_id, 10, 0, false, false, Some(2))
+            .await;
+        match result.map_err(AppError::from_db_error) {
+            Err(AppError::Conflict(_)) => {}
+            _ => panic!(),
+        };
+        // insufficient balance
+        let result = wallet_helper
+            .update_wallet_version_session(&mut session, user_id, 40, 0, true, false, None)
+            .await;
+        match result.map_err(AppError::from_db_error) {
+            Err(AppError::BadRequest(msg)) => assert_eq!(msg, "Insufficient wallet balance"),
+            _ => panic!(),
+        };
+        // wallet modified in between, update matched no wallet
+        session
+            .expect_find_with_session::<BonusLot>()
+            .once()
+            .withf(|_, coll, _, _| coll == COLL_BONUS_LOTS)
+            .returning(|_, _, _, _| Ok(vec![]));
+        session
+            .expect_find_one_and_update_with_session::<Wallet, Vec<Document>>()
+            .once()
+            .withf(|_, _, filter, _, _| filter.get_i64("version") == Ok(3))
+            .returning(|_, _, _, _, _| Ok(None));
+        let result = wallet_helper
+            .update_wallet_version_session(&mut session, user_id, 10, 5, true, false, Some(3))
+            .await;
+        match result.map_err(AppError::from_db_error) {
+            Err(AppError::Conflict(_)) => {}
+            _ => panic!(),
+        };
+        // version matched and wallet updated
+        session
+            .expect_find_one_and_update_with_session::<Wallet, Document>()
+            .once()
+            .withf(|_, _, filter, update, _| {
+                filter.get_i64("version") == Ok(3)
+                    && update.get_document("$inc").unwrap().get_i32("version") == Ok(1)
+            })
+            .returning(move |_, _, _, _, _| {
+                let wallet = Wallet {
+                    user_id,
+                    balance: Money::new(40, 15),
+                    version: 4,
+                    ..Default::default()
+                };
+                Ok(Some(wallet))
+            });
+        let (before, after) = wallet_helper
+            .update_wallet_version_session(&mut session, user_id, 10, 0, false, false, None)
+            .await
+            .unwrap();
+        assert_eq!(before, Money::new(30, 15));
+        assert_eq!(after, Money::new(40, 15));
+    }
+
+    #[tokio::test]
+    async fn test_update_wallet_session_expires_due_lots() {
+        let user_id = 7;
+        let ts = get_epoch_ts();
+        let lot = BonusLot {
+            id: Some(ObjectId::new()),
+            user_id,
+            amount: 10,
+            remaining: 10,
+            expires_ts: ts - 100,
+            ..Default::default()
+        };
+        let lot_id = lot.id.unwrap();
+        let lots = vec![lot.clone()];
+        let wallet = move |bonus, version| Wallet {
+            user_id,
+            balance: Money::new(30, bonus),
+            version,
+            ..Default::default()
+        };
+        let wallet_helper = WalletHelpers::new();
+        let mut session = DbSession::default();
+        let mut seq = mockall::Sequence::new();
+        session
+            .expect_find_one_with_session::<Wallet>()
+            .times(2)
+            .in_sequence(&mut seq)
+            .returning(move |_, _, _, _| Ok(Some(wallet(15, 3))));
+        // bonus of the expired lot is still in the wallet as the expiry job is not run yet
+        session
+            .expect_find_with_session::<BonusLot>()
+            .once()
+            .withf(move |_, coll, filter, _| {
+                let filter = filter.as_ref().unwrap();
+                coll == COLL_BONUS_LOTS
+                    && filter
+                        .get_document("expiresTs")
+                        .unwrap()
+                        .contains_key("$lte")
+            })
+            .returning(move |_, _, _, _| Ok(lots.clone()));
+        session
+            .expect_find_one_and_update_with_session::<BonusLot, Document>()
+            .once()
+            .withf(move |_, _, filter, _, _| filter.get_object_id("_id") == Ok(lot_id))
+            .returning(move |_, _, _, _, _| Ok(Some(lot.clone())));
+        session
+            .expect_find_one_and_update_with_session::<Wallet, Vec<Document>>()
+            .once()
+            .withf(|_, _, filter, _, _| filter.get_i64("version") == Ok(3))
+            .returning(move |_, _, _, _, _| Ok(Some(wallet(5, 4))));
+        session
+            .expect_insert_one_with_session::<WalletTransaction>()
+            .once()
+            .withf(|_, coll, _, _| coll == COLL_WALLET_TRANSACTIONS)
+            .returning(move |_, _, _, _| Ok(InsertedId(lot_id.to_hex())));
+        session
+            .expect_find_one_with_session::<Wallet>()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move |_, _, _, _| Ok(Some(wallet(5, 4))));
+        // debit is not allowed against the expired bonus
+        let result = wallet_helper
+            .update_wallet_with_session(&mut session, user_id, 0, 10, true, false)
+            .await;
+        match result.map_err(AppError::from_db_error) {
+            Err(AppError::BadRequest(msg)) => assert_eq!(msg, "Insufficient wallet balance"),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn test_allocate_bonus_lots() {
+        let lots = [30, 50, 20]
+            .into_iter()
+            .map(|remaining| BonusLot {
+                id: Some(ObjectId::new()),
+                remaining,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let allocation = allocate_bonus_lots(&lots, 60);
+        assert_eq!(allocation.len(), 2);
+        assert_eq!(allocation[0], (lots[0].id.unwrap(), 30));
+        assert_eq!(allocation[1], (lots[1].id.unwrap(), 30));
+
+        let allocation = allocate_bonus_lots(&lots, 150);
+        let total = allocation.iter().map(|(_, amount)| amount).sum::<u64>();
+        assert_eq!(allocation.len(), 3);
+        assert_eq!(total, 100);
+
+        let allocation = allocate_bonus_lots(&lots, 0);
+        assert!(allocation.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restore_bonus_lots() {
+        let user_id = 7;
+        let ts = get_epoch_ts();
+        let lot = |remaining, expires_ts| BonusLot {
+            id: Some(ObjectId::new()),
+            user_id,
+            amount: 50,
+            remaining,
+            expires_ts,
+            ..Default::default()
+        };
+        // sorted by expiry, the expired lots are restored after the unexpired ones
+        let lots = vec![
+            lot(0, ts - 200),
+            lot(10, ts - 100),
+            lot(30, ts + 100),
+            lot(45, ts + 200),
+        ];
+        let expected = vec![
+            (lots[2].id.unwrap(), 20),
+            (lots[3].id.unwrap(), 5),
+            (lots[1].id.unwrap(), 15),
+        ];
+        let wallet_helper = WalletHelpers::new();
+        let mut session = DbSession::default();
+        session
+            .expect_find_with_session::<BonusLot>()
+            .once()
+            .withf(|_, coll, filter, _| coll == COLL_BONUS_LOTS && filter.is_some())
+            .returning(move |_, _, _, _| Ok(lots.clone()));
+        let mut seq = mockall::Sequence::new();
+        for (lot_id, amount) in expected {
+            session
+                .expect_update_one_with_session()
+                .once()
+                .in_sequence(&mut seq)
+                .withf(move |_, coll, filter, update, _| {
+                    coll == COLL_BONUS_LOTS
+                        && filter.get_object_id("_id") == Ok(lot_id)
+                        && update.get_document("$inc").unwrap().get_i64("remaining") == Ok(amount)
+                })
+                .returning(|_, _, _, _, _| {
+                    let result = UpdateResult {
+                        matched_count: 1,
+                        modified_count: 1,
+                        upserted_id: None,
+                    };
+                    Ok(result)
+                });
+        }
+        let result = wallet_helper
+            .restore_bonus_lots(&mut session, user_id, 40)
+            .await;
+        assert!(result.is_ok());
+    }
 }
@@ -0,0 +1,160 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::FutureExt;
+
+use crate::{config::AppState, constants::*};
+
+/// Periodically deduct the bonus of the lots which are already expired
+pub async fn run_bonus_expiry_job(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(BONUS_EXPIRY_JOB_INTERVAL));
+    loop {
+        interval.tick().await;
+        tracing::debug!("Running bonus expiry job");
+        if let Err(e) = expire_bonus_lots(state.clone()).await {
+            tracing::error!("Error in bonus expiry job: {:?}", e);
+        }
+    }
+}
+
+/// Expire the lots in batches till no more expired lots are left.
+/// A run is stopped if no lot of a batch could be expired, to be retried in the next run.
+async fn expire_bonus_lots(state: Arc<AppState>) -> anyhow::Result<()> {
+    loop {
+        let lots = state
+            .helpers()
+            .wallet_helpers()
+            .get_expired_bonus_lots(state.db(), BONUS_EXPIRY_JOB_FETCH_LIMIT)
+            .await?;
+        let fetched = lots.len() as i64;
+        let mut expired = 0;
+        for lot in lots {
+            let Some(lot_id) = lot.id else {
+                continue;
+            };
+            let cloned_state = state.clone();
+            let result = state
+                .db()
+                .execute_transaction(None, None, move |session| {
+                    let cloned_state = cloned_state.clone();
+                    async move {
+                        cloned_state
+                            .helpers()
+                            .wallet_helpers()
+                            .expire_bonus_lot_with_session(session, &lot_id)
+                            .await
+                    }
+                    .boxed()
+                })
+                .await;
+            match result {
+                Ok(_) => expired += 1,
+                Err(e) => tracing::error!("Not able to expire bonus lot {}: {:?}", lot_id, e),
+            }
+        }
+        if fetched < BONUS_EXPIRY_JOB_FETCH_LIMIT || expired == 0 {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{always, eq, function};
+    use mongodb::bson::oid::ObjectId;
+
+    use crate::{import_double, models::BonusLot};
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_expire_bonus_lots() {
+        let mut state = AppState::mock();
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_expired_bonus_lots()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(BONUS_EXPIRY_JOB_FETCH_LIMIT),
+            )
+            .returning(|_, _| {
+                let lot = BonusLot {
+                    id: Some(ObjectId::new()),
+                    ..Default::default()
+                };
+                Ok(vec![lot.clone(), lot])
+            });
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .times(2)
+            .with(
+                function(Option::is_none),
+                function(Option::is_none),
+                always(),
+            )
+            .returning(|_, _, _| Ok(()));
+        let result = expire_bonus_lots(Arc::new(state)).await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn test_expire_bonus_lots_in_batches() {
+        let lots = |count: i64| {
+            (0..count)
+                .map(|_| BonusLot {
+                    id: Some(ObjectId::new()),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut state = AppState::mock();
+        let wallet_helpers = state.get_mut_helpers().mut_wallet_helpers();
+        let mut seq = mockall::Sequence::new();
+        // full batches are followed by the next batch till a partial batch is fetched
+        wallet_helpers
+            .expect_get_expired_bonus_lots()
+            .times(2)
+            .in_sequence(&mut seq)
+            .returning(move |_, _| Ok(lots(BONUS_EXPIRY_JOB_FETCH_LIMIT)));
+        wallet_helpers
+            .expect_get_expired_bonus_lots()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move |_, _| Ok(lots(3)));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .times(2 * BONUS_EXPIRY_JOB_FETCH_LIMIT as usize + 3)
+            .returning(|_, _, _| Ok(()));
+        let result = expire_bonus_lots(Arc::new(state)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_expire_bonus_lots_batch_failed() {
+        let mut state = AppState::mock();
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_expired_bonus_lots()
+            .once()
+            .returning(|_, _| {
+                let lot = BonusLot {
+                    id: Some(ObjectId::new()),
+                    ..Default::default()
+                };
+                Ok(vec![lot; BONUS_EXPIRY_JOB_FETCH_LIMIT as usize])
+            });
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .times(BONUS_EXPIRY_JOB_FETCH_LIMIT as usize)
+            .returning(|_, _, _| Err(mongodb::error::Error::custom("error")));
+        let result = expire_bonus_lots(Arc::new(state)).await;
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,406 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+use futures::FutureExt;
+use mongodb::error::Result as MongoResult;
+
+use crate::{
+    config::{AppError, AppState},
+    constants::*,
+    import_double,
+    models::*,
+};
+
+import_double!(DbSession);
+
+/// Periodically cancel the started contests short of the minimum players
+/// and refund the entry fees of the cancelled contests
+pub async fn run_cancel_contest_job(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(CANCEL_CONTEST_JOB_INTERVAL));
+    loop {
+        interval.tick().await;
+        tracing::debug!("Running cancel contest job");
+        if let Err(e) = cancel_contests(state.clone()).await {
+            tracing::error!("Error in cancel contest job: {:?}", e);
+        }
+        if let Err(e) = refund_cancelled_contests(state.clone()).await {
+            tracing::error!("Error in contest refund job: {:?}", e);
+        }
+    }
+}
+
+async fn cancel_contests(state: Arc<AppState>) -> anyhow::Result<()> {
+    let contest_helpers = state.helpers().contest_helpers();
+    let contests = contest_helpers
+        .get_contests_to_cancel(state.db(), CANCEL_CONTEST_JOB_FETCH_LIMIT)
+        .await?;
+    for contest in contests {
+        let Some(contest_id) = contest.id else {
+            continue;
+        };
+        let cancelled = contest_helpers
+            .cancel_contest(state.db(), &contest_id, None)
+            .await?;
+        if cancelled {
+            tracing::info!("Contest {} cancelled for minimum players", contest_id);
+            state
+                .publish_contest_event(contest_id, ContestUpdate::status(ContestStatus::Cancelled));
+        }
+    }
+    Ok(())
+}
+
+async fn refund_cancelled_contests(state: Arc<AppState>) -> anyhow::Result<()> {
+    let contests = state
+        .helpers()
+        .contest_helpers()
+        .get_refund_pending_contests(state.db(), CANCEL_CONTEST_JOB_FETCH_LIMIT)
+        .await?;
+    for contest in contests {
+        if let Err(e) = refund_contest(state.clone(), contest.clone()).await {
+            tracing::error!("Not able to refund contest {:?}: {:?}", contest.id, e);
+        }
+    }
+    Ok(())
+}
+
+/// Refund the entry fees of each player of the cancelled contest in a separate transaction.
+/// The contest is marked refunded only after all the refunds succeed,
+/// otherwise the pending refunds are retried in the next run.
+/// Refunds rejected e.g. for a wallet freeze are put on hold instead of failing the contest.
+pub(crate) async fn refund_contest(state: Arc<AppState>, contest: Contest) -> anyhow::Result<()> {
+    let contest_id = contest.id.ok_or(anyhow::anyhow!("Contest id is missing"))?;
+    let contest_helpers = state.helpers().contest_helpers();
+    let play_trackers = contest_helpers
+        .get_unrefunded_play_trackers(state.db(), &contest_id)
+        .await?;
+    // all the entries of a player are refunded together with a single notification
+    let mut user_play_trackers: BTreeMap<u32, Vec<PlayTracker>> = BTreeMap::new();
+    for play_tracker in play_trackers {
+        user_play_trackers
+            .entry(play_tracker.user_id)
+            .or_default()
+            .push(play_tracker);
+    }
+    let contest = Arc::new(contest);
+    let mut failed = 0;
+    for (user_id, play_trackers) in user_play_trackers {
+        let entry_nos = play_trackers.iter().map(|p| p.entry_no).collect::<Vec<_>>();
+        let cloned_state = state.clone();
+        let cloned_contest = contest.clone();
+        let result = state
+            .db()
+            .execute_transaction(None, None, move |session| {
+                let cloned_state = cloned_state.clone();
+                let cloned_contest = cloned_contest.clone();
+                let play_trackers = play_trackers.clone();
+                async move {
+                    refund_entry_fees(&cloned_state, session, &cloned_contest, &play_trackers).await
+                }
+                .boxed()
+            })
+            .await;
+        let Err(e) = result else {
+            continue;
+        };
+        let err = AppError::from_db_error(e);
+        if let Some(reason) = err.rejection() {
+            // the contest is not held back by a refund which can't be credited
+            tracing::error!(
+                "Refund of contest {} to user {} is put on hold: {}",
+                contest_id,
+                user_id,
+                reason
+            );
+            let held = contest_helpers
+                .hold_play_credit(state.db(), &contest_id, user_id, &entry_nos, reason)
+                .await;
+            if held.is_ok() {
+                continue;
+            }
+        }
+        tracing::error!(
+            "Not able to refund entry fee of contest {} to user {}: {:?}",
+            contest_id,
+            user_id,
+            err
+        );
+        failed += 1;
+    }
+    if failed > 0 {
+        anyhow::bail!("Entry fee refund failed for {} players", failed);
+    }
+    contest_helpers
+        .mark_contest_refunded(state.db(), &contest_id)
+        .await?;
+    Ok(())
+}
+
+/// Credit back the real and bonus split of the entry fees paid by the player for each entry,
+/// record the RefundContestEntryFee transactions and queue the cancel notification with the
+/// total refunded. Nothing is paid for the practice contests, only the notification is queued.
+async fn refund_entry_fees(
+    state: &AppState,
+    session: &mut DbSession,
+    contest: &Contest,
+    play_trackers: &[PlayTracker],
+) -> MongoResult<()> {
+    let Some(user_id) = play_trackers.first().map(|p| p.user_id) else {
+        return Ok(());
+    };
+    let mut refunded_entries = 0;
+    let mut refund_amount = 0;
+    for play_tracker in play_trackers {
+        let contest_id = play_tracker.contest_id;
+        let refunded = state
+            .helpers()
+            .contest_helpers()
+            .mark_refunded_session(session, &contest_id, user_id, play_tracker.entry_no)
+            .await?;
+        if !refunded {
+            continue;
+        }
+        refunded_entries += 1;
+        if contest.is_practice() {
+            continue;
+        }
+        let entry_fee = play_tracker.entry_fee;
+        let (balance_before, balance_after) = state
+            .helpers()
+            .wallet_helpers()
+            .refund_wallet_with_session(session, user_id, entry_fee.real(), entry_fee.bonus())
+            .await?;
+        let transaction = WalletTransaction::refund_contest_entry_fee_trans(
+            user_id,
+            &contest_id.to_hex(),
+            entry_fee,
+            balance_before,
+            balance_after,
+        );
+        session
+            .insert_one_with_session(DB_NAME, COLL_WALLET_TRANSACTIONS, &transaction, None)
+            .await?;
+        refund_amount += entry_fee.real() + entry_fee.bonus();
+    }
+    if refunded_entries == 0 {
+        return Ok(());
+    }
+    let data = HashMap::from([
+        ("contestTitle".to_string(), contest.title.clone()),
+        ("minPlayers".to_string(), contest.min_players.to_string()),
+        ("entries".to_string(), refunded_entries.to_string()),
+        ("refundAmount".to_string(), refund_amount.to_string()),
+    ]);
+    let notification = NotificationRequest::new(user_id, EVENT_CONTEST_CANCEL_MIN_PLAYER, data);
+    session
+        .insert_one_with_session(DB_NAME, COLL_NOTIFICATION_REQUESTS, &notification, None)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{always, eq, function};
+    use mongodb::bson::oid::ObjectId;
+
+    use crate::{config::database::InsertedId, import_double};
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_contests() {
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contests_to_cancel()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(CANCEL_CONTEST_JOB_FETCH_LIMIT),
+            )
+            .returning(move |_, _| {
+                let contest = Contest {
+                    id: Some(contest_id),
+                    ..Default::default()
+                };
+                Ok(vec![contest])
+            });
+        contest_helpers
+            .expect_cancel_contest()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id), eq(None))
+            .returning(|_, _, _| Ok(true));
+        let result = cancel_contests(Arc::new(state)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refund_contest() {
+        let contest_id = ObjectId::new();
+        let contest = Contest {
+            id: Some(contest_id),
+            status: ContestStatus::Cancelled,
+            ..Default::default()
+        };
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_unrefunded_play_trackers()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(move |_, _| {
+                // user 2 has joined with two entries
+                let play_trackers = [1, 2, 2, 3]
+                    .into_iter()
+                    .map(|user_id| PlayTracker::new(contest_id, user_id, Money::new(40, 10)))
+                    .collect();
+                Ok(play_trackers)
+            });
+        contest_helpers
+            .expect_mark_contest_refunded()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(|_, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .times(3)
+            .with(
+                function(Option::is_none),
+                function(Option::is_none),
+                always(),
+            )
+            .returning(|_, _, _| Ok(()));
+        let result = refund_contest(Arc::new(state), contest).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refund_contest_failed() {
+        let contest_id = ObjectId::new();
+        let contest = Contest {
+            id: Some(contest_id),
+            ..Default::default()
+        };
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_unrefunded_play_trackers()
+            .once()
+            .returning(move |_, _| Ok(vec![PlayTracker::new(contest_id, 1, Money::new(50, 0))]));
+        contest_helpers.expect_mark_contest_refunded().never();
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .returning(|_, _, _| Err(mongodb::error::Error::custom("error")));
+        let result = refund_contest(Arc::new(state), contest).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refund_contest_rejected() {
+        let contest_id = ObjectId::new();
+        let contest = Contest {
+            id: Some(contest_id),
+            ..Default::default()
+        };
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_unrefunded_play_trackers()
+            .once()
+            .returning(move |_, _| {
+                let play_trackers = (1..=2)
+                    .map(|entry_no| PlayTracker {
+                        entry_no,
+                        ..PlayTracker::new(contest_id, 1, Money::new(50, 0))
+                    })
+                    .collect();
+                Ok(play_trackers)
+            });
+        // credits of the user are frozen, both the entries are put on hold
+        contest_helpers
+            .expect_hold_play_credit()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(1),
+                function(|entry_nos: &[u32]| entry_nos == [1, 2]),
+                eq("Wallet credits are frozen"),
+            )
+            .returning(|_, _, _, _, _| Ok(()));
+        contest_helpers
+            .expect_mark_contest_refunded()
+            .once()
+            .returning(|_, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .returning(|_, _, _| {
+                let err = AppError::Forbidden("Wallet credits are frozen".into());
+                Err(err.into())
+            });
+        let result = refund_contest(Arc::new(state), contest).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refund_entry_fees() {
+        let contest_id = ObjectId::new();
+        let user_id = 2;
+        let contest = Contest {
+            id: Some(contest_id),
+            title: "Weekend quiz".into(),
+            entry_fee: 50,
+            ..Default::default()
+        };
+        let play_trackers = (1..=2)
+            .map(|entry_no| PlayTracker {
+                entry_no,
+                ..PlayTracker::new(contest_id, user_id, Money::new(40, 10))
+            })
+            .collect::<Vec<_>>();
+        let mut state = AppState::mock();
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_mark_refunded_session()
+            .times(2)
+            .with(always(), eq(contest_id), eq(user_id), always())
+            .returning(|_, _, _, _| Ok(true));
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_refund_wallet_with_session()
+            .times(2)
+            .with(always(), eq(user_id), eq(40), eq(10))
+            .returning(|_, _, _, _| Ok((Money::new(0, 0), Money::new(40, 10))));
+        let mut session = DbSession::default();
+        session
+            .expect_insert_one_with_session::<WalletTransaction>()
+            .times(2)
+            .returning(|_, _, _, _| Ok(InsertedId(ObjectId::new().to_hex())));
+        session
+            .expect_insert_one_with_session::<NotificationRequest>()
+            .once()
+            .withf(move |_, coll, notification, _| {
+                coll == COLL_NOTIFICATION_REQUESTS
+                    && notification.user_id == user_id
+                    && notification.data.get("entries") == Some(&"2".to_string())
+                    && notification.data.get("refundAmount") == Some(&"100".to_string())
+            })
+            .returning(|_, _, _, _| Ok(InsertedId(ObjectId::new().to_hex())));
+        let result = refund_entry_fees(&state, &mut session, &contest, &play_trackers).await;
+        assert!(result.is_ok());
+    }
+}
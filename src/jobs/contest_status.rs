@@ -0,0 +1,225 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    config::AppState, constants::*, handlers::contest::waitlist::promote_waitlist, models::*,
+    utils::get_epoch_ts,
+};
+
+/// Periodically move the published contests to live at the start time
+/// and the live contests to ended at the end time, and fill the freed spots
+/// of the contests from their waitlists
+pub async fn run_contest_status_job(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(CONTEST_STATUS_JOB_INTERVAL));
+    loop {
+        interval.tick().await;
+        tracing::debug!("Running contest status job");
+        if let Err(e) = update_contest_status(state.clone()).await {
+            tracing::error!("Error in contest status job: {:?}", e);
+        }
+        if let Err(e) = promote_waitlists(state.clone()).await {
+            tracing::error!("Error in waitlist promotion job: {:?}", e);
+        }
+    }
+}
+
+async fn update_contest_status(state: Arc<AppState>) -> anyhow::Result<()> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    // contests short of minimum players are cancelled by the cancel contest job
+    let to_start = contest_helpers
+        .get_contests_to_start(db, CONTEST_STATUS_JOB_FETCH_LIMIT)
+        .await?;
+    let to_end = contest_helpers
+        .get_contests_to_end(db, CONTEST_STATUS_JOB_FETCH_LIMIT)
+        .await?;
+    let transitions = to_start
+        .iter()
+        .map(|c| (c, ContestStatus::Live))
+        .chain(to_end.iter().map(|c| (c, ContestStatus::Ended)));
+    for (contest, to) in transitions {
+        let Some(contest_id) = contest.id else {
+            continue;
+        };
+        let updated = contest_helpers
+            .transition_status(db, &contest_id, &contest.status, &to, None, None)
+            .await?;
+        if updated.is_some() {
+            tracing::info!("Contest {} is {:?} now", contest_id, to);
+            state.publish_contest_event(contest_id, ContestUpdate::status(to));
+        }
+    }
+    Ok(())
+}
+
+/// Promote the waiting users of the contests which have spots, whichever way the spots
+/// are freed. Interrupted promotions are retried and the waitlists of the contests
+/// closed for joining are marked failed.
+async fn promote_waitlists(state: Arc<AppState>) -> anyhow::Result<()> {
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    let promoting_before = get_epoch_ts().saturating_sub(WAITLIST_PROMOTING_TIMEOUT_SECS);
+    let reclaimed = contest_helpers
+        .reclaim_waitlist_entries(db, promoting_before)
+        .await?;
+    if reclaimed > 0 {
+        tracing::info!("{} stale waitlist promotions put back to wait", reclaimed);
+    }
+    let contest_ids = contest_helpers
+        .get_waitlisted_contest_ids(db, CONTEST_STATUS_JOB_FETCH_LIMIT)
+        .await?;
+    for contest_id in contest_ids {
+        let Some(contest) = contest_helpers.get_contest(db, &contest_id).await? else {
+            continue;
+        };
+        if !contest.is_joinable(get_epoch_ts()) {
+            let reason = "Contest is closed for joining";
+            contest_helpers
+                .close_waitlist(db, &contest_id, reason)
+                .await?;
+            continue;
+        }
+        if let Err(e) = promote_waitlist(&state, &contest_id).await {
+            tracing::error!(
+                "Not able to promote waitlist of contest {}: {:?}",
+                contest_id,
+                e
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+    use mongodb::bson::oid::ObjectId;
+
+    use crate::import_double;
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update_contest_status() {
+        let (start_id, end_id) = (ObjectId::new(), ObjectId::new());
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_contests_to_start()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(CONTEST_STATUS_JOB_FETCH_LIMIT),
+            )
+            .returning(move |_, _| {
+                let contest = Contest {
+                    id: Some(start_id),
+                    status: ContestStatus::Published,
+                    ..Default::default()
+                };
+                Ok(vec![contest])
+            });
+        contest_helpers
+            .expect_get_contests_to_end()
+            .once()
+            .returning(move |_, _| {
+                let contest = Contest {
+                    id: Some(end_id),
+                    status: ContestStatus::Live,
+                    ..Default::default()
+                };
+                Ok(vec![contest])
+            });
+        contest_helpers
+            .expect_transition_status()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(start_id),
+                eq(ContestStatus::Published),
+                eq(ContestStatus::Live),
+                eq(None),
+                eq(None),
+            )
+            .returning(|_, _, _, _, _, _| Ok(Some(Contest::default())));
+        contest_helpers
+            .expect_transition_status()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(end_id),
+                eq(ContestStatus::Live),
+                eq(ContestStatus::Ended),
+                eq(None),
+                eq(None),
+            )
+            .returning(|_, _, _, _, _, _| Ok(None));
+        let result = update_contest_status(Arc::new(state)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_promote_waitlists() {
+        let (open_id, closed_id) = (ObjectId::new(), ObjectId::new());
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_reclaim_waitlist_entries()
+            .once()
+            .withf(|_, promoting_before| {
+                *promoting_before <= get_epoch_ts() - WAITLIST_PROMOTING_TIMEOUT_SECS
+            })
+            .returning(|_, _| Ok(1));
+        contest_helpers
+            .expect_get_waitlisted_contest_ids()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(CONTEST_STATUS_JOB_FETCH_LIMIT),
+            )
+            .returning(move |_, _| Ok(vec![closed_id, open_id]));
+        // closed contest is started, the open contest is still full
+        contest_helpers
+            .expect_get_contest()
+            .with(function(|_: &DbClient| true), eq(closed_id))
+            .once()
+            .returning(move |_, _| {
+                let contest = Contest {
+                    id: Some(closed_id),
+                    status: ContestStatus::Live,
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_get_contest()
+            .with(function(|_: &DbClient| true), eq(open_id))
+            .times(2)
+            .returning(move |_, _| {
+                let contest = Contest {
+                    id: Some(open_id),
+                    status: ContestStatus::Published,
+                    start_ts: get_epoch_ts() + 3600,
+                    joined_players: 10,
+                    max_players: 10,
+                    waitlist: true,
+                    ..Default::default()
+                };
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_close_waitlist()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(closed_id),
+                eq("Contest is closed for joining"),
+            )
+            .returning(|_, _, _| Ok(()));
+        contest_helpers.expect_claim_waitlist_entry().never();
+        let result = promote_waitlists(Arc::new(state)).await;
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,152 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{config::AppState, constants::*, models::*, utils::get_epoch_ts};
+
+/// Periodically create the contests of the active templates
+/// starting within `CONTEST_SCHEDULE_AHEAD_SECS`
+pub async fn run_contest_template_job(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(CONTEST_TEMPLATE_JOB_INTERVAL));
+    loop {
+        interval.tick().await;
+        tracing::debug!("Running contest template job");
+        if let Err(e) = schedule_contests(state.clone()).await {
+            tracing::error!("Error in contest template job: {:?}", e);
+        }
+    }
+}
+
+async fn schedule_contests(state: Arc<AppState>) -> anyhow::Result<()> {
+    let templates = state
+        .helpers()
+        .contest_helpers()
+        .get_active_contest_templates(state.db(), CONTEST_TEMPLATE_JOB_FETCH_LIMIT)
+        .await?;
+    let mut failed = 0;
+    for template in templates {
+        if let Err(e) = schedule_template_contests(&state, &template).await {
+            tracing::error!("Not able to schedule template {:?}: {:?}", template.id, e);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("Scheduling failed for {} templates", failed);
+    }
+    Ok(())
+}
+
+/// Create the due contests of the template in order of the start time.
+/// Scheduled time of the template is moved after each contest,
+/// so that a failed one is retried in the next run.
+async fn schedule_template_contests(
+    state: &AppState,
+    template: &ContestTemplate,
+) -> anyhow::Result<()> {
+    let template_id = template
+        .id
+        .ok_or(anyhow::anyhow!("Template id is missing"))?;
+    let contest_helpers = state.helpers().contest_helpers();
+    let ts = get_epoch_ts();
+    for start_ts in template.due_occurrences(ts, ts + CONTEST_SCHEDULE_AHEAD_SECS) {
+        let contest = template.contest_at(start_ts);
+        let inserted = contest_helpers
+            .insert_scheduled_contest(state.db(), &contest)
+            .await?;
+        if inserted {
+            tracing::info!("Contest {} created from template", contest.title);
+        } else {
+            tracing::warn!("Contest {} already exists, skipped", contest.title);
+        }
+        contest_helpers
+            .set_template_scheduled_till(state.db(), &template_id, start_ts)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+    use mongodb::bson::oid::ObjectId;
+
+    use crate::import_double;
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn daily_template(template_id: ObjectId) -> ContestTemplate {
+        ContestTemplate {
+            id: Some(template_id),
+            title: "Daily trivia".into(),
+            duration_secs: 30 * 60,
+            recurrence: Recurrence {
+                days: vec![],
+                hour: 21,
+                minute: 0,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schedule_contests() {
+        let template_id = ObjectId::new();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_active_contest_templates()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(CONTEST_TEMPLATE_JOB_FETCH_LIMIT),
+            )
+            .returning(move |_, _| Ok(vec![daily_template(template_id)]));
+        // one contest a day for the days scheduled ahead, the first one already exists
+        let mut seq = mockall::Sequence::new();
+        contest_helpers
+            .expect_insert_scheduled_contest()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(false));
+        contest_helpers
+            .expect_insert_scheduled_contest()
+            .times(2)
+            .in_sequence(&mut seq)
+            .withf(move |_, contest: &Contest| {
+                contest.template_id == Some(template_id)
+                    && contest.title.starts_with("Daily trivia ")
+                    && contest.status == ContestStatus::Published
+            })
+            .returning(|_, _| Ok(true));
+        contest_helpers
+            .expect_set_template_scheduled_till()
+            .times(3)
+            .with(
+                function(|_: &DbClient| true),
+                eq(template_id),
+                function(|ts: &u64| *ts > get_epoch_ts()),
+            )
+            .returning(|_, _, _| Ok(()));
+        let result = schedule_contests(Arc::new(state)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_schedule_contests_failed() {
+        let template_id = ObjectId::new();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_active_contest_templates()
+            .once()
+            .returning(move |_, _| Ok(vec![daily_template(template_id)]));
+        contest_helpers
+            .expect_insert_scheduled_contest()
+            .once()
+            .returning(|_, _| Err(anyhow::anyhow!("error")));
+        contest_helpers.expect_set_template_scheduled_till().never();
+        let result = schedule_contests(Arc::new(state)).await;
+        assert!(result.is_err());
+    }
+}
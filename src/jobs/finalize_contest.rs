@@ -0,0 +1,621 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use futures::FutureExt;
+use mongodb::error::Result as MongoResult;
+
+use crate::{
+    config::{AppError, AppState},
+    constants::*,
+    import_double,
+    models::*,
+};
+
+import_double!(DbSession);
+
+/// Periodically finalize the ended contests and credit the prizes to the winners
+pub async fn run_finalize_contest_job(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(FINALIZE_CONTEST_JOB_INTERVAL));
+    loop {
+        interval.tick().await;
+        tracing::debug!("Running finalize contest job");
+        if let Err(e) = finalize_contests(state.clone()).await {
+            tracing::error!("Error in finalize contest job: {:?}", e);
+        }
+    }
+}
+
+async fn finalize_contests(state: Arc<AppState>) -> anyhow::Result<()> {
+    let contests = state
+        .helpers()
+        .contest_helpers()
+        .get_ended_contests(state.db(), FINALIZE_CONTEST_JOB_FETCH_LIMIT)
+        .await?;
+    for contest in contests {
+        if let Err(e) = finalize_contest(state.clone(), contest.clone()).await {
+            tracing::error!("Not able to finalize contest {:?}: {:?}", contest.id, e);
+        }
+    }
+    Ok(())
+}
+
+/// Move the ended contest to finalizing, credit the prize of each winner
+/// in a separate transaction, settle the prize pool with the house and then
+/// mark the contest finalized. If any step fails, the contest is left as it is
+/// to be retried in the next run, prizes already credited are skipped then.
+/// Prizes rejected e.g. for a wallet freeze are put on hold instead of failing the contest.
+async fn finalize_contest(state: Arc<AppState>, contest: Contest) -> anyhow::Result<()> {
+    let contest_id = contest.id.ok_or(anyhow::anyhow!("Contest id is missing"))?;
+    let contest_helpers = state.helpers().contest_helpers();
+    let contest = match contest.status {
+        ContestStatus::Ended => {
+            let contest = contest_helpers
+                .transition_status(
+                    state.db(),
+                    &contest_id,
+                    &ContestStatus::Ended,
+                    &ContestStatus::Finalizing,
+                    None,
+                    None,
+                )
+                .await?;
+            // finalization is already taken up by another run
+            let Some(contest) = contest else {
+                return Ok(());
+            };
+            contest
+        }
+        ContestStatus::Finalizing => contest,
+        _ => anyhow::bail!("Contest {} is not ended", contest_id),
+    };
+    let ranked = contest_helpers
+        .get_leaderboard(state.db(), &contest_id)
+        .await?;
+    let payouts = contest.prize_structure().payouts(&ranked);
+    let contest = Arc::new(contest);
+    let mut failed = 0;
+    // a contest is counted as won once for the user, with the best ranked entry
+    let mut winners = HashSet::new();
+    for payout in payouts {
+        let (user_id, entry_no) = (payout.user_id, payout.entry_no);
+        let count_win = winners.insert(user_id);
+        let cloned_state = state.clone();
+        let cloned_contest = contest.clone();
+        let result = state
+            .db()
+            .execute_transaction(None, None, move |session| {
+                let cloned_state = cloned_state.clone();
+                let cloned_contest = cloned_contest.clone();
+                let payout = payout.clone();
+                async move {
+                    credit_prize(&cloned_state, session, &cloned_contest, &payout, count_win).await
+                }
+                .boxed()
+            })
+            .await;
+        let Err(e) = result else {
+            continue;
+        };
+        let err = AppError::from_db_error(e);
+        if let Some(reason) = err.rejection() {
+            // the contest is not held back by a prize which can't be credited
+            tracing::error!(
+                "Prize of contest {} to user {} is put on hold: {}",
+                contest_id,
+                user_id,
+                reason
+            );
+            let held = contest_helpers
+                .hold_play_credit(state.db(), &contest_id, user_id, &[entry_no], reason)
+                .await;
+            if held.is_ok() {
+                continue;
+            }
+        }
+        tracing::error!(
+            "Not able to credit prize of contest {} to user {}: {:?}",
+            contest_id,
+            user_id,
+            err
+        );
+        failed += 1;
+    }
+    if failed > 0 {
+        anyhow::bail!("Prize credit failed for {} winners", failed);
+    }
+    if contest.prize_pool_type != PrizePoolType::Fixed {
+        let collected = contest_helpers
+            .get_collected_entry_fee(state.db(), &contest_id)
+            .await?;
+        if contest.house_settlement(collected) != (0, 0) {
+            let cloned_state = state.clone();
+            state
+                .db()
+                .execute_transaction(None, None, move |session| {
+                    let cloned_state = cloned_state.clone();
+                    let contest = contest.clone();
+                    async move { settle_house(&cloned_state, session, &contest, collected).await }
+                        .boxed()
+                })
+                .await?;
+        }
+    }
+    let finalized = contest_helpers
+        .transition_status(
+            state.db(),
+            &contest_id,
+            &ContestStatus::Finalizing,
+            &ContestStatus::Finalized,
+            None,
+            None,
+        )
+        .await?;
+    state.leaderboard_cache().remove(&contest_id);
+    if finalized.is_some() {
+        let update = ContestUpdate::status(ContestStatus::Finalized);
+        state.publish_contest_event(contest_id, update);
+    }
+    Ok(())
+}
+
+/// Credit the prize as withdrawable real balance, record the ContestWin transaction,
+/// update the winning stats of the user and queue the prize notification.
+/// The contest is counted in the wins of the user only if `count_win` is set.
+/// Prizes of the practice contests are credited as bonus balance and not counted in the stats.
+async fn credit_prize(
+    state: &AppState,
+    session: &mut DbSession,
+    contest: &Contest,
+    payout: &Payout,
+    count_win: bool,
+) -> MongoResult<()> {
+    let contest_id = contest.id.unwrap_or_default();
+    let user_id = payout.user_id;
+    let (rank, prize) = (payout.rank, payout.amount);
+    let credited = state
+        .helpers()
+        .contest_helpers()
+        .mark_prize_credited_session(session, &contest_id, user_id, payout.entry_no, rank, prize)
+        .await?;
+    if !credited {
+        return Ok(());
+    }
+    let amount = if contest.is_practice() {
+        Money::new(0, prize)
+    } else {
+        Money::new(prize, 0)
+    };
+    let (balance_before, balance_after) = state
+        .helpers()
+        .wallet_helpers()
+        .update_wallet_with_session(
+            session,
+            user_id,
+            amount.real(),
+            amount.bonus(),
+            false,
+            !contest.is_practice(),
+        )
+        .await?;
+    let remarks = format!("Prize for rank {} in contest: {}", rank, contest_id);
+    let transaction = WalletTransaction::contest_win_trans(
+        user_id,
+        amount,
+        balance_before,
+        balance_after,
+        &remarks,
+    );
+    session
+        .insert_one_with_session(DB_NAME, COLL_WALLET_TRANSACTIONS, &transaction, None)
+        .await?;
+    if !contest.is_practice() {
+        state
+            .helpers()
+            .user_helpers()
+            .add_contest_win_session(session, user_id, prize, count_win)
+            .await?;
+    }
+    let data = HashMap::from([
+        ("contestTitle".to_string(), contest.title.clone()),
+        ("rank".to_string(), rank.to_string()),
+        ("prize".to_string(), prize.to_string()),
+    ]);
+    let notification = NotificationRequest::new(user_id, EVENT_CREDIT_PRIZE, data);
+    session
+        .insert_one_with_session(DB_NAME, COLL_NOTIFICATION_REQUESTS, &notification, None)
+        .await?;
+    Ok(())
+}
+
+/// Record the rake kept from the real entry fees `collected` and the part of the
+/// prize pool covered by the house against the house account, once for the contest
+async fn settle_house(
+    state: &AppState,
+    session: &mut DbSession,
+    contest: &Contest,
+    collected: u64,
+) -> MongoResult<()> {
+    let contest_id = contest.id.unwrap_or_default();
+    let contest_helpers = state.helpers().contest_helpers();
+    let settled = contest_helpers
+        .mark_house_settled_session(session, &contest_id)
+        .await?;
+    if !settled {
+        return Ok(());
+    }
+    let (rake, shortfall) = contest.house_settlement(collected);
+    let amounts = [
+        (HouseTransactionType::Rake, rake),
+        (HouseTransactionType::Shortfall, shortfall),
+    ];
+    for (transaction_type, amount) in amounts {
+        if amount == 0 {
+            continue;
+        }
+        let transaction = HouseTransaction::new(contest_id, transaction_type, amount);
+        contest_helpers
+            .insert_house_transaction_session(session, &transaction)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{always, eq, function};
+    use mongodb::bson::oid::ObjectId;
+
+    use crate::{config::database::InsertedId, import_double, utils::get_epoch_ts};
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    fn ended_contest() -> Contest {
+        let ts = get_epoch_ts();
+        Contest {
+            id: Some(ObjectId::new()),
+            prizes: vec![RankPrize {
+                rank_from: 1,
+                rank_to: 2,
+                amount: 100,
+                ..Default::default()
+            }],
+            start_ts: ts - 200,
+            end_ts: ts - 100,
+            status: ContestStatus::Ended,
+            ..Default::default()
+        }
+    }
+
+    fn ranked() -> Vec<LeaderboardEntry> {
+        (1..=5)
+            .map(|rank| LeaderboardEntry {
+                rank,
+                user_id: rank * 10,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_finalize_contests() {
+        let contest = ended_contest();
+        let contest_id = contest.id.unwrap();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_ended_contests()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(FINALIZE_CONTEST_JOB_FETCH_LIMIT),
+            )
+            .returning(move |_, _| Ok(vec![contest.clone()]));
+        let mut seq = mockall::Sequence::new();
+        contest_helpers
+            .expect_transition_status()
+            .once()
+            .in_sequence(&mut seq)
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(ContestStatus::Ended),
+                eq(ContestStatus::Finalizing),
+                eq(None),
+                eq(None),
+            )
+            .returning(|_, _, _, to, _, _| {
+                let mut contest = ended_contest();
+                contest.status = to.clone();
+                Ok(Some(contest))
+            });
+        contest_helpers
+            .expect_get_leaderboard()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(|_, _| Ok(ranked()));
+        contest_helpers
+            .expect_transition_status()
+            .once()
+            .in_sequence(&mut seq)
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(ContestStatus::Finalizing),
+                eq(ContestStatus::Finalized),
+                eq(None),
+                eq(None),
+            )
+            .returning(|_, _, _, _, _, _| Ok(None));
+        // only the first 2 ranks win a prize
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .times(2)
+            .with(
+                function(Option::is_none),
+                function(Option::is_none),
+                always(),
+            )
+            .returning(|_, _, _| Ok(()));
+        let result = finalize_contests(Arc::new(state)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_finalize_contest_credit_failed() {
+        let mut contest = ended_contest();
+        contest.status = ContestStatus::Finalizing;
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_leaderboard()
+            .once()
+            .returning(|_, _| Ok(ranked()));
+        contest_helpers.expect_transition_status().never();
+        let mut seq = mockall::Sequence::new();
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Err(mongodb::error::Error::custom("error")));
+        let result = finalize_contest(Arc::new(state), contest).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_finalize_contest_credit_rejected() {
+        let mut contest = ended_contest();
+        contest.status = ContestStatus::Finalizing;
+        let contest_id = contest.id.unwrap();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_leaderboard()
+            .once()
+            .returning(|_, _| Ok(ranked()));
+        // credits of the second winner are frozen, the prize is put on hold
+        contest_helpers
+            .expect_hold_play_credit()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(20),
+                function(|entry_nos: &[u32]| entry_nos.len() == 1),
+                eq("Wallet credits are frozen"),
+            )
+            .returning(|_, _, _, _, _| Ok(()));
+        contest_helpers
+            .expect_transition_status()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(contest_id),
+                eq(ContestStatus::Finalizing),
+                eq(ContestStatus::Finalized),
+                eq(None),
+                eq(None),
+            )
+            .returning(|_, _, _, _, _, _| Ok(None));
+        let mut seq = mockall::Sequence::new();
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+        state
+            .get_mut_db()
+            .expect_execute_transaction()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| {
+                let err = AppError::Forbidden("Wallet credits are frozen".into());
+                Err(err.into())
+            });
+        let result = finalize_contest(Arc::new(state), contest).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_finalize_contest_wrong_status() {
+        let mut contest = ended_contest();
+        contest.status = ContestStatus::Live;
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers.expect_transition_status().never();
+        contest_helpers.expect_get_leaderboard().never();
+        let result = finalize_contest(Arc::new(state), contest).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_credit_prize_practice_contest() {
+        let mut contest = ended_contest();
+        contest.mode = ContestMode::Practice;
+        let contest_id = contest.id.unwrap();
+        let payout = Payout {
+            user_id: 10,
+            entry_no: 2,
+            rank: 1,
+            amount: 50,
+        };
+        let mut state = AppState::mock();
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_mark_prize_credited_session()
+            .once()
+            .with(always(), eq(contest_id), eq(10), eq(2), eq(1), eq(50))
+            .returning(|_, _, _, _, _, _| Ok(true));
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_update_wallet_with_session()
+            .once()
+            .with(always(), eq(10), eq(0), eq(50), eq(false), eq(false))
+            .returning(|_, _, _, _, _, _| Ok((Money::new(0, 0), Money::new(0, 50))));
+        state
+            .get_mut_helpers()
+            .mut_user_helpers()
+            .expect_add_contest_win_session()
+            .never();
+        let mut session = DbSession::default();
+        session
+            .expect_insert_one_with_session::<WalletTransaction>()
+            .once()
+            .withf(|_, coll, transaction, _| {
+                coll == COLL_WALLET_TRANSACTIONS && transaction.amount() == Money::new(0, 50)
+            })
+            .returning(|_, _, _, _| Ok(InsertedId(ObjectId::new().to_hex())));
+        session
+            .expect_insert_one_with_session::<NotificationRequest>()
+            .once()
+            .returning(|_, _, _, _| Ok(InsertedId(ObjectId::new().to_hex())));
+        let result = credit_prize(&state, &mut session, &contest, &payout, true).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_credit_prize_second_entry() {
+        let contest = ended_contest();
+        let payout = Payout {
+            user_id: 10,
+            entry_no: 2,
+            rank: 2,
+            amount: 50,
+        };
+        let mut state = AppState::mock();
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_mark_prize_credited_session()
+            .once()
+            .returning(|_, _, _, _, _, _| Ok(true));
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_update_wallet_with_session()
+            .once()
+            .with(always(), eq(10), eq(50), eq(0), eq(false), eq(true))
+            .returning(|_, _, _, _, _, _| Ok((Money::new(0, 0), Money::new(50, 0))));
+        // the contest is already counted as won with the better ranked entry
+        state
+            .get_mut_helpers()
+            .mut_user_helpers()
+            .expect_add_contest_win_session()
+            .once()
+            .with(always(), eq(10), eq(50), eq(false))
+            .returning(|_, _, _, _| Ok(()));
+        let mut session = DbSession::default();
+        session
+            .expect_insert_one_with_session::<WalletTransaction>()
+            .once()
+            .returning(|_, _, _, _| Ok(InsertedId(ObjectId::new().to_hex())));
+        session
+            .expect_insert_one_with_session::<NotificationRequest>()
+            .once()
+            .returning(|_, _, _, _| Ok(InsertedId(ObjectId::new().to_hex())));
+        let result = credit_prize(&state, &mut session, &contest, &payout, false).await;
+        assert!(result.is_ok());
+    }
+
+    fn guaranteed_contest() -> Contest {
+        Contest {
+            entry_fee: 10,
+            joined_players: 40,
+            prize_pool: 500,
+            commission_percent: 10,
+            prize_pool_type: PrizePoolType::Guaranteed,
+            status: ContestStatus::Finalizing,
+            ..ended_contest()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_settle_house() {
+        let contest = guaranteed_contest();
+        let contest_id = contest.id.unwrap();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_mark_house_settled_session()
+            .once()
+            .with(always(), eq(contest_id))
+            .returning(|_, _| Ok(true));
+        let mut seq = mockall::Sequence::new();
+        contest_helpers
+            .expect_insert_house_transaction_session()
+            .once()
+            .in_sequence(&mut seq)
+            .withf(move |_, transaction| {
+                transaction.contest_id == contest_id
+                    && transaction.transaction_type == HouseTransactionType::Rake
+                    && transaction.amount == 30
+            })
+            .returning(|_, _| Ok(()));
+        contest_helpers
+            .expect_insert_house_transaction_session()
+            .once()
+            .in_sequence(&mut seq)
+            .withf(|_, transaction| {
+                transaction.transaction_type == HouseTransactionType::Shortfall
+                    && transaction.amount == 230
+            })
+            .returning(|_, _| Ok(()));
+        // 100 of the entry fees is paid from bonus
+        let mut session = DbSession::default();
+        let result = settle_house(&state, &mut session, &contest, 300).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_settle_house_already_settled() {
+        let contest = guaranteed_contest();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_mark_house_settled_session()
+            .once()
+            .returning(|_, _| Ok(false));
+        contest_helpers
+            .expect_insert_house_transaction_session()
+            .never();
+        let mut session = DbSession::default();
+        let result = settle_house(&state, &mut session, &contest, 400).await;
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,112 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{config::AppState, constants::*, models::*};
+
+/// Periodically refresh the leaderboards of the live contests
+/// and push the top of each to the subscribed users
+pub async fn run_live_leaderboard_job(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(LIVE_LEADERBOARD_JOB_INTERVAL));
+    loop {
+        interval.tick().await;
+        tracing::debug!("Running live leaderboard job");
+        if let Err(e) = publish_live_leaderboards(state.clone()).await {
+            tracing::error!("Error in live leaderboard job: {:?}", e);
+        }
+    }
+}
+
+async fn publish_live_leaderboards(state: Arc<AppState>) -> anyhow::Result<()> {
+    // nobody to push the leaderboards to
+    if state.contest_events().receiver_count() == 0 {
+        return Ok(());
+    }
+    let db = state.db();
+    let contest_helpers = state.helpers().contest_helpers();
+    let contests = contest_helpers
+        .get_live_contests(db, LIVE_LEADERBOARD_JOB_FETCH_LIMIT)
+        .await?;
+    for contest in contests {
+        let Some(contest_id) = contest.id else {
+            continue;
+        };
+        let ranked = Arc::new(contest_helpers.get_leaderboard(db, &contest_id).await?);
+        let snapshot = LeaderboardSnapshot {
+            total: ranked.len() as u64,
+            entries: ranked.iter().take(LIVE_LEADERBOARD_SIZE).cloned().collect(),
+        };
+        state.leaderboard_cache().insert(contest_id, ranked.clone());
+        state.publish_contest_event(contest_id, ContestUpdate::Leaderboard(snapshot));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+    use mongodb::bson::oid::ObjectId;
+
+    use crate::import_double;
+
+    import_double!(DbClient);
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_live_leaderboards_no_subscriber() {
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers.expect_get_live_contests().never();
+        contest_helpers.expect_get_leaderboard().never();
+        let result = publish_live_leaderboards(Arc::new(state)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_publish_live_leaderboards() {
+        let contest_id = ObjectId::new();
+        let mut state = AppState::mock();
+        let contest_helpers = state.get_mut_helpers().mut_contest_helpers();
+        contest_helpers
+            .expect_get_live_contests()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(LIVE_LEADERBOARD_JOB_FETCH_LIMIT),
+            )
+            .returning(move |_, _| {
+                let contest = Contest {
+                    id: Some(contest_id),
+                    status: ContestStatus::Live,
+                    ..Default::default()
+                };
+                Ok(vec![contest])
+            });
+        contest_helpers
+            .expect_get_leaderboard()
+            .once()
+            .with(function(|_: &DbClient| true), eq(contest_id))
+            .returning(|_, _| {
+                let ranked = (1..=25)
+                    .map(|rank| LeaderboardEntry {
+                        rank,
+                        user_id: rank,
+                        ..Default::default()
+                    })
+                    .collect();
+                Ok(ranked)
+            });
+        let state = Arc::new(state);
+        let mut events = state.contest_events().subscribe();
+        let result = publish_live_leaderboards(state.clone()).await;
+        assert!(result.is_ok());
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.contest_id, contest_id);
+        let ContestUpdate::Leaderboard(snapshot) = event.update else {
+            panic!("Leaderboard update expected");
+        };
+        assert_eq!(snapshot.total, 25);
+        assert_eq!(snapshot.entries.len(), LIVE_LEADERBOARD_SIZE);
+        assert!(state.leaderboard_cache().get(&contest_id).is_some());
+    }
+}
@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use crate::config::AppState;
+
+mod bonus_expiry;
+pub(crate) mod cancel_contest;
+mod contest_status;
+mod contest_template;
+mod finalize_contest;
+mod live_leaderboard;
+
+/// Spawn all the background jobs
+pub fn start_background_jobs(state: Arc<AppState>) {
+    tokio::spawn(bonus_expiry::run_bonus_expiry_job(state.clone()));
+    tokio::spawn(contest_status::run_contest_status_job(state.clone()));
+    tokio::spawn(contest_template::run_contest_template_job(state.clone()));
+    tokio::spawn(cancel_contest::run_cancel_contest_job(state.clone()));
+    tokio::spawn(finalize_contest::run_finalize_contest_job(state.clone()));
+    tokio::spawn(live_leaderboard::run_live_leaderboard_job(state));
+}
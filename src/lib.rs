@@ -24,6 +24,8 @@ pub async fn start_web_server() {
     // create AppState instance
     let state = config::AppState::new().await;
     let state = Arc::new(state);
+    // start the background jobs
+    jobs::start_background_jobs(state.clone());
     // build app routes
     let app = config::build_app_routes(state);
     tracing::debug!("Starting the app in: {addr}");
@@ -0,0 +1,480 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::constants::*;
+
+use super::{
+    CreateContestReq, Money, PrizeStructure, Question, RankPrize, ScoringRule, TieRule,
+    UpdateContestReq,
+};
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum MediaType {
+    #[default]
+    Movie,
+    Clip,
+}
+
+/// Trailer of a movie or a clip the contest is about.
+/// `name` refers to the unique name in `movies` or `clips` collection.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestMedia {
+    pub media_type: MediaType,
+    #[validate(length(min = 1))]
+    pub name: String,
+}
+
+/// Lifecycle state of the contest, see `can_transition_to` for the allowed changes
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ContestStatus {
+    /// being prepared by admin, not visible to the users
+    #[default]
+    Draft,
+    /// open for joining till the start time, `created` is the status before drafts
+    #[serde(alias = "created")]
+    Published,
+    Live,
+    Ended,
+    /// prizes are being credited
+    Finalizing,
+    Finalized,
+    Cancelled,
+}
+
+impl ContestStatus {
+    pub fn can_transition_to(&self, next: &ContestStatus) -> bool {
+        use ContestStatus::*;
+        matches!(
+            (self, next),
+            (Draft, Published)
+                | (Draft, Cancelled)
+                | (Published, Live)
+                | (Published, Cancelled)
+                | (Live, Ended)
+                | (Ended, Finalizing)
+                | (Finalizing, Finalized)
+        )
+    }
+
+    /// Contest details other than the prize table can be edited
+    pub fn is_editable(&self) -> bool {
+        matches!(self, ContestStatus::Draft | ContestStatus::Published)
+    }
+}
+
+/// Private contests are created by the users and joined with the invite code
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ContestVisibility {
+    #[default]
+    Public,
+    Private,
+}
+
+/// Practice contests are free to join, prizes if any are credited as bonus balance
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ContestMode {
+    #[default]
+    Paid,
+    Practice,
+}
+
+/// How the prize pool of the contest is funded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PrizePoolType {
+    /// `prizePool` irrespective of the entries
+    #[default]
+    Fixed,
+    /// entry fees collected after the house commission
+    Dynamic,
+    /// entry fees collected after the house commission,
+    /// the house covers the shortfall below `prizePool`
+    Guaranteed,
+}
+
+impl PrizePoolType {
+    /// Prize pool for the entry fees collected
+    pub fn pool(&self, prize_pool: u64, collected: u64, commission_percent: u32) -> u64 {
+        let net = collected * (100 - commission_percent.min(100)) as u64 / 100;
+        match self {
+            PrizePoolType::Fixed => prize_pool,
+            PrizePoolType::Dynamic => net,
+            PrizePoolType::Guaranteed => net.max(prize_pool),
+        }
+    }
+}
+
+/// House commission configured with the env variable, `default` if it's not set
+pub fn commission_percent_from_env(key: &str, default: u32) -> u32 {
+    let percent = std::env::var(key).unwrap_or_default();
+    percent.parse::<u32>().unwrap_or(default).min(100)
+}
+
+/// Change of the contest status, `actor` is the admin id and missing for the jobs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestStatusChange {
+    pub from: ContestStatus,
+    pub to: ContestStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<u32>,
+    pub ts: u64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Contest {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub id: Option<ObjectId>,
+    pub title: String,
+    pub category: String,
+    pub banner: String,
+    pub entry_fee: u64,
+
+    /// maximum percentage of the entry fee that can be paid from bonus balance
+    pub bonus_usage_percent: u32,
+
+    /// fixed or the guaranteed minimum pool as per `prize_pool_type`
+    pub prize_pool: u64,
+
+    #[serde(default)]
+    pub prize_pool_type: PrizePoolType,
+    pub prizes: Vec<RankPrize>,
+
+    #[serde(default)]
+    pub tie_rule: TieRule,
+    pub min_players: u32,
+    pub max_players: u32,
+
+    /// entries allowed per user, a single entry if not set
+    #[serde(default)]
+    pub max_entries: u32,
+
+    /// users can wait for a spot once the contest is full
+    #[serde(default)]
+    pub waitlist: bool,
+
+    /// each entry of an user is counted as a player
+    #[serde(default)]
+    pub joined_players: u32,
+    pub start_ts: u64,
+    pub end_ts: u64,
+    pub media: ContestMedia,
+    pub questions: Vec<Question>,
+
+    #[serde(default)]
+    pub scoring: ScoringRule,
+    pub status: ContestStatus,
+
+    #[serde(default)]
+    pub status_history: Vec<ContestStatusChange>,
+
+    #[serde(default)]
+    pub visibility: ContestVisibility,
+
+    #[serde(default)]
+    pub mode: ContestMode,
+
+    /// shareable code to join the private contest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invite_code: Option<String>,
+
+    /// percentage of the entry fees kept by the house as the rake in the dynamic
+    /// and guaranteed prize pools
+    #[serde(default)]
+    pub commission_percent: u32,
+
+    /// template the contest is created from by the scheduler
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub template_id: Option<ObjectId>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finalized_ts: Option<u64>,
+
+    /// set when the rake and the shortfall of the prize pool are recorded against the house
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub house_settled_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancelled_ts: Option<u64>,
+
+    /// set when the entry fees of all the players are refunded after cancellation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refunded_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<u32>,
+}
+
+impl Contest {
+    pub fn prize_structure(&self) -> PrizeStructure<'_> {
+        self.prize_structure_for(self.joined_players)
+    }
+
+    /// Prize table if the given number of players join
+    pub fn prize_structure_for(&self, players: u32) -> PrizeStructure<'_> {
+        PrizeStructure {
+            pool: self.prize_pool_for(players),
+            prizes: &self.prizes,
+            tie_rule: &self.tie_rule,
+        }
+    }
+
+    /// Prize pool if the given number of players join
+    pub fn prize_pool_for(&self, players: u32) -> u64 {
+        let collected = self.entry_fee * players as u64;
+        self.prize_pool_type
+            .pool(self.prize_pool, collected, self.commission_percent)
+    }
+
+    /// Rake kept by the house from the entry fees `collected` in real balance and the part
+    /// of the prize pool of the joined players covered by the house, i.e. the shortfall of
+    /// the guaranteed pool and the entry fees paid from bonus.
+    /// Fixed pools are not settled with the house.
+    pub fn house_settlement(&self, collected: u64) -> (u64, u64) {
+        if self.prize_pool_type == PrizePoolType::Fixed {
+            return (0, 0);
+        }
+        let net = PrizePoolType::Dynamic.pool(0, collected, self.commission_percent);
+        let pool = self.prize_pool_for(self.joined_players);
+        (collected - net, pool.saturating_sub(net))
+    }
+
+    pub fn is_practice(&self) -> bool {
+        self.mode == ContestMode::Practice
+    }
+
+    pub fn has_started(&self, ts: u64) -> bool {
+        self.start_ts <= ts
+    }
+
+    pub fn is_live(&self, ts: u64) -> bool {
+        self.status == ContestStatus::Live && self.start_ts <= ts && ts < self.end_ts
+    }
+
+    /// Open for joining till the start time after it's published
+    pub fn is_joinable(&self, ts: u64) -> bool {
+        self.status == ContestStatus::Published && !self.has_started(ts)
+    }
+
+    pub fn is_under_subscribed(&self) -> bool {
+        self.joined_players < self.min_players
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.joined_players >= self.max_players
+    }
+
+    pub fn entries_per_user(&self) -> u32 {
+        self.max_entries.max(1)
+    }
+
+    /// Split of the entry fee into real and bonus amount for the given balance.
+    /// Bonus is used as much as allowed by `bonus_usage_percent`, rest is paid from real.
+    /// Returns None if the balance is not sufficient.
+    pub fn entry_fee_split(&self, balance: Money) -> Option<Money> {
+        let max_bonus = self.entry_fee * self.bonus_usage_percent as u64 / 100;
+        let bonus = max_bonus.min(balance.bonus());
+        let real = self.entry_fee - bonus;
+        if real > balance.real() {
+            return None;
+        }
+        Some(Money::new(real, bonus))
+    }
+
+    /// Apply the provided fields of the update request
+    pub fn apply_update(&mut self, req: UpdateContestReq) {
+        if let Some(title) = req.title {
+            self.title = title;
+        }
+        if let Some(category) = req.category {
+            self.category = category;
+        }
+        if let Some(banner) = req.banner {
+            self.banner = banner;
+        }
+        if let Some(entry_fee) = req.entry_fee {
+            self.entry_fee = entry_fee;
+        }
+        if let Some(bonus_usage_percent) = req.bonus_usage_percent {
+            self.bonus_usage_percent = bonus_usage_percent;
+        }
+        if let Some(prize_pool) = req.prize_pool {
+            self.prize_pool = prize_pool;
+        }
+        if let Some(prize_pool_type) = req.prize_pool_type {
+            self.prize_pool_type = prize_pool_type;
+        }
+        if let Some(commission_percent) = req.commission_percent {
+            self.commission_percent = commission_percent;
+        }
+        if let Some(prizes) = req.prizes {
+            self.prizes = prizes;
+        }
+        if let Some(tie_rule) = req.tie_rule {
+            self.tie_rule = tie_rule;
+        }
+        if let Some(min_players) = req.min_players {
+            self.min_players = min_players;
+        }
+        if let Some(max_players) = req.max_players {
+            self.max_players = max_players;
+        }
+        if let Some(max_entries) = req.max_entries {
+            self.max_entries = max_entries;
+        }
+        if let Some(waitlist) = req.waitlist {
+            self.waitlist = waitlist;
+        }
+        if let Some(start_ts) = req.start_ts {
+            self.start_ts = start_ts;
+        }
+        if let Some(end_ts) = req.end_ts {
+            self.end_ts = end_ts;
+        }
+        if let Some(media) = req.media {
+            self.media = media;
+        }
+        if let Some(questions) = req.questions {
+            self.questions = questions;
+        }
+        if let Some(scoring) = req.scoring {
+            self.scoring = scoring;
+        }
+    }
+}
+
+impl From<CreateContestReq> for Contest {
+    fn from(req: CreateContestReq) -> Self {
+        Self {
+            title: req.title,
+            category: req.category,
+            banner: req.banner,
+            entry_fee: req.entry_fee,
+            bonus_usage_percent: req.bonus_usage_percent,
+            prize_pool: req.prize_pool,
+            prize_pool_type: req.prize_pool_type.unwrap_or_default(),
+            // configurable with `CONTEST_COMMISSION_PERCENT` env variable if not provided
+            commission_percent: req.commission_percent.unwrap_or_else(|| {
+                commission_percent_from_env(
+                    "CONTEST_COMMISSION_PERCENT",
+                    CONTEST_DEFAULT_COMMISSION_PERCENT,
+                )
+            }),
+            prizes: req.prizes,
+            tie_rule: req.tie_rule.unwrap_or_default(),
+            min_players: req.min_players,
+            max_players: req.max_players,
+            max_entries: req.max_entries.unwrap_or(1),
+            waitlist: req.waitlist.unwrap_or_default(),
+            start_ts: req.start_ts,
+            end_ts: req.end_ts,
+            media: req.media,
+            questions: req.questions,
+            scoring: req.scoring.unwrap_or_default(),
+            mode: req.mode.unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_contest_entry_fee_split() {
+        let contest = Contest {
+            entry_fee: 50,
+            bonus_usage_percent: 20,
+            ..Default::default()
+        };
+        let split = contest.entry_fee_split(Money::new(100, 100));
+        assert_eq!(split, Some(Money::new(40, 10)));
+        let split = contest.entry_fee_split(Money::new(100, 4));
+        assert_eq!(split, Some(Money::new(46, 4)));
+        let split = contest.entry_fee_split(Money::new(45, 4));
+        assert_eq!(split, None);
+        let contest = Contest::default();
+        assert_eq!(
+            contest.entry_fee_split(Money::default()),
+            Some(Money::default())
+        );
+    }
+
+    #[test]
+    fn test_contest_apply_update() {
+        let mut contest = Contest {
+            title: "Old title".into(),
+            entry_fee: 10,
+            max_players: 100,
+            ..Default::default()
+        };
+        let req = UpdateContestReq {
+            title: Some("New title".into()),
+            max_players: Some(50),
+            ..Default::default()
+        };
+        contest.apply_update(req);
+        assert_eq!(contest.title, "New title");
+        assert_eq!(contest.entry_fee, 10);
+        assert_eq!(contest.max_players, 50);
+    }
+
+    #[test]
+    fn test_contest_prize_pool_for() {
+        let mut contest = Contest {
+            entry_fee: 20,
+            prize_pool: 500,
+            joined_players: 4,
+            commission_percent: 10,
+            ..Default::default()
+        };
+        assert_eq!(contest.prize_pool_for(10), 500);
+        assert_eq!(contest.house_settlement(80), (0, 0));
+        contest.prize_pool_type = PrizePoolType::Dynamic;
+        assert_eq!(contest.prize_pool_for(10), 180);
+        assert_eq!(contest.prize_structure().pool, 72);
+        assert_eq!(contest.house_settlement(80), (8, 0));
+        // 20 of the entry fees is paid from bonus
+        assert_eq!(contest.house_settlement(60), (6, 18));
+        contest.prize_pool_type = PrizePoolType::Guaranteed;
+        assert_eq!(contest.prize_pool_for(40), 720);
+        assert_eq!(contest.prize_structure().pool, 500);
+        assert_eq!(contest.house_settlement(80), (8, 428));
+        assert_eq!(contest.house_settlement(60), (6, 446));
+    }
+
+    #[test]
+    fn test_contest_status_transition() {
+        use ContestStatus::*;
+        assert!(Draft.can_transition_to(&Published));
+        assert!(Published.can_transition_to(&Live));
+        assert!(Ended.can_transition_to(&Finalizing));
+        assert!(!Draft.can_transition_to(&Live));
+        assert!(!Live.can_transition_to(&Cancelled));
+        assert!(!Finalized.can_transition_to(&Cancelled));
+        let status: ContestStatus = serde_json::from_str("\"created\"").unwrap();
+        assert_eq!(status, Published);
+    }
+}
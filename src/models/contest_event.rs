@@ -0,0 +1,72 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::get_epoch_ts;
+
+use super::{ContestStatus, LeaderboardEntry};
+
+/// Number of players joined the contest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantCount {
+    pub joined_players: u32,
+}
+
+/// Top of the leaderboard of the live contest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardSnapshot {
+    pub total: u64,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusUpdate {
+    pub status: ContestStatus,
+    pub ts: u64,
+}
+
+/// Update pushed to the users following the contest.
+/// Each update carries the complete state, so a missed update is replaced by the next one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContestUpdate {
+    ParticipantCount(ParticipantCount),
+    Leaderboard(LeaderboardSnapshot),
+    Status(StatusUpdate),
+}
+
+impl ContestUpdate {
+    pub fn status(status: ContestStatus) -> Self {
+        let ts = get_epoch_ts();
+        ContestUpdate::Status(StatusUpdate { status, ts })
+    }
+
+    /// Name of the event sent to the client
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContestUpdate::ParticipantCount(_) => "participantCount",
+            ContestUpdate::Leaderboard(_) => "leaderboard",
+            ContestUpdate::Status(_) => "status",
+        }
+    }
+
+    /// No more updates are sent after the contest is finalized or cancelled
+    pub fn is_last(&self) -> bool {
+        matches!(
+            self,
+            ContestUpdate::Status(StatusUpdate {
+                status: ContestStatus::Finalized | ContestStatus::Cancelled,
+                ..
+            })
+        )
+    }
+}
+
+/// Update of a contest broadcast to all the subscribers
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContestEvent {
+    pub contest_id: ObjectId,
+    pub update: ContestUpdate,
+}
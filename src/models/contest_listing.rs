@@ -0,0 +1,307 @@
+use std::{fmt, str::FromStr};
+
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{Contest, ContestMedia, ContestMode, ContestStatus, PrizePoolType};
+
+/// Phase of the contest as shown to the users
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ContestPhase {
+    /// published and yet to start
+    Upcoming,
+    Live,
+    /// ended, being finalized or finalized
+    Completed,
+}
+
+impl ContestPhase {
+    /// Statuses of the contests in the phase
+    pub fn statuses(&self) -> Vec<ContestStatus> {
+        match self {
+            ContestPhase::Upcoming => vec![ContestStatus::Published],
+            ContestPhase::Live => vec![ContestStatus::Live],
+            ContestPhase::Completed => vec![
+                ContestStatus::Ended,
+                ContestStatus::Finalizing,
+                ContestStatus::Finalized,
+            ],
+        }
+    }
+
+    /// Statuses of all the contests visible to the users, drafts and cancelled
+    /// contests are not listed
+    pub fn all_statuses() -> Vec<ContestStatus> {
+        [
+            ContestPhase::Upcoming,
+            ContestPhase::Live,
+            ContestPhase::Completed,
+        ]
+        .iter()
+        .flat_map(ContestPhase::statuses)
+        .collect()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ContestSort {
+    #[default]
+    StartTs,
+    EntryFee,
+    PrizePool,
+}
+
+impl ContestSort {
+    pub fn field(&self) -> &'static str {
+        match self {
+            ContestSort::StartTs => "startTs",
+            ContestSort::EntryFee => "entryFee",
+            ContestSort::PrizePool => "prizePool",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn value(&self) -> i32 {
+        match self {
+            SortOrder::Asc => 1,
+            SortOrder::Desc => -1,
+        }
+    }
+}
+
+/// Position after the last contest of a page, the value of the sort field
+/// and the contest id which breaks the ties. Sent to the client as `<value>_<id>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContestCursor {
+    pub value: u64,
+    pub id: ObjectId,
+}
+
+impl ContestCursor {
+    /// Filter for the contests after the cursor in the sort order
+    pub fn filter(&self, sort: ContestSort, order: SortOrder) -> Document {
+        let op = match order {
+            SortOrder::Asc => "$gt",
+            SortOrder::Desc => "$lt",
+        };
+        let value = Bson::Int64(self.value as i64);
+        doc! {
+            "$or": [
+                {sort.field(): {op: value.clone()}},
+                {sort.field(): value, "_id": {op: self.id}},
+            ]
+        }
+    }
+}
+
+impl fmt::Display for ContestCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.value, self.id.to_hex())
+    }
+}
+
+impl FromStr for ContestCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || "Invalid cursor".to_string();
+        let (value, id) = s.split_once('_').ok_or_else(err)?;
+        let value = value.parse().map_err(|_| err())?;
+        let id = ObjectId::parse_str(id).map_err(|_| err())?;
+        Ok(Self { value, id })
+    }
+}
+
+/// Contest as listed to the users, without the questions and the answers
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestSummary {
+    #[serde(rename = "_id")]
+    #[schema(value_type = String)]
+    pub id: ObjectId,
+    pub title: String,
+    pub category: String,
+    pub banner: String,
+    pub entry_fee: u64,
+    pub prize_pool: u64,
+
+    #[serde(default)]
+    pub prize_pool_type: PrizePoolType,
+
+    #[serde(default)]
+    pub commission_percent: u32,
+    pub min_players: u32,
+    pub max_players: u32,
+
+    #[serde(default)]
+    pub joined_players: u32,
+    pub start_ts: u64,
+    pub end_ts: u64,
+    pub media: ContestMedia,
+    pub status: ContestStatus,
+
+    #[serde(default)]
+    pub mode: ContestMode,
+
+    /// percentage of `maxPlayers` joined
+    #[serde(default)]
+    pub fill_percent: u32,
+
+    /// seconds left for the start, 0 once started
+    #[serde(default)]
+    pub starts_in_secs: u64,
+
+    /// prize pool for the players joined till now
+    #[serde(default)]
+    pub current_prize_pool: u64,
+}
+
+impl From<Contest> for ContestSummary {
+    fn from(contest: Contest) -> Self {
+        Self {
+            id: contest.id.unwrap_or_default(),
+            title: contest.title,
+            category: contest.category,
+            banner: contest.banner,
+            entry_fee: contest.entry_fee,
+            prize_pool: contest.prize_pool,
+            prize_pool_type: contest.prize_pool_type,
+            commission_percent: contest.commission_percent,
+            min_players: contest.min_players,
+            max_players: contest.max_players,
+            joined_players: contest.joined_players,
+            start_ts: contest.start_ts,
+            end_ts: contest.end_ts,
+            media: contest.media,
+            status: contest.status,
+            mode: contest.mode,
+            fill_percent: 0,
+            starts_in_secs: 0,
+            current_prize_pool: 0,
+        }
+    }
+}
+
+impl ContestSummary {
+    /// Fields fetched from the contests collection
+    pub fn projection() -> Document {
+        let fields = [
+            "title",
+            "category",
+            "banner",
+            "entryFee",
+            "prizePool",
+            "prizePoolType",
+            "commissionPercent",
+            "minPlayers",
+            "maxPlayers",
+            "joinedPlayers",
+            "startTs",
+            "endTs",
+            "media",
+            "status",
+            "mode",
+        ];
+        fields
+            .into_iter()
+            .map(|field| (field.into(), 1.into()))
+            .collect()
+    }
+
+    /// Set the fields computed at the timestamp
+    pub fn with_computed(mut self, ts: u64) -> Self {
+        self.fill_percent = match self.max_players {
+            0 => 0,
+            max_players => (self.joined_players * 100 / max_players).min(100),
+        };
+        self.starts_in_secs = self.start_ts.saturating_sub(ts);
+        let collected = self.entry_fee * self.joined_players as u64;
+        self.current_prize_pool =
+            self.prize_pool_type
+                .pool(self.prize_pool, collected, self.commission_percent);
+        self
+    }
+
+    pub fn cursor(&self, sort: ContestSort) -> ContestCursor {
+        let value = match sort {
+            ContestSort::StartTs => self.start_ts,
+            ContestSort::EntryFee => self.entry_fee,
+            ContestSort::PrizePool => self.prize_pool,
+        };
+        ContestCursor { value, id: self.id }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_contest_cursor_parse() {
+        let id = ObjectId::new();
+        let cursor = ContestCursor { value: 150, id };
+        let parsed: ContestCursor = cursor.to_string().parse().unwrap();
+        assert_eq!(parsed, cursor);
+        assert!("150".parse::<ContestCursor>().is_err());
+        assert!("abc_def".parse::<ContestCursor>().is_err());
+        assert!(format!("-1_{id}").parse::<ContestCursor>().is_err());
+    }
+
+    #[test]
+    fn test_contest_cursor_filter() {
+        let id = ObjectId::new();
+        let cursor = ContestCursor { value: 20, id };
+        let filter = cursor.filter(ContestSort::EntryFee, SortOrder::Desc);
+        let expected = doc! {
+            "$or": [
+                {"entryFee": {"$lt": 20_i64}},
+                {"entryFee": 20_i64, "_id": {"$lt": id}},
+            ]
+        };
+        assert_eq!(filter, expected);
+    }
+
+    #[test]
+    fn test_contest_summary_with_computed() {
+        let summary = ContestSummary {
+            id: ObjectId::new(),
+            title: "Contest".into(),
+            category: "Movies".into(),
+            banner: "".into(),
+            entry_fee: 10,
+            prize_pool: 100,
+            prize_pool_type: PrizePoolType::Guaranteed,
+            commission_percent: 10,
+            min_players: 2,
+            max_players: 8,
+            joined_players: 3,
+            start_ts: 1000,
+            end_ts: 2000,
+            media: ContestMedia::default(),
+            status: ContestStatus::Published,
+            mode: ContestMode::Paid,
+            fill_percent: 0,
+            starts_in_secs: 0,
+            current_prize_pool: 0,
+        };
+        let computed = summary.clone().with_computed(400);
+        assert_eq!(computed.fill_percent, 37);
+        assert_eq!(computed.starts_in_secs, 600);
+        assert_eq!(computed.current_prize_pool, 100);
+        let computed = summary.with_computed(1500);
+        assert_eq!(computed.starts_in_secs, 0);
+    }
+}
@@ -0,0 +1,418 @@
+use chrono::{Datelike, FixedOffset, TimeZone, Weekday};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::{
+    constants::*,
+    utils::{format_ts_ist, get_epoch_ts},
+};
+
+use super::{
+    commission_percent_from_env, Contest, ContestMedia, ContestMode, ContestStatus,
+    ContestStatusChange, CreateContestTemplateReq, PrizePoolType, Question, RankPrize, ScoringRule,
+    TieRule, UpdateContestTemplateReq,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DayOfWeek {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl From<Weekday> for DayOfWeek {
+    fn from(weekday: Weekday) -> Self {
+        match weekday {
+            Weekday::Mon => DayOfWeek::Monday,
+            Weekday::Tue => DayOfWeek::Tuesday,
+            Weekday::Wed => DayOfWeek::Wednesday,
+            Weekday::Thu => DayOfWeek::Thursday,
+            Weekday::Fri => DayOfWeek::Friday,
+            Weekday::Sat => DayOfWeek::Saturday,
+            Weekday::Sun => DayOfWeek::Sunday,
+        }
+    }
+}
+
+/// Contest starts at `hour`:`minute` IST on each of the `days`, every day if `days` is empty
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Recurrence {
+    #[serde(default)]
+    pub days: Vec<DayOfWeek>,
+    #[validate(range(max = 23))]
+    pub hour: u32,
+    #[validate(range(max = 59))]
+    pub minute: u32,
+}
+
+impl Recurrence {
+    /// Start times after `from_ts` till `till_ts` (inclusive), earliest first
+    pub fn occurrences(&self, from_ts: u64, till_ts: u64) -> Vec<u64> {
+        let ist = FixedOffset::east_opt(IST_OFFSET_SECS).unwrap();
+        let from = ist.timestamp_opt(from_ts as i64, 0).single();
+        let till = ist.timestamp_opt(till_ts as i64, 0).single();
+        let (Some(from), Some(till)) = (from, till) else {
+            return vec![];
+        };
+        let last_date = till.date_naive();
+        let mut occurrences = vec![];
+        for date in from.date_naive().iter_days() {
+            if date > last_date {
+                break;
+            }
+            let day = DayOfWeek::from(date.weekday());
+            if !self.days.is_empty() && !self.days.contains(&day) {
+                continue;
+            }
+            let start = date
+                .and_hms_opt(self.hour, self.minute, 0)
+                .and_then(|dt| ist.from_local_datetime(&dt).single());
+            let Some(start) = start else {
+                continue;
+            };
+            let start_ts = start.timestamp() as u64;
+            if from_ts < start_ts && start_ts <= till_ts {
+                occurrences.push(start_ts);
+            }
+        }
+        occurrences
+    }
+
+    /// First start time after the timestamp, there is one in every week
+    pub fn next_after(&self, ts: u64) -> Option<u64> {
+        self.occurrences(ts, ts + 7 * 24 * 60 * 60).first().copied()
+    }
+}
+
+/// Details of the contests created at each recurrence, contests already created
+/// are not affected by any change in the template
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestTemplate {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub id: Option<ObjectId>,
+
+    /// title of the contests is the template title followed by the start time
+    pub title: String,
+    pub category: String,
+    pub banner: String,
+    pub entry_fee: u64,
+    pub bonus_usage_percent: u32,
+    pub prize_pool: u64,
+
+    #[serde(default)]
+    pub prize_pool_type: PrizePoolType,
+
+    /// rake on the entry fees for the dynamic and guaranteed pools
+    #[serde(default)]
+    pub commission_percent: u32,
+    pub prizes: Vec<RankPrize>,
+
+    #[serde(default)]
+    pub tie_rule: TieRule,
+    pub min_players: u32,
+    pub max_players: u32,
+
+    /// entries allowed per user, a single entry if not set
+    #[serde(default)]
+    pub max_entries: u32,
+
+    #[serde(default)]
+    pub waitlist: bool,
+    pub duration_secs: u64,
+    pub media: ContestMedia,
+    pub questions: Vec<Question>,
+
+    #[serde(default)]
+    pub scoring: ScoringRule,
+
+    #[serde(default)]
+    pub mode: ContestMode,
+    pub recurrence: Recurrence,
+
+    #[serde(default)]
+    pub paused: bool,
+
+    /// start time of the latest contest created from the template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_till: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<u32>,
+}
+
+impl ContestTemplate {
+    /// Start times of the contests to be created till the timestamp,
+    /// recurrences missed while paused are skipped
+    pub fn due_occurrences(&self, ts: u64, till_ts: u64) -> Vec<u64> {
+        let from_ts = self.scheduled_till.unwrap_or_default().max(ts);
+        self.recurrence.occurrences(from_ts, till_ts)
+    }
+
+    /// Published contest starting at the timestamp
+    pub fn contest_at(&self, start_ts: u64) -> Contest {
+        let ts = get_epoch_ts();
+        let status_change = ContestStatusChange {
+            from: ContestStatus::Draft,
+            to: ContestStatus::Published,
+            actor: None,
+            ts,
+        };
+        Contest {
+            title: format!("{} {}", self.title, format_ts_ist(start_ts)),
+            category: self.category.clone(),
+            banner: self.banner.clone(),
+            entry_fee: self.entry_fee,
+            bonus_usage_percent: self.bonus_usage_percent,
+            prize_pool: self.prize_pool,
+            prize_pool_type: self.prize_pool_type,
+            commission_percent: self.commission_percent,
+            prizes: self.prizes.clone(),
+            tie_rule: self.tie_rule.clone(),
+            min_players: self.min_players,
+            max_players: self.max_players,
+            max_entries: self.max_entries,
+            waitlist: self.waitlist,
+            start_ts,
+            end_ts: start_ts + self.duration_secs,
+            media: self.media.clone(),
+            questions: self.questions.clone(),
+            scoring: self.scoring.clone(),
+            mode: self.mode.clone(),
+            status: ContestStatus::Published,
+            status_history: vec![status_change],
+            template_id: self.id,
+            created_ts: Some(ts),
+            ..Default::default()
+        }
+    }
+
+    /// Apply the provided fields of the update request
+    pub fn apply_update(&mut self, req: UpdateContestTemplateReq) {
+        if let Some(title) = req.title {
+            self.title = title;
+        }
+        if let Some(category) = req.category {
+            self.category = category;
+        }
+        if let Some(banner) = req.banner {
+            self.banner = banner;
+        }
+        if let Some(entry_fee) = req.entry_fee {
+            self.entry_fee = entry_fee;
+        }
+        if let Some(bonus_usage_percent) = req.bonus_usage_percent {
+            self.bonus_usage_percent = bonus_usage_percent;
+        }
+        if let Some(prize_pool) = req.prize_pool {
+            self.prize_pool = prize_pool;
+        }
+        if let Some(prize_pool_type) = req.prize_pool_type {
+            self.prize_pool_type = prize_pool_type;
+        }
+        if let Some(commission_percent) = req.commission_percent {
+            self.commission_percent = commission_percent;
+        }
+        if let Some(prizes) = req.prizes {
+            self.prizes = prizes;
+        }
+        if let Some(tie_rule) = req.tie_rule {
+            self.tie_rule = tie_rule;
+        }
+        if let Some(min_players) = req.min_players {
+            self.min_players = min_players;
+        }
+        if let Some(max_players) = req.max_players {
+            self.max_players = max_players;
+        }
+        if let Some(max_entries) = req.max_entries {
+            self.max_entries = max_entries;
+        }
+        if let Some(waitlist) = req.waitlist {
+            self.waitlist = waitlist;
+        }
+        if let Some(duration_secs) = req.duration_secs {
+            self.duration_secs = duration_secs;
+        }
+        if let Some(media) = req.media {
+            self.media = media;
+        }
+        if let Some(questions) = req.questions {
+            self.questions = questions;
+        }
+        if let Some(scoring) = req.scoring {
+            self.scoring = scoring;
+        }
+        if let Some(recurrence) = req.recurrence {
+            self.recurrence = recurrence;
+        }
+        if let Some(paused) = req.paused {
+            self.paused = paused;
+        }
+    }
+}
+
+impl From<CreateContestTemplateReq> for ContestTemplate {
+    fn from(req: CreateContestTemplateReq) -> Self {
+        Self {
+            title: req.title,
+            category: req.category,
+            banner: req.banner,
+            entry_fee: req.entry_fee,
+            bonus_usage_percent: req.bonus_usage_percent,
+            prize_pool: req.prize_pool,
+            prize_pool_type: req.prize_pool_type.unwrap_or_default(),
+            // configurable with `CONTEST_COMMISSION_PERCENT` env variable if not provided
+            commission_percent: req.commission_percent.unwrap_or_else(|| {
+                commission_percent_from_env(
+                    "CONTEST_COMMISSION_PERCENT",
+                    CONTEST_DEFAULT_COMMISSION_PERCENT,
+                )
+            }),
+            prizes: req.prizes,
+            tie_rule: req.tie_rule.unwrap_or_default(),
+            min_players: req.min_players,
+            max_players: req.max_players,
+            max_entries: req.max_entries.unwrap_or(1),
+            waitlist: req.waitlist.unwrap_or_default(),
+            duration_secs: req.duration_secs,
+            media: req.media,
+            questions: req.questions,
+            scoring: req.scoring.unwrap_or_default(),
+            mode: req.mode.unwrap_or_default(),
+            recurrence: req.recurrence,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Monday, 19 October 2026 00:00:00 IST
+    const MONDAY_TS: u64 = 1792348200;
+
+    const HOUR: u64 = 60 * 60;
+
+    const DAY: u64 = 24 * HOUR;
+
+    #[test]
+    fn test_recurrence_occurrences() {
+        let daily = Recurrence {
+            days: vec![],
+            hour: 21,
+            minute: 0,
+        };
+        let occurrences = daily.occurrences(MONDAY_TS, MONDAY_TS + 3 * DAY);
+        let expected: Vec<_> = (0..3).map(|d| MONDAY_TS + d * DAY + 21 * HOUR).collect();
+        assert_eq!(occurrences, expected);
+        // start time equal to `from_ts` is excluded
+        let from_ts = MONDAY_TS + 21 * HOUR;
+        assert_eq!(
+            daily.occurrences(from_ts, from_ts + DAY),
+            vec![from_ts + DAY]
+        );
+
+        let weekend = Recurrence {
+            days: vec![DayOfWeek::Saturday, DayOfWeek::Sunday],
+            hour: 10,
+            minute: 30,
+        };
+        let occurrences = weekend.occurrences(MONDAY_TS, MONDAY_TS + 14 * DAY);
+        assert_eq!(occurrences.len(), 4);
+        assert_eq!(occurrences[0], MONDAY_TS + 5 * DAY + 10 * HOUR + 30 * 60);
+        assert_eq!(occurrences[1], occurrences[0] + DAY);
+        assert_eq!(
+            weekend.next_after(MONDAY_TS + 6 * DAY + 11 * HOUR),
+            Some(occurrences[2])
+        );
+    }
+
+    #[test]
+    fn test_contest_template_due_occurrences() {
+        let mut template = ContestTemplate {
+            recurrence: Recurrence {
+                days: vec![],
+                hour: 21,
+                minute: 0,
+            },
+            ..Default::default()
+        };
+        let till_ts = MONDAY_TS + 3 * DAY;
+        assert_eq!(template.due_occurrences(MONDAY_TS, till_ts).len(), 3);
+        template.scheduled_till = Some(MONDAY_TS + DAY + 21 * HOUR);
+        assert_eq!(template.due_occurrences(MONDAY_TS, till_ts).len(), 1);
+        // recurrences missed while paused are not created later
+        template.scheduled_till = Some(MONDAY_TS - 10 * DAY);
+        assert_eq!(template.due_occurrences(MONDAY_TS, till_ts).len(), 3);
+    }
+
+    #[test]
+    fn test_contest_template_contest_at() {
+        let template = ContestTemplate {
+            id: Some(ObjectId::new()),
+            title: "Daily trivia".into(),
+            duration_secs: HOUR,
+            ..Default::default()
+        };
+        let start_ts = MONDAY_TS + 21 * HOUR;
+        let contest = template.contest_at(start_ts);
+        assert_eq!(contest.title, "Daily trivia 19-10-2026 21:00");
+        assert_eq!(contest.end_ts, start_ts + HOUR);
+        assert_eq!(contest.status, ContestStatus::Published);
+        assert_eq!(contest.template_id, template.id);
+        assert_eq!(contest.status_history.len(), 1);
+        assert_eq!(contest.prize_pool_type, PrizePoolType::Fixed);
+        assert_eq!(contest.entries_per_user(), 1);
+
+        // pool type, rake, entries and waitlist are carried to the contest
+        let req = CreateContestTemplateReq {
+            title: "Daily trivia".into(),
+            category: "movies".into(),
+            banner: "https://example.com/banner.png".into(),
+            entry_fee: 10,
+            bonus_usage_percent: 0,
+            prize_pool: 100,
+            prize_pool_type: Some(PrizePoolType::Guaranteed),
+            commission_percent: Some(15),
+            prizes: vec![],
+            tie_rule: None,
+            min_players: 2,
+            max_players: 50,
+            max_entries: Some(3),
+            waitlist: Some(true),
+            duration_secs: HOUR,
+            media: ContestMedia::default(),
+            questions: vec![],
+            scoring: None,
+            mode: None,
+            recurrence: Recurrence::default(),
+        };
+        let contest = ContestTemplate::from(req).contest_at(start_ts);
+        assert_eq!(contest.prize_pool_type, PrizePoolType::Guaranteed);
+        assert_eq!(contest.commission_percent, 15);
+        assert_eq!(contest.max_entries, 3);
+        assert!(contest.waitlist);
+        assert_eq!(contest.mode, ContestMode::default());
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::constants::*;
+
+use super::PeriodLimitsReq;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl LimitPeriod {
+    /// Limits are applied on rolling windows ending at the current time
+    pub fn window_secs(&self) -> u64 {
+        match self {
+            Self::Daily => 24 * 60 * 60,
+            Self::Weekly => 7 * 24 * 60 * 60,
+            Self::Monthly => 30 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl Display for LimitPeriod {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Daily => write!(f, "daily"),
+            Self::Weekly => write!(f, "weekly"),
+            Self::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+/// A raised limit which will be applied after the cooling off period
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingLimit {
+    /// None means the limit is going to be removed
+    pub amount: Option<u64>,
+    pub effective_ts: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Limit {
+    /// None means there is no limit
+    pub amount: Option<u64>,
+    pub pending: Option<PendingLimit>,
+}
+
+impl Limit {
+    /// Returns the limit applicable at the given time
+    pub fn effective(&self, ts: u64) -> Option<u64> {
+        match self.pending {
+            Some(pending) if pending.effective_ts <= ts => pending.amount,
+            _ => self.amount,
+        }
+    }
+
+    /// Update the limit. A lowered limit is applied immediately
+    /// whereas a raised or removed limit is applied after the cooling off period.
+    pub fn update(&mut self, amount: Option<u64>, ts: u64) {
+        let current = self.effective(ts);
+        self.amount = current;
+        self.pending = None;
+        let is_lowered = match (amount, current) {
+            (Some(new), Some(current)) => new < current,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if is_lowered {
+            self.amount = amount;
+        } else if amount != current {
+            self.pending = Some(PendingLimit {
+                amount,
+                effective_ts: ts + LIMIT_RAISE_COOLING_OFF_SECS,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodLimits {
+    #[serde(default)]
+    pub daily: Limit,
+    #[serde(default)]
+    pub weekly: Limit,
+    #[serde(default)]
+    pub monthly: Limit,
+}
+
+/// Sum of amounts in the rolling daily, weekly and monthly windows
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PeriodTotals {
+    pub daily: u64,
+    pub weekly: u64,
+    pub monthly: u64,
+}
+
+impl PeriodLimits {
+    pub fn update(&mut self, req: &PeriodLimitsReq, ts: u64) {
+        if let Some(amount) = req.daily {
+            self.daily.update(amount, ts);
+        }
+        if let Some(amount) = req.weekly {
+            self.weekly.update(amount, ts);
+        }
+        if let Some(amount) = req.monthly {
+            self.monthly.update(amount, ts);
+        }
+    }
+
+    /// Check if adding the amount to the totals exceeds any of the limits.
+    /// Returns the first period along with it's limit which is exceeded.
+    pub fn check(&self, totals: &PeriodTotals, amount: u64, ts: u64) -> Option<(LimitPeriod, u64)> {
+        let periods = [
+            (LimitPeriod::Daily, self.daily, totals.daily),
+            (LimitPeriod::Weekly, self.weekly, totals.weekly),
+            (LimitPeriod::Monthly, self.monthly, totals.monthly),
+        ];
+        periods
+            .into_iter()
+            .find_map(|(period, limit, total)| match limit.effective(ts) {
+                Some(limit) if total + amount > limit => Some((period, limit)),
+                _ => None,
+            })
+    }
+}
+
+/// Responsible gaming limits and self exclusion of an user
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GamingLimits {
+    pub user_id: u32,
+    #[serde(default)]
+    pub deposit: PeriodLimits,
+    #[serde(default)]
+    pub contest_spend: PeriodLimits,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_excluded_till: Option<u64>,
+
+    /// incremented on every update of the limits
+    #[serde(default)]
+    pub version: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_ts: Option<u64>,
+}
+
+impl GamingLimits {
+    pub fn is_self_excluded(&self, ts: u64) -> bool {
+        self.self_excluded_till
+            .map(|till| ts < till)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_limit_update() {
+        let ts = 1000;
+        let mut limit = Limit::default();
+        // setting a limit when there is no limit applies immediately
+        limit.update(Some(100), ts);
+        assert_eq!(limit.effective(ts), Some(100));
+        assert_eq!(limit.pending, None);
+        // lowered limit applies immediately
+        limit.update(Some(50), ts);
+        assert_eq!(limit.effective(ts), Some(50));
+        // raised limit applies after cooling off period
+        limit.update(Some(200), ts);
+        assert_eq!(limit.effective(ts), Some(50));
+        let effective_ts = ts + LIMIT_RAISE_COOLING_OFF_SECS;
+        assert_eq!(limit.effective(effective_ts - 1), Some(50));
+        assert_eq!(limit.effective(effective_ts), Some(200));
+        // lowering the limit cancels the pending raise
+        limit.update(Some(40), ts);
+        assert_eq!(limit.pending, None);
+        assert_eq!(limit.effective(effective_ts), Some(40));
+        // removed limit applies after cooling off period
+        limit.update(None, ts);
+        assert_eq!(limit.effective(ts), Some(40));
+        assert_eq!(limit.effective(effective_ts), None);
+        // pending limit is settled on next update
+        limit.update(Some(500), effective_ts);
+        assert_eq!(limit.amount, Some(500));
+        assert_eq!(limit.pending, None);
+    }
+
+    #[test]
+    fn test_period_limits_check() {
+        let ts = 1000;
+        let mut limits = PeriodLimits::default();
+        let totals = PeriodTotals {
+            daily: 50,
+            weekly: 300,
+            monthly: 900,
+        };
+        assert_eq!(limits.check(&totals, 1000, ts), None);
+        let req = PeriodLimitsReq {
+            daily: Some(Some(100)),
+            monthly: Some(Some(1000)),
+            ..Default::default()
+        };
+        limits.update(&req, ts);
+        assert_eq!(limits.check(&totals, 50, ts), None);
+        assert_eq!(
+            limits.check(&totals, 51, ts),
+            Some((LimitPeriod::Daily, 100))
+        );
+        let totals = PeriodTotals {
+            daily: 0,
+            weekly: 0,
+            monthly: 990,
+        };
+        assert_eq!(
+            limits.check(&totals, 20, ts),
+            Some((LimitPeriod::Monthly, 1000))
+        );
+    }
+
+    #[test]
+    fn test_period_limits_req_deserialize() {
+        let req: PeriodLimitsReq =
+            serde_json::from_str(r#"{"daily": 100, "weekly": null}"#).unwrap();
+        assert_eq!(req.daily, Some(Some(100)));
+        assert_eq!(req.weekly, Some(None));
+        assert_eq!(req.monthly, None);
+    }
+
+    #[test]
+    fn test_gaming_limits_is_self_excluded() {
+        let mut limits = GamingLimits::default();
+        assert!(!limits.is_self_excluded(100));
+        limits.self_excluded_till = Some(200);
+        assert!(limits.is_self_excluded(100));
+        assert!(!limits.is_self_excluded(200));
+    }
+}
@@ -0,0 +1,52 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::get_epoch_ts;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum HouseTransactionType {
+    /// commission earned from the entry fees
+    #[default]
+    Rake,
+    /// paid to cover the guaranteed prize pool
+    Shortfall,
+}
+
+/// Amount earned or paid by the house for a contest, recorded once per type
+/// when the contest is finalized
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HouseTransaction {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub id: Option<ObjectId>,
+    #[schema(value_type = String)]
+    pub contest_id: ObjectId,
+    pub transaction_type: HouseTransactionType,
+    pub amount: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<u64>,
+}
+
+impl HouseTransaction {
+    pub fn new(contest_id: ObjectId, transaction_type: HouseTransactionType, amount: u64) -> Self {
+        Self {
+            contest_id,
+            transaction_type,
+            amount,
+            created_ts: Some(get_epoch_ts()),
+            ..Default::default()
+        }
+    }
+
+    /// Field of the house account with the total of the transaction type
+    pub fn total_field(&self) -> &'static str {
+        match self.transaction_type {
+            HouseTransactionType::Rake => "rakeTotal",
+            HouseTransactionType::Shortfall => "shortfallTotal",
+        }
+    }
+}
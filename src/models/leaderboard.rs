@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Rank of an entry of the user in the contest leaderboard
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub user_id: u32,
+
+    #[serde(default)]
+    pub entry_no: u32,
+    pub score: i32,
+
+    /// missing till the user finishes the play
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_taken: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_ts: Option<u64>,
+}
+
+/// A page of the leaderboard with the rank of the requesting user
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LeaderboardPage {
+    pub total: u64,
+    pub entries: Vec<LeaderboardEntry>,
+    pub own_rank: Option<LeaderboardEntry>,
+}
+
+impl LeaderboardPage {
+    /// Page of the complete leaderboard sorted by rank, own rank is the best entry of the user
+    pub fn from_ranked(ranked: &[LeaderboardEntry], skip: u64, limit: u64, user_id: u32) -> Self {
+        let entries = ranked
+            .iter()
+            .skip(skip as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        let own_rank = ranked.iter().find(|e| e.user_id == user_id).cloned();
+        Self {
+            total: ranked.len() as u64,
+            entries,
+            own_rank,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_leaderboard_page_from_ranked() {
+        let ranked: Vec<_> = (1..=5)
+            .map(|rank| LeaderboardEntry {
+                rank,
+                user_id: rank * 10,
+                score: 10 - rank as i32,
+                ..Default::default()
+            })
+            .collect();
+        let page = LeaderboardPage::from_ranked(&ranked, 2, 2, 50);
+        assert_eq!(page.total, 5);
+        assert_eq!(page.entries, ranked[2..4].to_vec());
+        assert_eq!(page.own_rank.map(|e| e.rank), Some(5));
+        let page = LeaderboardPage::from_ranked(&ranked, 4, 2, 60);
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.own_rank, None);
+    }
+}
@@ -1,13 +1,45 @@
+mod contest;
+mod contest_event;
+mod contest_listing;
+mod contest_template;
+mod gaming_limit;
+mod house;
 mod jwt_claims;
+mod leaderboard;
+mod notification;
 mod otp;
+mod play_tracker;
+mod prize;
+mod question;
+mod question_set;
+mod referral;
 mod request;
 mod response;
+mod statement;
+mod tax;
 mod user;
+mod waitlist;
 mod wallet;
 
+pub use contest::*;
+pub use contest_event::*;
+pub use contest_listing::*;
+pub use contest_template::*;
+pub use gaming_limit::*;
+pub use house::*;
 pub use jwt_claims::*;
+pub use leaderboard::*;
+pub use notification::*;
 pub use otp::*;
+pub use play_tracker::*;
+pub use prize::*;
+pub use question::*;
+pub use question_set::*;
+pub use referral::*;
 pub use request::*;
 pub use response::*;
+pub use statement::*;
+pub use tax::*;
 pub use user::*;
+pub use waitlist::*;
 pub use wallet::*;
@@ -0,0 +1,46 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::utils::get_epoch_ts;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationReqStatus {
+    #[default]
+    Pending,
+    Finished,
+    Error,
+}
+
+/// Notification queued for the user, sent later by the notification job.
+/// `data` has the values of the event to be filled in the notification content.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRequest {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: u32,
+    pub event_name: String,
+    pub data: HashMap<String, String>,
+    pub status: NotificationReqStatus,
+
+    #[serde(default)]
+    pub retry_count: u32,
+    pub created_ts: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_ts: Option<u64>,
+}
+
+impl NotificationRequest {
+    pub fn new(user_id: u32, event_name: &str, data: HashMap<String, String>) -> Self {
+        Self {
+            user_id,
+            event_name: event_name.into(),
+            data,
+            created_ts: get_epoch_ts(),
+            ..Default::default()
+        }
+    }
+}
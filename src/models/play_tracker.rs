@@ -0,0 +1,181 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::get_epoch_ts;
+
+use super::Money;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PlayTrackerStatus {
+    #[default]
+    Joined,
+    Started,
+    Finished,
+    /// left before the start, the entry fee is refunded
+    Withdrawn,
+}
+
+/// Answer of a question submitted by the user, `question_no` is the index of the question
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayAnswer {
+    pub question_no: u32,
+    pub answer: u32,
+    pub is_correct: bool,
+    pub points: i32,
+    pub answered_ts: u64,
+}
+
+/// Time when a question is served to the user for the first time
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServedQuestion {
+    pub question_no: u32,
+    pub served_ts: u64,
+}
+
+/// Participation of an user in a contest
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayTracker {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub id: Option<ObjectId>,
+    #[schema(value_type = String)]
+    pub contest_id: ObjectId,
+    pub user_id: u32,
+
+    /// entry of the user in the contest starting from 1, set to 1 for the older plays
+    #[serde(default = "default_entry_no")]
+    pub entry_no: u32,
+    pub status: PlayTrackerStatus,
+
+    /// entry fee paid from real and bonus balance
+    pub entry_fee: Money,
+
+    #[serde(default)]
+    pub served: Vec<ServedQuestion>,
+
+    #[serde(default)]
+    pub answers: Vec<PlayAnswer>,
+
+    /// can be negative with negative marking
+    #[serde(default)]
+    pub score: i32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_ts: Option<u64>,
+
+    /// seconds taken from start till finish of the play
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_taken: Option<u64>,
+
+    /// joined a practice contest, counted for the daily free play limit
+    #[serde(default)]
+    pub practice: bool,
+
+    /// final rank, set only for the winners when the prize is credited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prize: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prize_credited_ts: Option<u64>,
+
+    /// set when the entry fee is refunded for the cancelled contest or the withdrawn entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refunded_ts: Option<u64>,
+
+    /// prize or refund which can not be credited, e.g. for a wallet freeze,
+    /// is put on hold with the reason for the manual handling
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit_held_reason: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit_held_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_ts: Option<u64>,
+}
+
+fn default_entry_no() -> u32 {
+    1
+}
+
+impl PlayTracker {
+    pub fn new(contest_id: ObjectId, user_id: u32, entry_fee: Money) -> Self {
+        Self {
+            contest_id,
+            user_id,
+            entry_no: 1,
+            entry_fee,
+            created_ts: Some(get_epoch_ts()),
+            ..Default::default()
+        }
+    }
+
+    pub fn served_ts(&self, question_no: u32) -> Option<u64> {
+        self.served
+            .iter()
+            .find(|s| s.question_no == question_no)
+            .map(|s| s.served_ts)
+    }
+
+    pub fn is_answered(&self, question_no: u32) -> bool {
+        self.answers.iter().any(|a| a.question_no == question_no)
+    }
+
+    /// Play without the submitted answers, to not reveal the correct answers
+    /// of a contest which is still live
+    pub fn without_answers(self) -> Self {
+        Self {
+            answers: vec![],
+            ..self
+        }
+    }
+
+    /// Time taken till the given finish time, play after the contest end is not counted
+    pub fn time_taken_till(&self, finished_ts: u64, contest_end_ts: u64) -> u64 {
+        let started_ts = self.started_ts.unwrap_or(finished_ts);
+        finished_ts.min(contest_end_ts).saturating_sub(started_ts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_play_tracker() {
+        let mut play_tracker = PlayTracker {
+            started_ts: Some(100),
+            ..Default::default()
+        };
+        assert!(!play_tracker.is_answered(0));
+        assert_eq!(play_tracker.served_ts(0), None);
+        play_tracker.served.push(ServedQuestion {
+            question_no: 0,
+            served_ts: 120,
+        });
+        assert_eq!(play_tracker.served_ts(0), Some(120));
+        play_tracker.answers.push(PlayAnswer {
+            question_no: 0,
+            ..Default::default()
+        });
+        assert!(play_tracker.is_answered(0));
+        assert!(!play_tracker.is_answered(1));
+        assert_eq!(play_tracker.time_taken_till(150, 200), 50);
+        assert_eq!(play_tracker.time_taken_till(250, 200), 100);
+    }
+}
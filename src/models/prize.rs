@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::LeaderboardEntry;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PrizeType {
+    #[default]
+    Fixed,
+    Percent,
+}
+
+/// Prize for each of the ranks from `rank_from` till `rank_to`, both inclusive.
+/// `amount` is the fixed amount or the percentage of the prize pool as per `prize_type`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RankPrize {
+    #[validate(range(min = 1))]
+    pub rank_from: u32,
+    #[validate(range(min = 1))]
+    pub rank_to: u32,
+    #[serde(default)]
+    pub prize_type: PrizeType,
+    #[validate(range(min = 1))]
+    pub amount: u64,
+}
+
+impl RankPrize {
+    /// Prize amount for each rank of the range
+    pub fn amount_of(&self, pool: u64) -> u64 {
+        match self.prize_type {
+            PrizeType::Fixed => self.amount,
+            PrizeType::Percent => pool * self.amount / 100,
+        }
+    }
+
+    pub fn total(&self, pool: u64) -> u64 {
+        let ranks = self.rank_to.saturating_sub(self.rank_from) + 1;
+        self.amount_of(pool) * ranks as u64
+    }
+}
+
+/// How the prizes are given to the players with the same score
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum TieRule {
+    /// ties are broken by the time taken and then finish time as in the leaderboard
+    #[default]
+    BreakByTime,
+    /// players with the same score share the prizes of their ranks equally
+    SplitByScore,
+}
+
+/// Prize paid to the entry of the user for the rank
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payout {
+    pub user_id: u32,
+    pub entry_no: u32,
+    pub rank: u32,
+    pub amount: u64,
+}
+
+/// Prize for each of the ranks of the range in the payout table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PrizePayout {
+    pub rank_from: u32,
+    pub rank_to: u32,
+    pub amount: u64,
+}
+
+/// Prize table of a contest
+pub struct PrizeStructure<'a> {
+    pub pool: u64,
+    pub prizes: &'a [RankPrize],
+    pub tie_rule: &'a TieRule,
+}
+
+impl<'a> PrizeStructure<'a> {
+    /// Check that the rank ranges are valid for the players and do not overlap,
+    /// and the total prize does not exceed the pool
+    pub fn validate(&self, max_players: u32) -> Result<(), String> {
+        let mut prizes: Vec<_> = self.prizes.iter().collect();
+        prizes.sort_by_key(|p| p.rank_from);
+        for prize in prizes.iter() {
+            let range = format!("{}-{}", prize.rank_from, prize.rank_to);
+            if prize.rank_from > prize.rank_to || prize.rank_to > max_players {
+                return Err(format!("Invalid prize rank range {range}"));
+            }
+            if prize.prize_type == PrizeType::Percent && prize.amount > 100 {
+                return Err(format!(
+                    "Prize percentage of rank range {range} exceeds 100"
+                ));
+            }
+        }
+        for pair in prizes.windows(2) {
+            if pair[0].rank_to >= pair[1].rank_from {
+                let msg = format!(
+                    "Prize rank ranges {}-{} and {}-{} overlap",
+                    pair[0].rank_from, pair[0].rank_to, pair[1].rank_from, pair[1].rank_to
+                );
+                return Err(msg);
+            }
+        }
+        if self.total() > self.pool {
+            let msg = format!(
+                "Total prize {} exceeds the prize pool {}",
+                self.total(),
+                self.pool
+            );
+            return Err(msg);
+        }
+        Ok(())
+    }
+
+    pub fn total(&self) -> u64 {
+        self.prizes.iter().map(|p| p.total(self.pool)).sum()
+    }
+
+    /// Prize amount for the rank, 0 if the rank does not win any prize
+    pub fn prize_for_rank(&self, rank: u32) -> u64 {
+        self.prizes
+            .iter()
+            .find(|p| p.rank_from <= rank && rank <= p.rank_to)
+            .map(|p| p.amount_of(self.pool))
+            .unwrap_or_default()
+    }
+
+    /// Prizes of the players in the leaderboard sorted by rank.
+    /// Split prizes are rounded down, players with the same score get the top rank of the group.
+    pub fn payouts(&self, ranked: &[LeaderboardEntry]) -> Vec<Payout> {
+        let mut payouts = vec![];
+        if *self.tie_rule == TieRule::BreakByTime {
+            for entry in ranked {
+                payouts.push(Payout {
+                    user_id: entry.user_id,
+                    entry_no: entry.entry_no,
+                    rank: entry.rank,
+                    amount: self.prize_for_rank(entry.rank),
+                });
+            }
+        } else {
+            let mut position = 1;
+            for group in ranked.chunk_by(|a, b| a.score == b.score) {
+                let last = position + group.len() as u32;
+                let total: u64 = (position..last).map(|r| self.prize_for_rank(r)).sum();
+                for entry in group {
+                    payouts.push(Payout {
+                        user_id: entry.user_id,
+                        entry_no: entry.entry_no,
+                        rank: position,
+                        amount: total / group.len() as u64,
+                    });
+                }
+                position = last;
+            }
+        }
+        payouts.retain(|p| p.amount > 0);
+        payouts
+    }
+
+    /// Payout table if the given number of players participate, without any ties
+    pub fn preview(&self, participants: u32) -> Vec<PrizePayout> {
+        let mut prizes: Vec<_> = self
+            .prizes
+            .iter()
+            .filter(|p| p.rank_from <= participants)
+            .map(|p| PrizePayout {
+                rank_from: p.rank_from,
+                rank_to: p.rank_to.min(participants),
+                amount: p.amount_of(self.pool),
+            })
+            .collect();
+        prizes.sort_by_key(|p| p.rank_from);
+        prizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn prizes() -> Vec<RankPrize> {
+        vec![
+            RankPrize {
+                rank_from: 1,
+                rank_to: 1,
+                prize_type: PrizeType::Percent,
+                amount: 50,
+            },
+            RankPrize {
+                rank_from: 2,
+                rank_to: 5,
+                prize_type: PrizeType::Percent,
+                amount: 10,
+            },
+            RankPrize {
+                rank_from: 6,
+                rank_to: 10,
+                prize_type: PrizeType::Fixed,
+                amount: 20,
+            },
+        ]
+    }
+
+    fn entry(rank: u32, score: i32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank,
+            user_id: rank * 10,
+            score,
+            ..Default::default()
+        }
+    }
+
+    fn validate(prizes: &[RankPrize], pool: u64, max_players: u32) -> Result<(), String> {
+        let structure = PrizeStructure {
+            pool,
+            prizes,
+            tie_rule: &TieRule::BreakByTime,
+        };
+        structure.validate(max_players)
+    }
+
+    #[test]
+    fn test_prize_structure_validate() {
+        let mut prizes = prizes();
+        assert!(validate(&prizes, 1000, 10).is_ok());
+        let err = validate(&prizes, 1000, 9).unwrap_err();
+        assert_eq!(err, "Invalid prize rank range 6-10");
+        prizes[2].amount = 21;
+        let err = validate(&prizes, 1000, 10).unwrap_err();
+        assert_eq!(err, "Total prize 1005 exceeds the prize pool 1000");
+        prizes[2].rank_from = 5;
+        let err = validate(&prizes, 1000, 10).unwrap_err();
+        assert_eq!(err, "Prize rank ranges 2-5 and 5-10 overlap");
+        prizes[0].amount = 101;
+        let err = validate(&prizes, 1000, 10).unwrap_err();
+        assert_eq!(err, "Prize percentage of rank range 1-1 exceeds 100");
+    }
+
+    #[test]
+    fn test_prize_structure_payouts() {
+        let prizes = prizes();
+        let ranked = vec![
+            entry(1, 10),
+            entry(2, 8),
+            entry(3, 8),
+            entry(4, 8),
+            entry(5, 1),
+        ];
+        let tie_rule = TieRule::BreakByTime;
+        let structure = PrizeStructure {
+            pool: 1000,
+            prizes: &prizes,
+            tie_rule: &tie_rule,
+        };
+        let amounts: Vec<_> = structure
+            .payouts(&ranked)
+            .iter()
+            .map(|p| p.amount)
+            .collect();
+        assert_eq!(amounts, vec![500, 100, 100, 100, 100]);
+        let ranked = vec![entry(1, 10), entry(2, 10), entry(3, 8), entry(4, 1)];
+        let tie_rule = TieRule::SplitByScore;
+        let structure = PrizeStructure {
+            tie_rule: &tie_rule,
+            ..structure
+        };
+        let payouts = structure.payouts(&ranked);
+        assert_eq!(payouts.len(), 4);
+        assert_eq!((payouts[0].rank, payouts[0].amount), (1, 300));
+        assert_eq!((payouts[1].rank, payouts[1].amount), (1, 300));
+        assert_eq!((payouts[2].rank, payouts[2].amount), (3, 100));
+    }
+
+    #[test]
+    fn test_prize_structure_preview() {
+        let prizes = prizes();
+        let tie_rule = TieRule::default();
+        let structure = PrizeStructure {
+            pool: 1000,
+            prizes: &prizes,
+            tie_rule: &tie_rule,
+        };
+        let preview = structure.preview(3);
+        assert_eq!(preview.len(), 2);
+        assert_eq!(
+            preview[1],
+            PrizePayout {
+                rank_from: 2,
+                rank_to: 3,
+                amount: 100
+            }
+        );
+        assert_eq!(structure.preview(50).len(), 3);
+    }
+}
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::MediaType;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum QuestionType {
+    #[default]
+    MultipleChoice,
+    Image,
+    Clip,
+    TrueFalse,
+}
+
+/// Segment of a trailer in `movies` or `clips` collection to be played with the question
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionMedia {
+    pub media_type: MediaType,
+    #[validate(length(min = 1))]
+    pub name: String,
+    pub start_secs: Option<u32>,
+    pub end_secs: Option<u32>,
+}
+
+/// Question of the contest, `answer` is the index of the correct option
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Question {
+    #[serde(default)]
+    pub question_type: QuestionType,
+    #[validate(length(min = 1))]
+    pub question: String,
+    #[validate(length(min = 2))]
+    pub options: Vec<String>,
+    pub answer: u32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(url)]
+    pub image_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate]
+    pub media: Option<QuestionMedia>,
+
+    /// points for the correct answer, overrides the points of the contest scoring rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub points: Option<u32>,
+
+    /// time allowed to answer after the question is served
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1))]
+    pub time_limit_secs: Option<u32>,
+}
+
+impl Question {
+    /// Check the fields required by the question type
+    pub fn check_type_fields(&self) -> Result<(), String> {
+        if self.answer as usize >= self.options.len() {
+            return Err("invalid answer".into());
+        }
+        match self.question_type {
+            QuestionType::TrueFalse if self.options.len() != 2 => {
+                Err("true/false question must have 2 options".into())
+            }
+            QuestionType::Image if self.image_url.is_none() => {
+                Err("image question must have imageUrl".into())
+            }
+            QuestionType::Clip if self.media.is_none() => {
+                Err("clip question must have media".into())
+            }
+            _ => match &self.media {
+                Some(QuestionMedia {
+                    start_secs: Some(start),
+                    end_secs: Some(end),
+                    ..
+                }) if start >= end => Err("media endSecs must be greater than startSecs".into()),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+/// Question served to the user, without the answer
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionView {
+    pub question_no: u32,
+    pub question_type: QuestionType,
+    pub question: String,
+    pub options: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media: Option<QuestionMedia>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_limit_secs: Option<u32>,
+}
+
+impl QuestionView {
+    pub fn new(question_no: u32, question: &Question) -> Self {
+        Self {
+            question_no,
+            question_type: question.question_type.clone(),
+            question: question.question.clone(),
+            options: question.options.clone(),
+            image_url: question.image_url.clone(),
+            media: question.media.clone(),
+            time_limit_secs: question.time_limit_secs,
+        }
+    }
+}
+
+/// Scoring rule of a contest.
+/// Time bonus is given for correct answers of questions with time limit,
+/// in proportion to the time left out of the time limit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoringRule {
+    pub points: u32,
+    #[serde(default)]
+    pub negative_points: u32,
+    #[serde(default)]
+    pub max_time_bonus: u32,
+}
+
+impl Default for ScoringRule {
+    fn default() -> Self {
+        Self {
+            points: 1,
+            negative_points: 0,
+            max_time_bonus: 0,
+        }
+    }
+}
+
+impl ScoringRule {
+    /// Points for the answer of the question, None if answered after the time limit
+    pub fn score(&self, question: &Question, answer: u32, elapsed_secs: u64) -> Option<i32> {
+        let time_limit = question.time_limit_secs.map(|t| t as u64);
+        if time_limit.is_some_and(|limit| elapsed_secs > limit) {
+            return None;
+        }
+        if question.answer != answer {
+            return Some(-(self.negative_points as i32));
+        }
+        let points = question.points.unwrap_or(self.points) as u64;
+        let time_bonus = match time_limit {
+            Some(limit) => self.max_time_bonus as u64 * (limit - elapsed_secs) / limit,
+            None => 0,
+        };
+        Some((points + time_bonus) as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn question() -> Question {
+        Question {
+            question: "Question 1".into(),
+            options: vec!["A".into(), "B".into(), "C".into()],
+            answer: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_question_check_type_fields() {
+        assert!(question().check_type_fields().is_ok());
+        let mut q = question();
+        q.answer = 3;
+        assert_eq!(q.check_type_fields().unwrap_err(), "invalid answer");
+        let mut q = question();
+        q.question_type = QuestionType::TrueFalse;
+        assert!(q.check_type_fields().is_err());
+        q.options.pop();
+        assert!(q.check_type_fields().is_ok());
+        let mut q = question();
+        q.question_type = QuestionType::Clip;
+        assert_eq!(
+            q.check_type_fields().unwrap_err(),
+            "clip question must have media"
+        );
+        q.media = Some(QuestionMedia {
+            start_secs: Some(20),
+            end_secs: Some(10),
+            ..Default::default()
+        });
+        assert!(q.check_type_fields().is_err());
+    }
+
+    #[test]
+    fn test_scoring_rule_score() {
+        let rule = ScoringRule {
+            points: 10,
+            negative_points: 2,
+            max_time_bonus: 5,
+        };
+        let mut q = question();
+        assert_eq!(rule.score(&q, 1, 100), Some(10));
+        assert_eq!(rule.score(&q, 0, 100), Some(-2));
+        q.time_limit_secs = Some(20);
+        q.points = Some(20);
+        assert_eq!(rule.score(&q, 1, 0), Some(25));
+        assert_eq!(rule.score(&q, 1, 10), Some(22));
+        assert_eq!(rule.score(&q, 1, 20), Some(20));
+        assert_eq!(rule.score(&q, 1, 21), None);
+    }
+}
@@ -0,0 +1,70 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{ContestMedia, CreateQuestionSetReq, Question};
+
+/// Questions prepared by admin for the private contests created by the users.
+/// Only the approved sets can be used.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionSet {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    pub category: String,
+    pub media: ContestMedia,
+    pub questions: Vec<Question>,
+
+    #[serde(default)]
+    pub approved: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<u32>,
+}
+
+impl From<CreateQuestionSetReq> for QuestionSet {
+    fn from(req: CreateQuestionSetReq) -> Self {
+        Self {
+            name: req.name,
+            category: req.category,
+            media: req.media,
+            questions: req.questions,
+            ..Default::default()
+        }
+    }
+}
+
+/// Question set as shown to the users, without the questions
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionSetView {
+    #[schema(value_type = String)]
+    pub id: ObjectId,
+    pub name: String,
+    pub category: String,
+    pub media: ContestMedia,
+    pub question_count: usize,
+}
+
+impl From<QuestionSet> for QuestionSetView {
+    fn from(set: QuestionSet) -> Self {
+        Self {
+            id: set.id.unwrap_or_default(),
+            name: set.name,
+            category: set.category,
+            media: set.media,
+            question_count: set.questions.len(),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{LoginScheme, User};
+
+/// Segment of users eligible to redeem a special referral code.
+/// All the criteria provided must be satisfied by the user.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralTargetSegment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub login_scheme: Option<LoginScheme>,
+
+    /// user must be registered on or after this timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registered_after: Option<u64>,
+
+    /// user must not have played more contests than this
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_total_played: Option<u32>,
+}
+
+impl ReferralTargetSegment {
+    pub fn is_eligible(&self, user: &User) -> bool {
+        if let Some(login_scheme) = &self.login_scheme {
+            if login_scheme != &user.login_scheme {
+                return false;
+            }
+        }
+        if let Some(registered_after) = self.registered_after {
+            if user.created_ts.unwrap_or_default() < registered_after {
+                return false;
+            }
+        }
+        if let Some(max_total_played) = self.max_total_played {
+            if user.total_played.unwrap_or_default() > max_total_played {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Referral code for special campaigns like influencers and partners
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecialReferralCode {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub id: Option<ObjectId>,
+    pub referral_code: String,
+    pub campaign_name: String,
+    pub bonus: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_redemptions: Option<u32>,
+
+    #[serde(default)]
+    pub redemption_count: u32,
+    pub valid_from: u64,
+    pub valid_till: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_segment: Option<ReferralTargetSegment>,
+    pub is_active: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<u32>,
+}
+
+impl SpecialReferralCode {
+    pub fn is_valid_at(&self, ts: u64) -> bool {
+        self.is_active && self.valid_from <= ts && ts <= self.valid_till
+    }
+
+    pub fn is_fully_redeemed(&self) -> bool {
+        self.max_redemptions
+            .map(|max| self.redemption_count >= max)
+            .unwrap_or_default()
+    }
+
+    pub fn is_eligible(&self, user: &User) -> bool {
+        self.target_segment
+            .as_ref()
+            .map(|segment| segment.is_eligible(user))
+            .unwrap_or(true)
+    }
+}
+
+/// A single redemption of a special referral code
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralRedemption {
+    pub user_id: u32,
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redeemed_ts: Option<u64>,
+}
+
+impl From<User> for ReferralRedemption {
+    fn from(user: User) -> Self {
+        Self {
+            user_id: user.id,
+            name: user.name,
+            redeemed_ts: user.used_referral_code_ts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_referral_target_segment_is_eligible() {
+        let user = User {
+            login_scheme: LoginScheme::Google,
+            created_ts: Some(100),
+            total_played: Some(3),
+            ..Default::default()
+        };
+        assert!(ReferralTargetSegment::default().is_eligible(&user));
+        let segment = ReferralTargetSegment {
+            login_scheme: Some(LoginScheme::Google),
+            registered_after: Some(100),
+            max_total_played: Some(3),
+        };
+        assert!(segment.is_eligible(&user));
+        let segment = ReferralTargetSegment {
+            login_scheme: Some(LoginScheme::Facebook),
+            ..Default::default()
+        };
+        assert!(!segment.is_eligible(&user));
+        let segment = ReferralTargetSegment {
+            registered_after: Some(101),
+            ..Default::default()
+        };
+        assert!(!segment.is_eligible(&user));
+        let segment = ReferralTargetSegment {
+            max_total_played: Some(2),
+            ..Default::default()
+        };
+        assert!(!segment.is_eligible(&user));
+    }
+
+    #[test]
+    fn test_special_referral_code_validity() {
+        let mut code = SpecialReferralCode {
+            is_active: true,
+            valid_from: 10,
+            valid_till: 20,
+            max_redemptions: Some(2),
+            redemption_count: 1,
+            ..Default::default()
+        };
+        assert!(code.is_valid_at(15));
+        assert!(!code.is_valid_at(9));
+        assert!(!code.is_valid_at(21));
+        assert!(!code.is_fully_redeemed());
+        code.redemption_count = 2;
+        assert!(code.is_fully_redeemed());
+        code.max_redemptions = None;
+        assert!(!code.is_fully_redeemed());
+        code.is_active = false;
+        assert!(!code.is_valid_at(15));
+    }
+}
@@ -1,9 +1,15 @@
 use mongodb::bson::oid::ObjectId;
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
-use crate::impl_validate_extra;
+use crate::{constants::*, impl_validate_extra};
+
+use super::{
+    ContestMedia, ContestMode, ContestPhase, ContestSort, ContestStatus, PrizePoolType, Question,
+    RankPrize, Recurrence, ReferralTargetSegment, ScoringRule, SortOrder, StatementFormat, TieRule,
+    WalletFreezeScope,
+};
 
 /// request schema for Add Balanace Init request
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
@@ -11,7 +17,6 @@ pub struct AddBalInitReq {
     #[validate(range(min = 1))]
     pub amount: u64,
 }
-impl_validate_extra!(AddBalInitReq);
 
 /// request schema for Add Balance Init request
 #[derive(Debug, Deserialize, Validate, ToSchema)]
@@ -24,3 +29,457 @@ pub struct AddBalEndReq {
     pub error_reason: Option<String>,
     pub tracking_id: Option<String>,
 }
+
+/// request schema for Withdraw Balance request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawReq {
+    #[validate(range(min = "WITHDRAW_BAL_MIN_AMOUNT"))]
+    pub amount: u64,
+    #[validate(length(min = 3))]
+    pub upi_id: String,
+}
+
+/// request schema for Apply Referral Code request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyReferralReq {
+    #[validate(length(min = 1))]
+    pub referral_code: String,
+}
+
+/// request schema for Create Special Referral Code request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSpecialReferralReq {
+    #[validate(length(min = 4, max = 20))]
+    pub referral_code: String,
+    #[validate(length(min = 1))]
+    pub campaign_name: String,
+    #[validate(range(min = 1))]
+    pub bonus: u64,
+    #[validate(range(min = 1))]
+    pub max_redemptions: Option<u32>,
+    pub valid_from: u64,
+    pub valid_till: u64,
+    pub target_segment: Option<ReferralTargetSegment>,
+}
+
+/// request schema for Update Special Referral Code request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSpecialReferralReq {
+    #[validate(length(min = 1))]
+    pub campaign_name: Option<String>,
+    #[validate(range(min = 1))]
+    pub bonus: Option<u64>,
+    #[validate(range(min = 1))]
+    pub max_redemptions: Option<u32>,
+    pub valid_from: Option<u64>,
+    pub valid_till: Option<u64>,
+    pub target_segment: Option<ReferralTargetSegment>,
+    pub is_active: Option<bool>,
+}
+
+/// query params for Wallet Statement request
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementParams {
+    pub from_ts: u64,
+    pub to_ts: u64,
+    pub format: Option<StatementFormat>,
+}
+
+/// query params for bulk Wallet Statement export request
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkStatementParams {
+    /// month in YYYY-MM format
+    #[validate(length(equal = 7))]
+    pub month: String,
+    pub format: Option<StatementFormat>,
+
+    /// `x-next-cursor` header of the previous page to get the next page of the users
+    pub cursor: Option<u32>,
+}
+
+/// query params for Tax Certificate request
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxCertificateParams {
+    /// starting year of the financial year, e.g. 2023 for 2023-24
+    #[validate(range(min = 2000, max = 9998))]
+    pub financial_year: i32,
+    pub format: Option<StatementFormat>,
+}
+impl_validate_extra!(TaxCertificateParams);
+
+/// Distinguish between a missing field and a field with null value
+fn deserialize_some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Limits to be updated, a missing field keeps the limit unchanged
+/// and a null value removes the limit
+#[derive(Debug, Default, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodLimitsReq {
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<u64>)]
+    pub daily: Option<Option<u64>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<u64>)]
+    pub weekly: Option<Option<u64>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<u64>)]
+    pub monthly: Option<Option<u64>>,
+}
+
+/// request schema for Update Gaming Limits request
+#[derive(Debug, Default, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateGamingLimitsReq {
+    pub deposit: Option<PeriodLimitsReq>,
+    pub contest_spend: Option<PeriodLimitsReq>,
+}
+impl_validate_extra!(UpdateGamingLimitsReq);
+
+/// request schema for Self Exclusion request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfExclusionReq {
+    #[validate(range(min = 1, max = "SELF_EXCLUSION_MAX_DAYS"))]
+    pub days: u64,
+}
+impl_validate_extra!(SelfExclusionReq);
+
+/// request schema for Freeze Wallet request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FreezeWalletReq {
+    pub scope: WalletFreezeScope,
+    #[validate(length(min = 3))]
+    pub reason: String,
+    pub block_credits: Option<bool>,
+    pub expires_ts: u64,
+}
+
+/// request schema for Unfreeze Wallet request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UnfreezeWalletReq {
+    #[validate(length(min = 3))]
+    pub reason: String,
+}
+impl_validate_extra!(UnfreezeWalletReq);
+
+/// request schema for Create Contest request
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateContestReq {
+    #[validate(length(min = 3, max = 100))]
+    pub title: String,
+    #[validate(length(min = 1))]
+    pub category: String,
+    #[validate(url)]
+    pub banner: String,
+    pub entry_fee: u64,
+    #[validate(range(max = 100))]
+    pub bonus_usage_percent: u32,
+    /// fixed or the guaranteed minimum pool as per `prizePoolType`
+    pub prize_pool: u64,
+    pub prize_pool_type: Option<PrizePoolType>,
+    /// rake on the entry fees for the dynamic and guaranteed pools
+    #[validate(range(max = 100))]
+    pub commission_percent: Option<u32>,
+    #[validate]
+    pub prizes: Vec<RankPrize>,
+    pub tie_rule: Option<TieRule>,
+    #[validate(range(min = 1))]
+    pub min_players: u32,
+    #[validate(range(min = 1, max = "CONTEST_MAX_PLAYERS"))]
+    pub max_players: u32,
+    #[validate(range(min = 1, max = "CONTEST_MAX_ENTRIES"))]
+    pub max_entries: Option<u32>,
+    pub waitlist: Option<bool>,
+    pub start_ts: u64,
+    pub end_ts: u64,
+    #[validate]
+    pub media: ContestMedia,
+    #[validate(length(min = 1, max = "CONTEST_MAX_QUESTIONS"))]
+    #[validate]
+    pub questions: Vec<Question>,
+    pub scoring: Option<ScoringRule>,
+    /// practice contests must have zero entry fee
+    pub mode: Option<ContestMode>,
+}
+
+/// request schema for Update Contest request
+#[derive(Debug, Default, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateContestReq {
+    #[validate(length(min = 3, max = 100))]
+    pub title: Option<String>,
+    #[validate(length(min = 1))]
+    pub category: Option<String>,
+    #[validate(url)]
+    pub banner: Option<String>,
+    pub entry_fee: Option<u64>,
+    #[validate(range(max = 100))]
+    pub bonus_usage_percent: Option<u32>,
+    pub prize_pool: Option<u64>,
+    pub prize_pool_type: Option<PrizePoolType>,
+    #[validate(range(max = 100))]
+    pub commission_percent: Option<u32>,
+    #[validate]
+    pub prizes: Option<Vec<RankPrize>>,
+    pub tie_rule: Option<TieRule>,
+    #[validate(range(min = 1))]
+    pub min_players: Option<u32>,
+    #[validate(range(min = 1, max = "CONTEST_MAX_PLAYERS"))]
+    pub max_players: Option<u32>,
+    #[validate(range(min = 1, max = "CONTEST_MAX_ENTRIES"))]
+    pub max_entries: Option<u32>,
+    pub waitlist: Option<bool>,
+    pub start_ts: Option<u64>,
+    pub end_ts: Option<u64>,
+    #[validate]
+    pub media: Option<ContestMedia>,
+    #[validate(length(min = 1, max = "CONTEST_MAX_QUESTIONS"))]
+    #[validate]
+    pub questions: Option<Vec<Question>>,
+    pub scoring: Option<ScoringRule>,
+}
+impl_validate_extra!(UpdateContestReq);
+
+impl UpdateContestReq {
+    /// Check if the entry fee or the prize table is being updated
+    pub fn updates_prize_table(&self) -> bool {
+        self.entry_fee.is_some()
+            || self.bonus_usage_percent.is_some()
+            || self.prize_pool.is_some()
+            || self.prize_pool_type.is_some()
+            || self.commission_percent.is_some()
+            || self.prizes.is_some()
+            || self.tie_rule.is_some()
+    }
+}
+
+/// request schema for Create Contest Template request
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateContestTemplateReq {
+    /// start time is appended to the title of each contest
+    #[validate(length(min = 3, max = 80))]
+    pub title: String,
+    #[validate(length(min = 1))]
+    pub category: String,
+    #[validate(url)]
+    pub banner: String,
+    pub entry_fee: u64,
+    #[validate(range(max = 100))]
+    pub bonus_usage_percent: u32,
+    /// fixed or the guaranteed minimum pool as per `prizePoolType`
+    pub prize_pool: u64,
+    pub prize_pool_type: Option<PrizePoolType>,
+    /// rake on the entry fees for the dynamic and guaranteed pools
+    #[validate(range(max = 100))]
+    pub commission_percent: Option<u32>,
+    #[validate]
+    pub prizes: Vec<RankPrize>,
+    pub tie_rule: Option<TieRule>,
+    #[validate(range(min = 1))]
+    pub min_players: u32,
+    #[validate(range(min = 1, max = "CONTEST_MAX_PLAYERS"))]
+    pub max_players: u32,
+    #[validate(range(min = 1, max = "CONTEST_MAX_ENTRIES"))]
+    pub max_entries: Option<u32>,
+    pub waitlist: Option<bool>,
+    #[validate(range(min = 60))]
+    pub duration_secs: u64,
+    #[validate]
+    pub media: ContestMedia,
+    #[validate(length(min = 1, max = "CONTEST_MAX_QUESTIONS"))]
+    #[validate]
+    pub questions: Vec<Question>,
+    pub scoring: Option<ScoringRule>,
+    /// practice contests must have zero entry fee
+    pub mode: Option<ContestMode>,
+    #[validate]
+    pub recurrence: Recurrence,
+}
+
+/// request schema for Update Contest Template request
+#[derive(Debug, Default, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateContestTemplateReq {
+    #[validate(length(min = 3, max = 80))]
+    pub title: Option<String>,
+    #[validate(length(min = 1))]
+    pub category: Option<String>,
+    #[validate(url)]
+    pub banner: Option<String>,
+    pub entry_fee: Option<u64>,
+    #[validate(range(max = 100))]
+    pub bonus_usage_percent: Option<u32>,
+    pub prize_pool: Option<u64>,
+    pub prize_pool_type: Option<PrizePoolType>,
+    #[validate(range(max = 100))]
+    pub commission_percent: Option<u32>,
+    #[validate]
+    pub prizes: Option<Vec<RankPrize>>,
+    pub tie_rule: Option<TieRule>,
+    #[validate(range(min = 1))]
+    pub min_players: Option<u32>,
+    #[validate(range(min = 1, max = "CONTEST_MAX_PLAYERS"))]
+    pub max_players: Option<u32>,
+    #[validate(range(min = 1, max = "CONTEST_MAX_ENTRIES"))]
+    pub max_entries: Option<u32>,
+    pub waitlist: Option<bool>,
+    #[validate(range(min = 60))]
+    pub duration_secs: Option<u64>,
+    #[validate]
+    pub media: Option<ContestMedia>,
+    #[validate(length(min = 1, max = "CONTEST_MAX_QUESTIONS"))]
+    #[validate]
+    pub questions: Option<Vec<Question>>,
+    pub scoring: Option<ScoringRule>,
+    #[validate]
+    pub recurrence: Option<Recurrence>,
+
+    /// no contest is created from a paused template
+    pub paused: Option<bool>,
+}
+impl_validate_extra!(UpdateContestTemplateReq);
+
+/// request schema for Create Question Set request
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateQuestionSetReq {
+    #[validate(length(min = 3, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1))]
+    pub category: String,
+    #[validate]
+    pub media: ContestMedia,
+    #[validate(length(min = 1, max = "CONTEST_MAX_QUESTIONS"))]
+    #[validate]
+    pub questions: Vec<Question>,
+}
+
+/// request schema for Update Question Set request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateQuestionSetReq {
+    /// approved sets can be used by the users to create private contests
+    pub approved: bool,
+}
+impl_validate_extra!(UpdateQuestionSetReq);
+
+/// request schema for Create Private Contest request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePrivateContestReq {
+    #[validate(length(min = 3, max = 50))]
+    pub title: String,
+    #[schema(value_type = String)]
+    pub question_set_id: ObjectId,
+    #[validate(range(max = "PRIVATE_CONTEST_MAX_ENTRY_FEE"))]
+    pub entry_fee: u64,
+    #[validate(range(min = 2, max = "PRIVATE_CONTEST_MAX_PLAYERS"))]
+    pub max_players: u32,
+    pub start_ts: u64,
+    #[validate(range(min = 60, max = "PRIVATE_CONTEST_MAX_DURATION_SECS"))]
+    pub duration_secs: u64,
+}
+impl_validate_extra!(CreatePrivateContestReq);
+
+/// query params for admin Contest list request
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestListParams {
+    pub status: Option<ContestStatus>,
+}
+impl_validate_extra!(ContestListParams);
+
+/// query params for public Contest list request
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicContestListParams {
+    pub category: Option<String>,
+    pub status: Option<ContestPhase>,
+    pub mode: Option<ContestMode>,
+    pub min_entry_fee: Option<u64>,
+    pub max_entry_fee: Option<u64>,
+    /// only the contests joined by the user if true, the ones not joined if false
+    pub joined: Option<bool>,
+    pub sort: Option<ContestSort>,
+    pub order: Option<SortOrder>,
+    /// `nextCursor` of the previous page
+    pub cursor: Option<String>,
+    #[validate(range(min = 1, max = "DEFAULT_QUERY_LIMIT"))]
+    pub limit: Option<u64>,
+}
+impl_validate_extra!(PublicContestListParams);
+
+/// query params for contest Prize Preview request
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct PrizePreviewParams {
+    /// number of players, maxPlayers of the contest by default
+    #[validate(range(min = 1, max = "CONTEST_MAX_PLAYERS"))]
+    pub participants: Option<u32>,
+}
+impl_validate_extra!(PrizePreviewParams);
+
+/// query params for contest Leaderboard request
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardParams {
+    /// page number starting from 1
+    #[validate(range(min = 1))]
+    pub page: Option<u64>,
+    #[validate(range(min = 1, max = "LEADERBOARD_MAX_PAGE_SIZE"))]
+    pub limit: Option<u64>,
+}
+impl_validate_extra!(LeaderboardParams);
+
+/// query params for the contest Play and Leave requests
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayEntryParams {
+    /// entry of the user in the contest, 1 by default
+    #[validate(range(min = 1, max = "CONTEST_MAX_ENTRIES"))]
+    pub entry_no: Option<u32>,
+}
+impl_validate_extra!(PlayEntryParams);
+
+impl PlayEntryParams {
+    pub fn entry_no(&self) -> u32 {
+        self.entry_no.unwrap_or(1)
+    }
+}
+
+impl LeaderboardParams {
+    /// Returns the number of entries to skip and the page size
+    pub fn skip_limit(&self) -> (u64, u64) {
+        let limit = self.limit.unwrap_or(LEADERBOARD_DEFAULT_PAGE_SIZE);
+        let skip = (self.page.unwrap_or(1) - 1) * limit;
+        (skip, limit)
+    }
+}
+
+/// request schema for Submit Answer request
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitAnswerReq {
+    /// index of the question in the contest
+    pub question_no: u32,
+    /// index of the selected option
+    pub answer: u32,
+}
+impl_validate_extra!(SubmitAnswerReq);
@@ -1,7 +1,14 @@
 use axum::Json;
+use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use super::{
+    BonusExpiry, Contest, ContestSummary, ContestTemplate, GamingLimits, LeaderboardEntry, Money,
+    PlayTracker, PrizePayout, PrizePoolType, QuestionSet, QuestionSetView, QuestionView,
+    ReferralRedemption, SpecialReferralCode, WalletFreezeAudit,
+};
+
 /// Response schema for generic response
 /// can be used for both success and error response
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -31,3 +38,216 @@ pub struct AddBalInitRes {
     pub transaction_id: String,
     pub app_upi_id: String,
 }
+
+/// response schema for Wallet Balance
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletBalanceRes {
+    pub success: bool,
+    pub balance: Money,
+    pub upcoming_bonus_expiry: Vec<BonusExpiry>,
+}
+
+/// response schema for Get Referral Code
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralCodeRes {
+    pub success: bool,
+    pub referral_code: String,
+}
+
+/// response schema for Special Referral Code list
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecialReferralCodesRes {
+    pub success: bool,
+    pub data: Vec<SpecialReferralCode>,
+}
+
+/// response schema for Special Referral Code redemption report
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecialReferralReportRes {
+    pub success: bool,
+    pub referral_code: SpecialReferralCode,
+    pub total_bonus: u64,
+    pub redemptions: Vec<ReferralRedemption>,
+}
+
+/// response schema for Gaming Limits
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GamingLimitsRes {
+    pub success: bool,
+    pub gaming_limits: GamingLimits,
+}
+
+/// response schema for Withdraw Balance
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawRes {
+    pub success: bool,
+    pub amount: u64,
+    pub tds: u64,
+    pub payout: u64,
+    pub balance: Money,
+}
+
+/// response schema for Wallet Freeze Audits
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletFreezeAuditsRes {
+    pub success: bool,
+    pub audits: Vec<WalletFreezeAudit>,
+}
+
+/// response schema for Contest
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestRes {
+    pub success: bool,
+    pub contest: Contest,
+}
+
+/// response schema for Contest list
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestsRes {
+    pub success: bool,
+    pub contests: Vec<Contest>,
+}
+
+/// response schema for public Contest list
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestListRes {
+    pub success: bool,
+    pub contests: Vec<ContestSummary>,
+
+    /// cursor for the next page, missing on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// response schema for Contest Template
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestTemplateRes {
+    pub success: bool,
+    pub template: ContestTemplate,
+}
+
+/// response schema for Contest Template list
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestTemplatesRes {
+    pub success: bool,
+    pub templates: Vec<ContestTemplate>,
+}
+
+/// response schema for Question Set
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionSetRes {
+    pub success: bool,
+    pub question_set: QuestionSet,
+}
+
+/// response schema for Question Set list
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionSetsRes {
+    pub success: bool,
+    pub question_sets: Vec<QuestionSetView>,
+}
+
+/// response schema for Create Private Contest
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivateContestRes {
+    pub success: bool,
+    #[schema(value_type = String)]
+    pub contest_id: ObjectId,
+    pub title: String,
+    pub invite_code: String,
+    pub entry_fee: u64,
+    pub max_players: u32,
+    pub commission_percent: u32,
+    pub start_ts: u64,
+    pub end_ts: u64,
+}
+
+/// response schema for Join Contest
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinContestRes {
+    pub success: bool,
+    /// entry of the user in the contest, to be passed to the play requests
+    pub entry_no: u32,
+    pub entry_fee: Money,
+    pub balance: Money,
+}
+
+/// response schema for Join Waitlist
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistRes {
+    pub success: bool,
+    /// position of the user in the waitlist starting from 1
+    pub position: u64,
+}
+
+/// response schema for Play Tracker
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayTrackerRes {
+    pub success: bool,
+    pub play_tracker: PlayTracker,
+}
+
+/// response schema for Question
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionRes {
+    pub success: bool,
+    pub question: QuestionView,
+}
+
+/// response schema for contest Leaderboard
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardRes {
+    pub success: bool,
+    pub total: u64,
+    pub entries: Vec<LeaderboardEntry>,
+
+    /// rank of the requesting user, missing if the user has not played
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub own_rank: Option<LeaderboardEntry>,
+    pub prize_pool_type: PrizePoolType,
+
+    /// prize pool for the players joined till now
+    pub current_prize_pool: u64,
+}
+
+/// response schema for Contest detail
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestDetailRes {
+    pub success: bool,
+    pub contest: ContestSummary,
+
+    /// payout table for the players joined till now
+    pub prizes: Vec<PrizePayout>,
+}
+
+/// response schema for contest Prize Preview
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PrizePreviewRes {
+    pub success: bool,
+    pub participants: u32,
+    pub prize_pool: u64,
+    pub total_payout: u64,
+    pub payouts: Vec<PrizePayout>,
+}
@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::{format_ts_ist, render_text_pdf};
+
+use super::{Money, WalletTransaction};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Csv,
+    Pdf,
+}
+
+impl StatementFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Pdf => "application/pdf",
+        }
+    }
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+/// Wallet statement of an user for a period.
+/// Opening and closing balances are taken from the completed transactions.
+#[derive(Debug)]
+pub struct WalletStatement {
+    pub user_id: u32,
+    pub from_ts: u64,
+    pub to_ts: u64,
+    pub opening_balance: Money,
+    pub closing_balance: Money,
+    pub transactions: Vec<WalletTransaction>,
+}
+
+const CSV_HEADER: &str = "userId,date,transactionType,amountReal,amountBonus,balanceBeforeReal,balanceBeforeBonus,balanceAfterReal,balanceAfterBonus,remarks";
+
+impl WalletStatement {
+    /// Build the statement from the completed transactions of the period sorted by time.
+    /// `last_balance` is the balance after the last transaction before the period,
+    /// used as the opening balance when there is no transaction in the period.
+    pub fn new(
+        user_id: u32,
+        from_ts: u64,
+        to_ts: u64,
+        last_balance: Money,
+        transactions: Vec<WalletTransaction>,
+    ) -> Self {
+        let opening_balance = transactions
+            .first()
+            .map(|t| t.balance_before())
+            .unwrap_or(last_balance);
+        let closing_balance = transactions
+            .last()
+            .and_then(|t| t.balance_after())
+            .unwrap_or(opening_balance);
+        Self {
+            user_id,
+            from_ts,
+            to_ts,
+            opening_balance,
+            closing_balance,
+            transactions,
+        }
+    }
+
+    /// Group the transactions of multiple users sorted by user and time into statements
+    pub fn from_transactions(
+        from_ts: u64,
+        to_ts: u64,
+        transactions: Vec<WalletTransaction>,
+    ) -> Vec<Self> {
+        let mut grouped: Vec<(u32, Vec<WalletTransaction>)> = vec![];
+        for transaction in transactions {
+            match grouped.last_mut() {
+                Some((user_id, list)) if *user_id == transaction.user_id() => {
+                    list.push(transaction)
+                }
+                _ => grouped.push((transaction.user_id(), vec![transaction])),
+            }
+        }
+        grouped
+            .into_iter()
+            .map(|(user_id, list)| Self::new(user_id, from_ts, to_ts, Money::default(), list))
+            .collect()
+    }
+
+    /// CSV rows of the statement, opening and closing balances are added as separate rows
+    pub fn csv_rows(&self) -> Vec<String> {
+        let mut rows =
+            vec![self.balance_row("OPENING_BALANCE", self.from_ts, self.opening_balance)];
+        for t in self.transactions.iter() {
+            let amount = t.amount();
+            let before = t.balance_before();
+            let after = t.balance_after().unwrap_or(before);
+            let date = t.created_ts().map(format_ts_ist).unwrap_or_default();
+            rows.push(format!(
+                "{},{},{},{},{},{},{},{},{},{}",
+                self.user_id,
+                date,
+                t.transaction_type().as_str(),
+                amount.real(),
+                amount.bonus(),
+                before.real(),
+                before.bonus(),
+                after.real(),
+                after.bonus(),
+                escape_csv(t.remarks().unwrap_or_default())
+            ));
+        }
+        rows.push(self.balance_row("CLOSING_BALANCE", self.to_ts, self.closing_balance));
+        rows
+    }
+
+    /// Lines of text to be rendered in the PDF statement
+    pub fn pdf_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            "Trailsbuddy Wallet Statement".to_owned(),
+            format!("User Id: {}", self.user_id),
+            format!(
+                "Period: {} to {}",
+                format_ts_ist(self.from_ts),
+                format_ts_ist(self.to_ts)
+            ),
+            format!(
+                "Opening Balance: real {}, bonus {}",
+                self.opening_balance.real(),
+                self.opening_balance.bonus()
+            ),
+            String::new(),
+            format!(
+                "{:<17}{:<26}{:>10}{:>10}{:>14}{:>14}  {}",
+                "Date", "Type", "Real", "Bonus", "Bal. Real", "Bal. Bonus", "Remarks"
+            ),
+        ];
+        for t in self.transactions.iter() {
+            let amount = t.amount();
+            let after = t.balance_after().unwrap_or(t.balance_before());
+            let date = t.created_ts().map(format_ts_ist).unwrap_or_default();
+            lines.push(format!(
+                "{:<17}{:<26}{:>10}{:>10}{:>14}{:>14}  {}",
+                date,
+                t.transaction_type().as_str(),
+                amount.real(),
+                amount.bonus(),
+                after.real(),
+                after.bonus(),
+                t.remarks().unwrap_or_default()
+            ));
+        }
+        lines.push(String::new());
+        lines.push(format!(
+            "Closing Balance: real {}, bonus {}",
+            self.closing_balance.real(),
+            self.closing_balance.bonus()
+        ));
+        lines
+    }
+
+    /// Render the statements of one or more users in the given format
+    pub fn render(statements: &[Self], format: StatementFormat) -> Vec<u8> {
+        match format {
+            StatementFormat::Csv => {
+                let mut csv = vec![CSV_HEADER.to_owned()];
+                for statement in statements {
+                    csv.extend(statement.csv_rows());
+                }
+                csv.join("\n").into_bytes()
+            }
+            StatementFormat::Pdf => {
+                let mut lines = vec![];
+                for statement in statements {
+                    if !lines.is_empty() {
+                        lines.push("-".repeat(120));
+                    }
+                    lines.extend(statement.pdf_lines());
+                }
+                render_text_pdf(&lines)
+            }
+        }
+    }
+
+    fn balance_row(&self, label: &str, ts: u64, balance: Money) -> String {
+        format!(
+            "{},{},{},,,,,{},{},",
+            self.user_id,
+            format_ts_ist(ts),
+            label,
+            balance.real(),
+            balance.bonus()
+        )
+    }
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn transactions(user_id: u32) -> Vec<WalletTransaction> {
+        vec![
+            WalletTransaction::pay_for_contest_trans(
+                user_id,
+                "contest1",
+                10,
+                5,
+                Money::new(100, 50),
+                Money::new(90, 45),
+            ),
+            WalletTransaction::referral_bonus_trans(
+                user_id,
+                20,
+                Money::new(90, 45),
+                Money::new(90, 65),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_wallet_statement_balances() {
+        let statement = WalletStatement::new(1, 10, 20, Money::new(1, 1), transactions(1));
+        assert_eq!(statement.opening_balance, Money::new(100, 50));
+        assert_eq!(statement.closing_balance, Money::new(90, 65));
+
+        let statement = WalletStatement::new(1, 10, 20, Money::new(7, 3), vec![]);
+        assert_eq!(statement.opening_balance, Money::new(7, 3));
+        assert_eq!(statement.closing_balance, Money::new(7, 3));
+    }
+
+    #[test]
+    fn test_wallet_statement_csv() {
+        let statement = WalletStatement::new(1, 0, 20, Money::default(), transactions(1));
+        let csv = WalletStatement::render(&[statement], StatementFormat::Csv);
+        let csv = String::from_utf8(csv).unwrap();
+        let rows = csv.lines().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0], CSV_HEADER);
+        assert_eq!(rows[1], "1,01-01-1970 05:30,OPENING_BALANCE,,,,,100,50,");
+        assert!(rows[2].starts_with("1,"));
+        assert!(rows[2].contains(",PAY_FOR_CONTEST,10,5,100,50,90,45,Pay for contest: contest1"));
+        assert!(rows[4].contains(",CLOSING_BALANCE,,,,,90,65,"));
+        assert_eq!(escape_csv("a,b \"c\""), "\"a,b \"\"c\"\"\"");
+    }
+
+    #[test]
+    fn test_wallet_statement_from_transactions() {
+        let mut all = transactions(1);
+        all.extend(transactions(2));
+        let statements = WalletStatement::from_transactions(0, 20, all);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].user_id, 1);
+        assert_eq!(statements[1].user_id, 2);
+        assert_eq!(statements[1].transactions.len(), 2);
+        let pdf = WalletStatement::render(&statements, StatementFormat::Pdf);
+        let pdf = String::from_utf8(pdf).unwrap();
+        assert!(pdf.contains("(User Id: 1) Tj"));
+        assert!(pdf.contains("(User Id: 2) Tj"));
+    }
+}
@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    constants::*,
+    utils::{format_ts_ist, render_text_pdf},
+};
+
+use super::{StatementFormat, WalletTransaction};
+
+/// Rate and threshold of tax deducted at source on net winnings.
+/// Configurable with `TDS_RATE_PERCENT` and `TDS_THRESHOLD` env variables.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TdsConfig {
+    pub rate_percent: u64,
+    /// TDS is deducted only when the untaxed net winnings exceed the threshold
+    pub threshold: u64,
+}
+
+impl TdsConfig {
+    pub fn from_env() -> Self {
+        let rate_percent = std::env::var("TDS_RATE_PERCENT").unwrap_or_default();
+        let rate_percent = rate_percent
+            .parse::<u64>()
+            .unwrap_or(TDS_DEFAULT_RATE_PERCENT)
+            .min(100);
+        let threshold = std::env::var("TDS_THRESHOLD").unwrap_or_default();
+        let threshold = threshold.parse::<u64>().unwrap_or(TDS_DEFAULT_THRESHOLD);
+        Self {
+            rate_percent,
+            threshold,
+        }
+    }
+
+    /// Compute the TDS to be deducted from a withdrawal of the given amount.
+    /// Net winnings already taxed in previous withdrawals are not taxed again and
+    /// the taxable amount is capped by the withdrawal amount, the rest is taxed
+    /// in the upcoming withdrawals.
+    pub fn compute(&self, financial_year: i32, summary: &TaxSummary, amount: u64) -> TdsDeduction {
+        let untaxed = summary.untaxed_winnings();
+        let taxable = if untaxed > self.threshold {
+            untaxed.min(amount)
+        } else {
+            0
+        };
+        TdsDeduction {
+            financial_year,
+            rate_percent: self.rate_percent,
+            taxable,
+            tds: taxable * self.rate_percent / 100,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TdsDeduction {
+    pub financial_year: i32,
+    pub rate_percent: u64,
+    pub taxable: u64,
+    pub tds: u64,
+}
+
+/// Real amounts of the completed transactions of an user in a financial year
+/// which are relevant for tax computation
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxSummary {
+    pub total_winnings: u64,
+    pub total_entry_fees: u64,
+    pub total_refunds: u64,
+    /// Net winnings on which TDS is already deducted
+    pub taxed_winnings: u64,
+    pub total_tds: u64,
+}
+
+impl TaxSummary {
+    /// Contest winnings minus the entry fees paid, refunded entry fees are not considered paid
+    pub fn net_winnings(&self) -> u64 {
+        let entry_fees = self.total_entry_fees.saturating_sub(self.total_refunds);
+        self.total_winnings.saturating_sub(entry_fees)
+    }
+
+    pub fn untaxed_winnings(&self) -> u64 {
+        self.net_winnings().saturating_sub(self.taxed_winnings)
+    }
+}
+
+/// Financial year is identified by it's starting year, e.g. 2023 is labelled as `2023-24`
+pub fn financial_year_label(financial_year: i32) -> String {
+    format!(
+        "{}-{:02}",
+        financial_year,
+        (financial_year + 1).rem_euclid(100)
+    )
+}
+
+/// Annual tax certificate of an user listing the TDS deducted in a financial year
+#[derive(Debug)]
+pub struct TaxCertificate {
+    pub user_id: u32,
+    pub financial_year: i32,
+    pub summary: TaxSummary,
+    pub deductions: Vec<WalletTransaction>,
+}
+
+const SUMMARY_CSV_HEADER: &str = "userId,financialYear,totalWinnings,totalEntryFees,totalRefunds,netWinnings,taxedWinnings,totalTds";
+const DEDUCTION_CSV_HEADER: &str = "date,taxableAmount,tdsAmount";
+
+impl TaxCertificate {
+    /// CSV with the summary followed by the individual deductions
+    pub fn csv_rows(&self) -> Vec<String> {
+        let s = &self.summary;
+        let mut rows = vec![
+            SUMMARY_CSV_HEADER.to_owned(),
+            format!(
+                "{},{},{},{},{},{},{},{}",
+                self.user_id,
+                financial_year_label(self.financial_year),
+                s.total_winnings,
+                s.total_entry_fees,
+                s.total_refunds,
+                s.net_winnings(),
+                s.taxed_winnings,
+                s.total_tds
+            ),
+            String::new(),
+            DEDUCTION_CSV_HEADER.to_owned(),
+        ];
+        for t in self.deductions.iter() {
+            let date = t.created_ts().map(format_ts_ist).unwrap_or_default();
+            rows.push(format!(
+                "{},{},{}",
+                date,
+                t.taxable_amount().unwrap_or_default(),
+                t.amount().real()
+            ));
+        }
+        rows
+    }
+
+    /// Lines of text to be rendered in the PDF certificate
+    pub fn pdf_lines(&self) -> Vec<String> {
+        let s = &self.summary;
+        let mut lines = vec![
+            "Trailsbuddy Tax Deduction Certificate".to_owned(),
+            format!("User Id: {}", self.user_id),
+            format!(
+                "Financial Year: {}",
+                financial_year_label(self.financial_year)
+            ),
+            String::new(),
+            format!("Total Winnings: {}", s.total_winnings),
+            format!("Total Entry Fees: {}", s.total_entry_fees),
+            format!("Total Refunds: {}", s.total_refunds),
+            format!("Net Winnings: {}", s.net_winnings()),
+            format!("Net Winnings Taxed: {}", s.taxed_winnings),
+            format!("Total TDS Deducted: {}", s.total_tds),
+            String::new(),
+            format!("{:<17}{:>16}{:>12}", "Date", "Taxable Amount", "TDS"),
+        ];
+        for t in self.deductions.iter() {
+            let date = t.created_ts().map(format_ts_ist).unwrap_or_default();
+            lines.push(format!(
+                "{:<17}{:>16}{:>12}",
+                date,
+                t.taxable_amount().unwrap_or_default(),
+                t.amount().real()
+            ));
+        }
+        lines
+    }
+
+    pub fn render(&self, format: StatementFormat) -> Vec<u8> {
+        match format {
+            StatementFormat::Csv => self.csv_rows().join("\n").into_bytes(),
+            StatementFormat::Pdf => render_text_pdf(&self.pdf_lines()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::models::Money;
+
+    use super::*;
+
+    #[test]
+    fn test_tax_summary() {
+        let summary = TaxSummary {
+            total_winnings: 1000,
+            total_entry_fees: 300,
+            total_refunds: 100,
+            taxed_winnings: 500,
+            total_tds: 150,
+        };
+        assert_eq!(summary.net_winnings(), 800);
+        assert_eq!(summary.untaxed_winnings(), 300);
+        let summary = TaxSummary {
+            total_winnings: 100,
+            total_entry_fees: 300,
+            ..Default::default()
+        };
+        assert_eq!(summary.net_winnings(), 0);
+    }
+
+    #[test]
+    fn test_tds_config_compute() {
+        let config = TdsConfig {
+            rate_percent: 30,
+            threshold: 100,
+        };
+        let summary = TaxSummary {
+            total_winnings: 1000,
+            total_entry_fees: 200,
+            ..Default::default()
+        };
+        // taxable amount is capped by the withdrawal amount
+        let deduction = config.compute(2023, &summary, 500);
+        assert_eq!(deduction.taxable, 500);
+        assert_eq!(deduction.tds, 150);
+        let deduction = config.compute(2023, &summary, 1000);
+        assert_eq!(deduction.taxable, 800);
+        assert_eq!(deduction.tds, 240);
+        // untaxed net winnings within threshold
+        let summary = TaxSummary {
+            total_winnings: 1000,
+            taxed_winnings: 950,
+            ..Default::default()
+        };
+        let deduction = config.compute(2023, &summary, 1000);
+        assert_eq!(deduction.taxable, 0);
+        assert_eq!(deduction.tds, 0);
+    }
+
+    #[test]
+    fn test_financial_year_label() {
+        assert_eq!(financial_year_label(2023), "2023-24");
+        assert_eq!(financial_year_label(2099), "2099-00");
+    }
+
+    #[test]
+    fn test_tax_certificate_render() {
+        let deduction = TdsDeduction {
+            financial_year: 2023,
+            rate_percent: 30,
+            taxable: 500,
+            tds: 150,
+        };
+        let transaction = WalletTransaction::tds_deduction_trans(
+            10,
+            &deduction,
+            Money::new(1000, 0),
+            Money::new(850, 0),
+        );
+        let certificate = TaxCertificate {
+            user_id: 10,
+            financial_year: 2023,
+            summary: TaxSummary {
+                total_winnings: 1000,
+                taxed_winnings: 500,
+                total_tds: 150,
+                ..Default::default()
+            },
+            deductions: vec![transaction],
+        };
+        let rows = certificate.csv_rows();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[1], "10,2023-24,1000,0,0,1000,500,150");
+        assert!(rows[4].ends_with(",500,150"));
+        let pdf = certificate.render(StatementFormat::Pdf);
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+    }
+}
@@ -24,7 +24,7 @@ pub struct LoginTokenData {
     pub profile_pic: Option<String>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LoginScheme {
     #[default]
@@ -67,6 +67,9 @@ pub struct User {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub used_referral_code: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_referral_code_ts: Option<u64>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub referral_code: Option<String>,
 
@@ -0,0 +1,51 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::get_epoch_ts;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum WaitlistStatus {
+    #[default]
+    Waiting,
+    /// picked up for joining when a spot is freed
+    Promoting,
+    Promoted,
+    /// joining failed for the user, e.g. insufficient balance
+    Failed,
+}
+
+/// User waiting for a spot in a full contest, promoted in the order of joining the waitlist
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub id: Option<ObjectId>,
+    #[schema(value_type = String)]
+    pub contest_id: ObjectId,
+    pub user_id: u32,
+    pub status: WaitlistStatus,
+
+    /// reason of the failed promotion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_ts: Option<u64>,
+}
+
+impl WaitlistEntry {
+    pub fn new(contest_id: ObjectId, user_id: u32) -> Self {
+        Self {
+            contest_id,
+            user_id,
+            created_ts: Some(get_epoch_ts()),
+            ..Default::default()
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
-use mongodb::bson::Bson;
+use mongodb::bson::{oid::ObjectId, Bson};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use crate::{constants::*, utils::get_epoch_ts};
+use crate::{
+    constants::*,
+    utils::{format_ts_ist, get_epoch_ts},
+};
+
+use super::{financial_year_label, TdsDeduction};
 
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, ToSchema)]
 pub struct Money {
@@ -83,6 +88,11 @@ impl std::cmp::PartialEq for Money {
 pub struct Wallet {
     pub user_id: u32,
     pub balance: Money,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freeze: Option<WalletFreeze>,
+    /// incremented on every update of the balance
+    #[serde(default)]
+    pub version: u64,
     pub created_ts: Option<u64>,
     pub updated_ts: Option<u64>,
     pub created_by: Option<u32>,
@@ -92,9 +102,106 @@ impl Wallet {
     pub fn balance(&self) -> Money {
         self.balance
     }
+
+    /// Returns the error message if the operation is blocked by an active freeze
+    pub fn freeze_error(&self, operation: WalletOperation, ts: u64) -> Option<String> {
+        let freeze = self.freeze.as_ref()?;
+        if !freeze.blocks(operation, ts) {
+            return None;
+        }
+        let msg = format!(
+            "Wallet is frozen for {} till {}",
+            operation,
+            format_ts_ist(freeze.expires_ts)
+        );
+        Some(msg)
+    }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// Wallet operations which can be blocked by a freeze
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WalletOperation {
+    Credit,
+    Debit,
+    Withdraw,
+}
+
+impl Display for WalletOperation {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Credit => write!(f, "credits"),
+            Self::Debit => write!(f, "debits"),
+            Self::Withdraw => write!(f, "withdrawals"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WalletFreezeScope {
+    /// all the debits including withdrawals are blocked
+    #[default]
+    Full,
+    /// only withdrawals are blocked
+    Withdrawals,
+}
+
+/// Freeze applied by an admin on the wallet of an user, e.g. during fraud investigation
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletFreeze {
+    pub scope: WalletFreezeScope,
+    pub reason: String,
+    /// credits are blocked as well irrespective of the scope
+    #[serde(default)]
+    pub block_credits: bool,
+    pub expires_ts: u64,
+    pub frozen_by: u32,
+    pub frozen_ts: u64,
+}
+
+impl WalletFreeze {
+    pub fn is_active(&self, ts: u64) -> bool {
+        ts < self.expires_ts
+    }
+
+    pub fn blocks(&self, operation: WalletOperation, ts: u64) -> bool {
+        if !self.is_active(ts) {
+            return false;
+        }
+        match operation {
+            WalletOperation::Credit => self.block_credits,
+            WalletOperation::Debit => self.scope == WalletFreezeScope::Full,
+            WalletOperation::Withdraw => true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WalletFreezeAction {
+    #[default]
+    Freeze,
+    Unfreeze,
+}
+
+/// Audit record of a wallet freeze or unfreeze
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletFreezeAudit {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub id: Option<ObjectId>,
+    pub user_id: u32,
+    pub action: WalletFreezeAction,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freeze: Option<WalletFreeze>,
+    pub created_by: u32,
+    pub created_ts: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum WalltetTransactionType {
     #[default]
@@ -106,6 +213,8 @@ pub enum WalltetTransactionType {
     ReferralBonus,
     ReferrerBonus,
     RefundContestEntryFee,
+    BonusExpired,
+    TdsDeduction,
 }
 
 impl WalltetTransactionType {
@@ -113,6 +222,20 @@ impl WalltetTransactionType {
         let bson = mongodb::bson::to_bson(self)?;
         Ok(bson)
     }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AddBalance => "ADD_BALANCE",
+            Self::Withdraw => "WITHDRAW",
+            Self::PayForContest => "PAY_FOR_CONTEST",
+            Self::ContestWin => "CONTEST_WIN",
+            Self::SignupBonus => "SIGNUP_BONUS",
+            Self::ReferralBonus => "REFERRAL_BONUS",
+            Self::ReferrerBonus => "REFERRER_BONUS",
+            Self::RefundContestEntryFee => "REFUND_CONTEST_ENTRY_FEE",
+            Self::BonusExpired => "BONUS_EXPIRED",
+            Self::TdsDeduction => "TDS_DEDUCTION",
+        }
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -144,6 +267,8 @@ pub struct WalletTransaction {
     remarks: Option<String>,
     receiver_upi_id: Option<String>,
     error_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    taxable_amount: Option<u64>,
     created_ts: Option<u64>,
     updated_ts: Option<u64>,
     created_by: Option<u32>,
@@ -181,6 +306,54 @@ impl WalletTransaction {
         transaction
     }
 
+    pub fn withdraw_bal_trans(
+        user_id: u32,
+        amount: u64,
+        balance_before: Money,
+        balance_after: Money,
+        receiver_upi_id: &str,
+    ) -> Self {
+        Self {
+            user_id,
+            transaction_type: WalltetTransactionType::Withdraw,
+            amount: Money::new(amount, 0),
+            status: WalletTransactionStatus::Completed,
+            balance_before,
+            balance_after: Some(balance_after),
+            receiver_upi_id: Some(receiver_upi_id.to_owned()),
+            created_ts: Some(get_epoch_ts()),
+            created_by: Some(user_id),
+            ..Default::default()
+        }
+    }
+
+    pub fn tds_deduction_trans(
+        user_id: u32,
+        deduction: &TdsDeduction,
+        balance_before: Money,
+        balance_after: Money,
+    ) -> Self {
+        let remarks = format!(
+            "TDS on net winnings: {}, rate: {}%, financial year: {}",
+            deduction.taxable,
+            deduction.rate_percent,
+            financial_year_label(deduction.financial_year)
+        );
+        Self {
+            user_id,
+            transaction_type: WalltetTransactionType::TdsDeduction,
+            amount: Money::new(deduction.tds, 0),
+            status: WalletTransactionStatus::Completed,
+            balance_before,
+            balance_after: Some(balance_after),
+            taxable_amount: Some(deduction.taxable),
+            remarks: Some(remarks),
+            created_ts: Some(get_epoch_ts()),
+            created_by: Some(user_id),
+            ..Default::default()
+        }
+    }
+
     pub fn pay_for_contest_trans(
         user_id: u32,
         contest_id: &str,
@@ -284,6 +457,46 @@ impl WalletTransaction {
         transaction
     }
 
+    pub fn bonus_expired_trans(
+        user_id: u32,
+        bonus: u64,
+        balance_before: Money,
+        balance_after: Money,
+        expires_ts: u64,
+    ) -> Self {
+        Self {
+            user_id,
+            transaction_type: WalltetTransactionType::BonusExpired,
+            amount: Money::new(0, bonus),
+            status: WalletTransactionStatus::Completed,
+            balance_before,
+            balance_after: Some(balance_after),
+            remarks: Some(format!("bonus expired: {}, expiry: {}", bonus, expires_ts)),
+            created_ts: Some(get_epoch_ts()),
+            ..Default::default()
+        }
+    }
+
+    pub fn user_id(&self) -> u32 {
+        self.user_id
+    }
+
+    pub fn transaction_type(&self) -> &WalltetTransactionType {
+        &self.transaction_type
+    }
+
+    pub fn remarks(&self) -> Option<&str> {
+        self.remarks.as_deref()
+    }
+
+    pub fn created_ts(&self) -> Option<u64> {
+        self.created_ts
+    }
+
+    pub fn taxable_amount(&self) -> Option<u64> {
+        self.taxable_amount
+    }
+
     pub fn amount(&self) -> Money {
         self.amount
     }
@@ -296,3 +509,87 @@ impl WalletTransaction {
         self.balance_after
     }
 }
+
+/// A lot of bonus credited to an user which expires after a certain time.
+/// Bonus is consumed from the earliest expiring lot first.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BonusLot {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: u32,
+    pub amount: u64,
+    pub remaining: u64,
+    pub expires_ts: u64,
+    pub created_ts: Option<u64>,
+    pub updated_ts: Option<u64>,
+}
+
+impl BonusLot {
+    pub fn new(user_id: u32, amount: u64) -> Self {
+        let ts = get_epoch_ts();
+        Self {
+            id: None,
+            user_id,
+            amount,
+            remaining: amount,
+            expires_ts: ts + BONUS_VALIDITY_SECS,
+            created_ts: Some(ts),
+            updated_ts: None,
+        }
+    }
+}
+
+/// Bonus amount which is going to expire at a given time
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BonusExpiry {
+    pub amount: u64,
+    pub expires_ts: u64,
+}
+
+impl From<BonusLot> for BonusExpiry {
+    fn from(lot: BonusLot) -> Self {
+        Self {
+            amount: lot.remaining,
+            expires_ts: lot.expires_ts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_wallet_freeze_blocks() {
+        let mut freeze = WalletFreeze {
+            scope: WalletFreezeScope::Withdrawals,
+            expires_ts: 100,
+            ..Default::default()
+        };
+        assert!(freeze.blocks(WalletOperation::Withdraw, 50));
+        assert!(!freeze.blocks(WalletOperation::Debit, 50));
+        assert!(!freeze.blocks(WalletOperation::Credit, 50));
+        freeze.scope = WalletFreezeScope::Full;
+        freeze.block_credits = true;
+        assert!(freeze.blocks(WalletOperation::Debit, 50));
+        assert!(freeze.blocks(WalletOperation::Credit, 50));
+        // expired freeze does not block anything
+        assert!(!freeze.blocks(WalletOperation::Withdraw, 100));
+    }
+
+    #[test]
+    fn test_wallet_freeze_error() {
+        let mut wallet = Wallet::default();
+        assert_eq!(wallet.freeze_error(WalletOperation::Debit, 50), None);
+        wallet.freeze = Some(WalletFreeze {
+            expires_ts: 100,
+            ..Default::default()
+        });
+        let err = wallet.freeze_error(WalletOperation::Debit, 50).unwrap();
+        assert!(err.starts_with("Wallet is frozen for debits till"));
+        assert_eq!(wallet.freeze_error(WalletOperation::Credit, 50), None);
+    }
+}
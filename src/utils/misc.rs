@@ -1,9 +1,13 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use chrono::{Datelike, FixedOffset, NaiveDate, TimeZone};
+
 use mongodb::{
-    bson::{doc, Document},
+    bson::{doc, Bson, Document},
+    error::{Error as MongoError, ErrorKind, WriteFailure},
     options::{FindOneAndUpdateOptions, ReturnDocument},
 };
+use rand::{distributions::Alphanumeric, Rng};
 
 use crate::{constants::*, import_double};
 
@@ -35,3 +39,169 @@ pub async fn get_seq_nxt_val(seq_id: &str, db: &DbClient) -> anyhow::Result<u32>
     }
     Ok(val as u32)
 }
+
+/// Format an epoch timestamp as date time in IST
+pub fn format_ts_ist(ts: u64) -> String {
+    let ist = FixedOffset::east_opt(IST_OFFSET_SECS).unwrap();
+    match ist.timestamp_opt(ts as i64, 0).single() {
+        Some(dt) => dt.format("%d-%m-%Y %H:%M").to_string(),
+        None => ts.to_string(),
+    }
+}
+
+/// Returns the start and end epoch timestamp of a month in IST.
+/// Month is expected in `YYYY-MM` format, end timestamp is exclusive.
+pub fn month_range_ts(month: &str) -> anyhow::Result<(u64, u64)> {
+    let err = || anyhow::anyhow!("Invalid month: {month}, expected format YYYY-MM");
+    let start = NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d").map_err(|_| err())?;
+    let end = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .ok_or_else(err)?;
+    let start_ts = ist_midnight_ts(start).ok_or_else(err)?;
+    let end_ts = ist_midnight_ts(end).ok_or_else(err)?;
+    Ok((start_ts, end_ts))
+}
+
+/// Returns the starting year of the financial year (April to March) of the timestamp in IST
+pub fn financial_year_of(ts: u64) -> i32 {
+    let ist = FixedOffset::east_opt(IST_OFFSET_SECS).unwrap();
+    match ist.timestamp_opt(ts as i64, 0).single() {
+        Some(dt) if dt.month() < FINANCIAL_YEAR_START_MONTH => dt.year() - 1,
+        Some(dt) => dt.year(),
+        None => 1970,
+    }
+}
+
+/// Returns the start and end epoch timestamp of a financial year in IST.
+/// Financial year is identified by it's starting year, end timestamp is exclusive.
+pub fn financial_year_range_ts(year: i32) -> anyhow::Result<(u64, u64)> {
+    let err = || anyhow::anyhow!("Invalid financial year: {year}");
+    let start = NaiveDate::from_ymd_opt(year, FINANCIAL_YEAR_START_MONTH, 1).ok_or_else(err)?;
+    let end = NaiveDate::from_ymd_opt(year + 1, FINANCIAL_YEAR_START_MONTH, 1).ok_or_else(err)?;
+    let start_ts = ist_midnight_ts(start).ok_or_else(err)?;
+    let end_ts = ist_midnight_ts(end).ok_or_else(err)?;
+    Ok((start_ts, end_ts))
+}
+
+fn ist_midnight_ts(date: NaiveDate) -> Option<u64> {
+    let ist = FixedOffset::east_opt(IST_OFFSET_SECS).unwrap();
+    let dt = date.and_hms_opt(0, 0, 0)?;
+    let ts = ist.from_local_datetime(&dt).single()?.timestamp();
+    u64::try_from(ts).ok()
+}
+
+/// Generate a random referral code of `REFERRAL_CODE_LEN` uppercase alphanumeric chars
+pub fn generate_referral_code() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(REFERRAL_CODE_LEN)
+        .map(|ch| (ch as char).to_ascii_uppercase())
+        .collect()
+}
+
+/// Returns the numeric value of the key as u64, aggregation results can have
+/// the same field as int32, int64 or double. Missing or negative value is returned as 0.
+pub fn get_doc_u64(doc: &Document, key: &str) -> u64 {
+    match doc.get(key) {
+        Some(Bson::Int32(val)) => (*val).max(0) as u64,
+        Some(Bson::Int64(val)) => (*val).max(0) as u64,
+        Some(Bson::Double(val)) => val.max(0.0) as u64,
+        _ => 0,
+    }
+}
+
+/// Returns the filter for the version of a document,
+/// documents created before versioning do not have the version field
+pub fn version_filter(version: u64) -> Bson {
+    if version == 0 {
+        Bson::Document(doc! {"$in": [0, null]})
+    } else {
+        Bson::Int64(version as i64)
+    }
+}
+
+/// Check if the error is raised due to an unique index violation
+pub fn is_duplicate_key_error(err: &MongoError) -> bool {
+    match err.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(e)) => e.code == DUPLICATE_KEY_ERROR_CODE,
+        ErrorKind::Command(e) => e.code == DUPLICATE_KEY_ERROR_CODE,
+        ErrorKind::BulkWrite(e) => e
+            .write_errors
+            .as_ref()
+            .map(|errors| errors.iter().any(|e| e.code == DUPLICATE_KEY_ERROR_CODE))
+            .unwrap_or_default(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_generate_referral_code() {
+        let code = generate_referral_code();
+        assert_eq!(code.len(), REFERRAL_CODE_LEN);
+        assert!(code
+            .chars()
+            .all(|ch| ch.is_ascii_digit() || ch.is_ascii_uppercase()));
+        let another_code = generate_referral_code();
+        assert_ne!(code, another_code);
+    }
+
+    #[test]
+    fn test_is_duplicate_key_error() {
+        let err = MongoError::custom("some error");
+        assert!(!is_duplicate_key_error(&err));
+    }
+
+    #[test]
+    fn test_format_ts_ist() {
+        assert_eq!(format_ts_ist(0), "01-01-1970 05:30");
+        assert_eq!(format_ts_ist(1696098600), "01-10-2023 00:00");
+    }
+
+    #[test]
+    fn test_month_range_ts() {
+        let (start, end) = month_range_ts("2023-10").unwrap();
+        assert_eq!(start, 1696098600);
+        assert_eq!(end, 1698777000);
+        let (start, end) = month_range_ts("2023-12").unwrap();
+        assert_eq!(format_ts_ist(start), "01-12-2023 00:00");
+        assert_eq!(format_ts_ist(end), "01-01-2024 00:00");
+        assert!(month_range_ts("2023-13").is_err());
+        assert!(month_range_ts("October").is_err());
+    }
+
+    #[test]
+    fn test_financial_year() {
+        assert_eq!(financial_year_of(1696098600), 2023);
+        let (start, end) = financial_year_range_ts(2023).unwrap();
+        assert_eq!(format_ts_ist(start), "01-04-2023 00:00");
+        assert_eq!(format_ts_ist(end), "01-04-2024 00:00");
+        assert_eq!(financial_year_of(start), 2023);
+        assert_eq!(financial_year_of(start - 1), 2022);
+        assert_eq!(financial_year_of(end - 1), 2023);
+    }
+
+    #[test]
+    fn test_get_doc_u64() {
+        let doc = doc! {"daily": 10, "weekly": 20_i64, "monthly": 30.0, "yearly": -1};
+        assert_eq!(get_doc_u64(&doc, "daily"), 10);
+        assert_eq!(get_doc_u64(&doc, "weekly"), 20);
+        assert_eq!(get_doc_u64(&doc, "monthly"), 30);
+        assert_eq!(get_doc_u64(&doc, "yearly"), 0);
+        assert_eq!(get_doc_u64(&doc, "total"), 0);
+    }
+
+    #[test]
+    fn test_version_filter() {
+        assert_eq!(version_filter(5), Bson::Int64(5));
+        let filter = doc! {"version": version_filter(0)};
+        assert_eq!(filter, doc! {"version": {"$in": [0, null]}});
+    }
+}
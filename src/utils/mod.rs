@@ -12,10 +12,17 @@ pub mod test_helper;
 mod impl_validate_extra;
 mod import_double;
 mod misc;
+mod pdf;
 mod token;
+mod ttl_cache;
 mod unprotected_route;
 
-pub use misc::get_epoch_ts;
+pub use misc::{
+    financial_year_of, financial_year_range_ts, format_ts_ist, generate_referral_code, get_doc_u64,
+    get_epoch_ts, is_duplicate_key_error, month_range_ts, version_filter,
+};
+pub use pdf::render_text_pdf;
+pub use ttl_cache::TtlCache;
 
 #[cfg_attr(test, mockall_double::double)]
 use crate::config::database::DbClient;
@@ -33,6 +40,9 @@ impl Utility {
     pub fn get_epoch_ts(&self) -> u64 {
         misc::get_epoch_ts()
     }
+    pub fn generate_referral_code(&self) -> String {
+        misc::generate_referral_code()
+    }
     pub async fn get_seq_nxt_val(&self, seq_id: &str, db: &DbClient) -> anyhow::Result<u32> {
         misc::get_seq_nxt_val(seq_id, db).await
     }
@@ -0,0 +1,116 @@
+const PAGE_WIDTH: usize = 842;
+const PAGE_HEIGHT: usize = 595;
+const MARGIN: usize = 30;
+const FONT_SIZE: usize = 8;
+const LINE_HEIGHT: usize = 11;
+
+/// Render plain text lines into a PDF document with a monospaced font.
+/// Pages are in A4 landscape and new pages are added when a page is full.
+pub fn render_text_pdf(lines: &[String]) -> Vec<u8> {
+    let lines_per_page = (PAGE_HEIGHT - 2 * MARGIN) / LINE_HEIGHT;
+    let pages = if lines.is_empty() {
+        vec![lines]
+    } else {
+        lines.chunks(lines_per_page).collect()
+    };
+    // object 1 is catalog, 2 is page tree, 3 is font
+    // and each page has a page object followed by it's content stream
+    let kids = (0..pages.len())
+        .map(|i| format!("{} 0 R", 4 + 2 * i))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+        format!("<< /Type /Pages /Kids [{kids}] /Count {} >>", pages.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_owned(),
+    ];
+    for (i, page) in pages.iter().enumerate() {
+        let mut content = format!(
+            "BT /F1 {FONT_SIZE} Tf {LINE_HEIGHT} TL {MARGIN} {} Td\n",
+            PAGE_HEIGHT - MARGIN
+        );
+        for line in page.iter() {
+            content.push_str(&format!("({}) Tj T*\n", escape_pdf_text(line)));
+        }
+        content.push_str("ET");
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+            /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            5 + 2 * i
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+    pdf.into_bytes()
+}
+
+/// Escape the special characters of a PDF string literal.
+/// Non ASCII characters are not supported by the standard font, those are replaced.
+fn escape_pdf_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            ch if ch.is_ascii() && !ch.is_ascii_control() => escaped.push(ch),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_escape_pdf_text() {
+        assert_eq!(escape_pdf_text("a(b)c\\d"), "a\\(b\\)c\\\\d");
+        assert_eq!(escape_pdf_text("₹10"), "?10");
+    }
+
+    #[test]
+    fn test_render_text_pdf() {
+        let lines = (0..100).map(|i| format!("line {i}")).collect::<Vec<_>>();
+        let pdf = render_text_pdf(&lines);
+        let pdf = String::from_utf8(pdf).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Count 3"));
+        assert!(pdf.contains("(line 99) Tj"));
+        // xref must point to the start of the objects
+        let xref_offset = pdf.find("xref\n").unwrap();
+        assert!(pdf.contains(&format!("startxref\n{xref_offset}\n")));
+        let first_offset = pdf.find("1 0 obj").unwrap();
+        assert!(pdf.contains(&format!("{:010} 00000 n ", first_offset)));
+
+        let pdf = String::from_utf8(render_text_pdf(&[])).unwrap();
+        assert!(pdf.contains("/Count 1"));
+    }
+}
@@ -3,9 +3,12 @@ use axum::{
     http::{Request, StatusCode},
     Router,
 };
+use mockall::predicate::eq;
 use serde::de::DeserializeOwned;
 use tower::ServiceExt;
 
+use crate::{config::AppState, models::JwtClaims, utils::get_epoch_ts};
+
 #[cfg(test)]
 pub fn build_post_request(path: &str, body: &str, token: Option<&str>) -> Request<Body> {
     let builder = Request::builder()
@@ -31,6 +34,17 @@ pub fn build_get_request(path: &str, token: Option<&str>) -> Request<Body> {
     builder.body(Body::empty()).unwrap()
 }
 
+#[cfg(test)]
+pub fn build_delete_request(path: &str, token: Option<&str>) -> Request<Body> {
+    let builder = Request::builder().uri(path).method("DELETE");
+    let builder = if let Some(token) = token {
+        builder.header("Authorization", format!("Bearer {token}"))
+    } else {
+        builder
+    };
+    builder.body(Body::empty()).unwrap()
+}
+
 #[cfg(test)]
 pub async fn oneshot_request<T>(
     app: Router,
@@ -67,3 +81,27 @@ pub async fn oneshot_req_plain(
     dbg!("response for {} -> {}", uri, body);
     body.to_owned()
 }
+
+/// Expect the token to be decoded once as the claims of the user
+#[cfg(test)]
+pub fn mock_user_token(state: &mut AppState, token: &'static str, user_id: u32) {
+    let ts = get_epoch_ts();
+    state
+        .get_mut_utility()
+        .expect_decode_token()
+        .once()
+        .with(eq(token))
+        .returning(move |_| Ok(JwtClaims::new(user_id, None, false, ts as usize)));
+}
+
+/// Expect the token to be decoded once as the claims of an admin
+#[cfg(test)]
+pub fn mock_admin_token(state: &mut AppState, token: &'static str) {
+    let ts = get_epoch_ts();
+    state
+        .get_mut_utility()
+        .expect_decode_token()
+        .once()
+        .with(eq(token))
+        .returning(move |_| Ok(JwtClaims::new(1, None, true, ts as usize)));
+}
@@ -0,0 +1,239 @@
+use crate::{config::AppError, helpers::Helpers, import_double, models::*, utils::get_epoch_ts};
+
+import_double!(DbClient);
+
+pub async fn validate_contest(
+    db: &DbClient,
+    helper: &Helpers,
+    contest: &Contest,
+) -> Result<(), AppError> {
+    check_contest_rules(contest, get_epoch_ts()).map_err(AppError::BadRequest)?;
+    validate_media(db, helper, &contest.media, &contest.questions).await
+}
+
+pub async fn validate_question_set(
+    db: &DbClient,
+    helper: &Helpers,
+    question_set: &QuestionSet,
+) -> Result<(), AppError> {
+    check_questions(&question_set.questions).map_err(AppError::BadRequest)?;
+    validate_media(db, helper, &question_set.media, &question_set.questions).await
+}
+
+/// Check that the media of the contest and the questions exist
+async fn validate_media(
+    db: &DbClient,
+    helper: &Helpers,
+    contest_media: &ContestMedia,
+    questions: &[Question],
+) -> Result<(), AppError> {
+    let mut media = vec![(&contest_media.media_type, &contest_media.name)];
+    for question in questions.iter() {
+        if let Some(m) = &question.media {
+            media.push((&m.media_type, &m.name));
+        }
+    }
+    media.dedup();
+    for (media_type, name) in media {
+        let contest_helpers = helper.contest_helpers();
+        if !contest_helpers.media_exists(db, media_type, name).await? {
+            let msg = format!("{media_type:?} {name} not found");
+            return Err(AppError::BadRequest(msg));
+        }
+    }
+    Ok(())
+}
+
+/// Template is valid if the next contest created from it is valid
+pub async fn validate_contest_template(
+    db: &DbClient,
+    helper: &Helpers,
+    template: &ContestTemplate,
+) -> Result<(), AppError> {
+    let start_ts = template
+        .recurrence
+        .next_after(get_epoch_ts())
+        .ok_or(AppError::BadRequest("Invalid recurrence".into()))?;
+    let contest = template.contest_at(start_ts);
+    validate_contest(db, helper, &contest).await
+}
+
+fn check_contest_rules(contest: &Contest, ts: u64) -> Result<(), String> {
+    if contest.start_ts <= ts {
+        return Err("startTs must be in future".into());
+    }
+    if contest.end_ts <= contest.start_ts {
+        return Err("endTs must be greater than startTs".into());
+    }
+    if contest.min_players > contest.max_players {
+        return Err("minPlayers must not be greater than maxPlayers".into());
+    }
+    if contest.max_entries > contest.max_players {
+        return Err("maxEntries must not be greater than maxPlayers".into());
+    }
+    if contest.is_practice() && contest.entry_fee > 0 {
+        return Err("entryFee must be 0 for practice contests".into());
+    }
+    if contest.is_practice() && contest.prize_pool_type != PrizePoolType::Fixed {
+        return Err("Practice contests must have a fixed prize pool".into());
+    }
+    let fixed_prizes = contest
+        .prizes
+        .iter()
+        .any(|p| p.prize_type == PrizeType::Fixed);
+    if contest.prize_pool_type == PrizePoolType::Dynamic && fixed_prizes {
+        return Err("Prizes of a dynamic prize pool must be percentage of the pool".into());
+    }
+    check_questions(&contest.questions)?;
+    // fixed prizes of a guaranteed pool must be covered even if only a few players join
+    let players = match contest.prize_pool_type {
+        PrizePoolType::Guaranteed => 0,
+        _ => contest.max_players,
+    };
+    contest
+        .prize_structure_for(players)
+        .validate(contest.max_players)?;
+    Ok(())
+}
+
+fn check_questions(questions: &[Question]) -> Result<(), String> {
+    for (i, question) in questions.iter().enumerate() {
+        if let Err(e) = question.check_type_fields() {
+            return Err(format!("Question {}: {e}", i + 1));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+
+    use crate::config::AppState;
+
+    use super::*;
+
+    fn valid_contest(ts: u64) -> Contest {
+        Contest {
+            title: "Trailer quiz".into(),
+            prize_pool: 1000,
+            prizes: vec![RankPrize {
+                rank_from: 1,
+                rank_to: 2,
+                amount: 500,
+                ..Default::default()
+            }],
+            min_players: 2,
+            max_players: 10,
+            start_ts: ts + 100,
+            end_ts: ts + 200,
+            media: ContestMedia {
+                media_type: MediaType::Movie,
+                name: "Movie 1".into(),
+            },
+            questions: vec![Question {
+                question: "Question 1".into(),
+                options: vec!["A".into(), "B".into()],
+                answer: 1,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_contest_rules() {
+        let ts = 1000;
+        let contest = valid_contest(ts);
+        assert!(check_contest_rules(&contest, ts).is_ok());
+
+        let mut contest = valid_contest(ts);
+        contest.start_ts = ts;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "startTs must be in future");
+
+        let mut contest = valid_contest(ts);
+        contest.end_ts = contest.start_ts;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "endTs must be greater than startTs");
+
+        let mut contest = valid_contest(ts);
+        contest.min_players = 11;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "minPlayers must not be greater than maxPlayers");
+
+        let mut contest = valid_contest(ts);
+        contest.max_entries = 11;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "maxEntries must not be greater than maxPlayers");
+
+        let mut contest = valid_contest(ts);
+        contest.mode = ContestMode::Practice;
+        contest.entry_fee = 10;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "entryFee must be 0 for practice contests");
+        contest.entry_fee = 0;
+        assert!(check_contest_rules(&contest, ts).is_ok());
+
+        let mut contest = valid_contest(ts);
+        contest.prize_pool_type = PrizePoolType::Dynamic;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(
+            err,
+            "Prizes of a dynamic prize pool must be percentage of the pool"
+        );
+
+        let mut contest = valid_contest(ts);
+        contest.prize_pool_type = PrizePoolType::Guaranteed;
+        contest.prize_pool = 999;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "Total prize 1000 exceeds the prize pool 999");
+
+        let mut contest = valid_contest(ts);
+        contest.questions[0].answer = 2;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "Question 1: invalid answer");
+
+        let mut contest = valid_contest(ts);
+        contest.prizes[0].rank_to = 11;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "Invalid prize rank range 1-11");
+
+        let mut contest = valid_contest(ts);
+        contest.prizes[0].amount = 501;
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "Total prize 1002 exceeds the prize pool 1000");
+
+        let mut contest = valid_contest(ts);
+        contest.prizes.push(RankPrize {
+            rank_from: 2,
+            rank_to: 3,
+            prize_type: PrizeType::Fixed,
+            amount: 10,
+        });
+        let err = check_contest_rules(&contest, ts).unwrap_err();
+        assert_eq!(err, "Prize rank ranges 1-2 and 2-3 overlap");
+    }
+
+    #[tokio::test]
+    async fn test_validate_contest() {
+        let contest = valid_contest(get_epoch_ts());
+        let mut state = AppState::mock();
+        state
+            .get_mut_helpers()
+            .mut_contest_helpers()
+            .expect_media_exists()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(MediaType::Movie),
+                eq("Movie 1"),
+            )
+            .returning(|_, _, _| Ok(false));
+        match validate_contest(state.db(), state.helpers(), &contest).await {
+            Err(AppError::BadRequest(e)) => assert_eq!(e, "Movie Movie 1 not found"),
+            _ => panic!(),
+        };
+    }
+}
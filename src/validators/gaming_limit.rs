@@ -0,0 +1,187 @@
+use crate::{
+    config::AppError,
+    helpers::Helpers,
+    import_double,
+    models::*,
+    utils::{format_ts_ist, get_epoch_ts},
+};
+
+import_double!(DbClient);
+
+pub async fn validate_deposit_limits(
+    db: &DbClient,
+    helper: &Helpers,
+    user_id: u32,
+    amount: u64,
+) -> Result<(), AppError> {
+    let gaming_limit_helpers = helper.gaming_limit_helpers();
+    let (limits_result, totals_result) = tokio::join!(
+        gaming_limit_helpers.get_gaming_limits(db, user_id),
+        gaming_limit_helpers.get_period_totals(db, user_id, WalltetTransactionType::AddBalance)
+    );
+    let gaming_limits = limits_result?;
+    let totals = totals_result?;
+    let ts = get_epoch_ts();
+    validate_self_exclusion(&gaming_limits, ts)?;
+    validate_period_limits(&gaming_limits.deposit, &totals, amount, ts, "Deposit")
+}
+
+/// Self excluded users can't join any contest, including the free practice contests
+pub async fn validate_not_self_excluded(
+    db: &DbClient,
+    helper: &Helpers,
+    user_id: u32,
+) -> Result<(), AppError> {
+    let gaming_limits = helper
+        .gaming_limit_helpers()
+        .get_gaming_limits(db, user_id)
+        .await?;
+    validate_self_exclusion(&gaming_limits, get_epoch_ts())
+}
+
+/// Self exclusion is validated separately with `validate_not_self_excluded`
+pub async fn validate_contest_spend_limits(
+    db: &DbClient,
+    helper: &Helpers,
+    user_id: u32,
+    amount: u64,
+) -> Result<(), AppError> {
+    let gaming_limit_helpers = helper.gaming_limit_helpers();
+    let (limits_result, totals_result) = tokio::join!(
+        gaming_limit_helpers.get_gaming_limits(db, user_id),
+        gaming_limit_helpers.get_period_totals(db, user_id, WalltetTransactionType::PayForContest)
+    );
+    let gaming_limits = limits_result?;
+    let totals = totals_result?;
+    validate_period_limits(
+        &gaming_limits.contest_spend,
+        &totals,
+        amount,
+        get_epoch_ts(),
+        "Contest spend",
+    )
+}
+
+fn validate_self_exclusion(gaming_limits: &GamingLimits, ts: u64) -> Result<(), AppError> {
+    match gaming_limits.self_excluded_till {
+        Some(till) if gaming_limits.is_self_excluded(ts) => {
+            let msg = format!("User is self excluded till {}", format_ts_ist(till));
+            Err(AppError::BadRequest(msg))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_period_limits(
+    limits: &PeriodLimits,
+    totals: &PeriodTotals,
+    amount: u64,
+    ts: u64,
+    label: &str,
+) -> Result<(), AppError> {
+    match limits.check(totals, amount, ts) {
+        Some((period, limit)) => {
+            let msg = format!("{} exceeds the {} limit of {}", label, period, limit);
+            Err(AppError::BadRequest(msg))
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+
+    use crate::config::AppState;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_validate_deposit_limits() {
+        let user_id = 5;
+        let mut state = AppState::mock();
+
+        // scenario user is self excluded
+        let gaming_limit_helpers = state.get_mut_helpers().mut_gaming_limit_helpers();
+        gaming_limit_helpers
+            .expect_get_gaming_limits()
+            .once()
+            .with(function(|_: &DbClient| true), eq(user_id))
+            .returning(move |_, _| {
+                let gaming_limits = GamingLimits {
+                    user_id,
+                    self_excluded_till: Some(get_epoch_ts() + 1000),
+                    ..Default::default()
+                };
+                Ok(gaming_limits)
+            });
+        gaming_limit_helpers
+            .expect_get_period_totals()
+            .once()
+            .returning(|_, _, _| Ok(PeriodTotals::default()));
+        let result = validate_deposit_limits(state.db(), state.helpers(), user_id, 100);
+        match result.await {
+            Err(AppError::BadRequest(e)) => assert!(e.starts_with("User is self excluded till")),
+            _ => panic!(),
+        };
+
+        // scenario weekly limit exceeded
+        let gaming_limit_helpers = state.get_mut_helpers().mut_gaming_limit_helpers();
+        gaming_limit_helpers
+            .expect_get_gaming_limits()
+            .once()
+            .returning(move |_, _| {
+                let mut gaming_limits = GamingLimits {
+                    user_id,
+                    ..Default::default()
+                };
+                gaming_limits.deposit.weekly.amount = Some(500);
+                Ok(gaming_limits)
+            });
+        gaming_limit_helpers
+            .expect_get_period_totals()
+            .once()
+            .with(
+                function(|_: &DbClient| true),
+                eq(user_id),
+                function(|t: &WalltetTransactionType| *t == WalltetTransactionType::AddBalance),
+            )
+            .returning(|_, _, _| {
+                let totals = PeriodTotals {
+                    daily: 100,
+                    weekly: 450,
+                    monthly: 450,
+                };
+                Ok(totals)
+            });
+        let result = validate_deposit_limits(state.db(), state.helpers(), user_id, 100);
+        match result.await {
+            Err(AppError::BadRequest(e)) => {
+                assert_eq!(e, "Deposit exceeds the weekly limit of 500")
+            }
+            _ => panic!(),
+        };
+
+        // scenario within limits
+        let gaming_limit_helpers = state.get_mut_helpers().mut_gaming_limit_helpers();
+        gaming_limit_helpers
+            .expect_get_gaming_limits()
+            .once()
+            .returning(move |_, _| {
+                let mut gaming_limits = GamingLimits {
+                    user_id,
+                    self_excluded_till: Some(get_epoch_ts() - 1),
+                    ..Default::default()
+                };
+                gaming_limits.deposit.weekly.amount = Some(500);
+                Ok(gaming_limits)
+            });
+        gaming_limit_helpers
+            .expect_get_period_totals()
+            .once()
+            .returning(|_, _, _| Ok(PeriodTotals::default()));
+        let result = validate_deposit_limits(state.db(), state.helpers(), user_id, 100);
+        assert!(result.await.is_ok());
+    }
+}
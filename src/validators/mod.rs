@@ -1,6 +1,10 @@
 mod add_bal;
+mod contest;
 mod custom_validator;
+mod gaming_limit;
+mod referral;
 mod validate_extra;
+mod withdraw;
 
 pub use custom_validator::*;
 pub use validate_extra::ValidateExtra;
@@ -31,4 +35,80 @@ impl Validators {
     ) -> Result<(), AppError> {
         add_bal::validate_add_bal_transaction(db, helper, user_id, body).await
     }
+
+    pub async fn validate_referral_code(
+        &self,
+        db: &DbClient,
+        helper: &Helpers,
+        user_id: u32,
+        referral_code: &str,
+    ) -> Result<(), AppError> {
+        referral::validate_referral_code(db, helper, user_id, referral_code).await
+    }
+
+    pub async fn validate_deposit_limits(
+        &self,
+        db: &DbClient,
+        helper: &Helpers,
+        user_id: u32,
+        amount: u64,
+    ) -> Result<(), AppError> {
+        gaming_limit::validate_deposit_limits(db, helper, user_id, amount).await
+    }
+
+    pub async fn validate_not_self_excluded(
+        &self,
+        db: &DbClient,
+        helper: &Helpers,
+        user_id: u32,
+    ) -> Result<(), AppError> {
+        gaming_limit::validate_not_self_excluded(db, helper, user_id).await
+    }
+
+    pub async fn validate_contest_spend_limits(
+        &self,
+        db: &DbClient,
+        helper: &Helpers,
+        user_id: u32,
+        amount: u64,
+    ) -> Result<(), AppError> {
+        gaming_limit::validate_contest_spend_limits(db, helper, user_id, amount).await
+    }
+
+    pub async fn validate_withdraw(
+        &self,
+        db: &DbClient,
+        helper: &Helpers,
+        user_id: u32,
+        amount: u64,
+    ) -> Result<(), AppError> {
+        withdraw::validate_withdraw(db, helper, user_id, amount).await
+    }
+
+    pub async fn validate_contest(
+        &self,
+        db: &DbClient,
+        helper: &Helpers,
+        contest: &Contest,
+    ) -> Result<(), AppError> {
+        contest::validate_contest(db, helper, contest).await
+    }
+
+    pub async fn validate_contest_template(
+        &self,
+        db: &DbClient,
+        helper: &Helpers,
+        template: &ContestTemplate,
+    ) -> Result<(), AppError> {
+        contest::validate_contest_template(db, helper, template).await
+    }
+
+    pub async fn validate_question_set(
+        &self,
+        db: &DbClient,
+        helper: &Helpers,
+        question_set: &QuestionSet,
+    ) -> Result<(), AppError> {
+        contest::validate_question_set(db, helper, question_set).await
+    }
 }
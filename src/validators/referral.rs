@@ -0,0 +1,218 @@
+use crate::{config::AppError, helpers::Helpers, import_double, models::*, utils::get_epoch_ts};
+
+import_double!(DbClient);
+
+pub async fn validate_referral_code(
+    db: &DbClient,
+    helper: &Helpers,
+    user_id: u32,
+    referral_code: &str,
+) -> Result<(), AppError> {
+    let user_helpers = helper.user_helpers();
+    let (user_result, referrer_result) = tokio::join!(
+        user_helpers.get_user(db, user_id),
+        user_helpers.get_user_by_referral_code(db, referral_code)
+    );
+    let user = user_result?.ok_or(AppError::NotFound("User not found".into()))?;
+    if user.has_used_referral_code == Some(true) {
+        let err = AppError::BadRequest("User has already used a referral code".into());
+        return Err(err);
+    }
+    if let Some(referrer) = referrer_result? {
+        if referrer.id == user_id {
+            let err = AppError::BadRequest("User can not use own referral code".into());
+            return Err(err);
+        }
+        return Ok(());
+    }
+    let special_referral_code = user_helpers
+        .get_special_referral_code(db, referral_code)
+        .await?
+        .ok_or(AppError::NotFound("Invalid referral code".into()))?;
+    validate_special_referral_code(&special_referral_code, &user, get_epoch_ts())
+}
+
+pub fn validate_special_referral_code(
+    special_referral_code: &SpecialReferralCode,
+    user: &User,
+    ts: u64,
+) -> Result<(), AppError> {
+    if !special_referral_code.is_valid_at(ts) {
+        let err = AppError::BadRequest("Referral code is not valid at this moment".into());
+        return Err(err);
+    }
+    if special_referral_code.is_fully_redeemed() {
+        let err = AppError::BadRequest("Referral code is fully redeemed".into());
+        return Err(err);
+    }
+    if !special_referral_code.is_eligible(user) {
+        let err = AppError::BadRequest("User is not eligible for this referral code".into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+
+    use crate::config::AppState;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_validate_referral_code() {
+        let user_id = 5;
+        let referral_code = "ABCD1234";
+        let mut state = AppState::mock();
+
+        // scenario user has already used a referral code
+        let user_helpers = state.get_mut_helpers().mut_user_helpers();
+        user_helpers
+            .expect_get_user()
+            .once()
+            .with(function(|_: &DbClient| true), eq(user_id))
+            .returning(|_, _| {
+                let user = User {
+                    has_used_referral_code: Some(true),
+                    ..Default::default()
+                };
+                Ok(Some(user))
+            });
+        user_helpers
+            .expect_get_user_by_referral_code()
+            .once()
+            .returning(|_, _| Ok(None));
+        let result = validate_referral_code(state.db(), state.helpers(), user_id, referral_code);
+        match result.await {
+            Err(AppError::BadRequest(e)) => assert_eq!(e, "User has already used a referral code"),
+            _ => panic!(),
+        };
+
+        // scenario referral code not found
+        let user_helpers = state.get_mut_helpers().mut_user_helpers();
+        user_helpers
+            .expect_get_user()
+            .once()
+            .returning(|_, _| Ok(Some(User::default())));
+        user_helpers
+            .expect_get_user_by_referral_code()
+            .once()
+            .with(function(|_: &DbClient| true), eq(referral_code))
+            .returning(|_, _| Ok(None));
+        user_helpers
+            .expect_get_special_referral_code()
+            .once()
+            .with(function(|_: &DbClient| true), eq(referral_code))
+            .returning(|_, _| Ok(None));
+        let result = validate_referral_code(state.db(), state.helpers(), user_id, referral_code);
+        match result.await {
+            Err(AppError::NotFound(e)) => assert_eq!(e, "Invalid referral code"),
+            _ => panic!(),
+        };
+
+        // scenario self referral
+        let user_helpers = state.get_mut_helpers().mut_user_helpers();
+        user_helpers
+            .expect_get_user()
+            .once()
+            .returning(|_, _| Ok(Some(User::default())));
+        user_helpers
+            .expect_get_user_by_referral_code()
+            .once()
+            .returning(move |_, _| {
+                let user = User {
+                    id: user_id,
+                    ..Default::default()
+                };
+                Ok(Some(user))
+            });
+        let result = validate_referral_code(state.db(), state.helpers(), user_id, referral_code);
+        match result.await {
+            Err(AppError::BadRequest(e)) => assert_eq!(e, "User can not use own referral code"),
+            _ => panic!(),
+        };
+
+        // scenario special referral code fully redeemed
+        let user_helpers = state.get_mut_helpers().mut_user_helpers();
+        user_helpers
+            .expect_get_user()
+            .once()
+            .returning(|_, _| Ok(Some(User::default())));
+        user_helpers
+            .expect_get_user_by_referral_code()
+            .once()
+            .returning(|_, _| Ok(None));
+        user_helpers
+            .expect_get_special_referral_code()
+            .once()
+            .returning(|_, _| {
+                let special_referral_code = SpecialReferralCode {
+                    is_active: true,
+                    valid_till: u64::MAX,
+                    max_redemptions: Some(5),
+                    redemption_count: 5,
+                    ..Default::default()
+                };
+                Ok(Some(special_referral_code))
+            });
+        let result = validate_referral_code(state.db(), state.helpers(), user_id, referral_code);
+        match result.await {
+            Err(AppError::BadRequest(e)) => assert_eq!(e, "Referral code is fully redeemed"),
+            _ => panic!(),
+        };
+
+        // scenario valid referral code
+        let user_helpers = state.get_mut_helpers().mut_user_helpers();
+        user_helpers
+            .expect_get_user()
+            .once()
+            .returning(|_, _| Ok(Some(User::default())));
+        user_helpers
+            .expect_get_user_by_referral_code()
+            .once()
+            .returning(move |_, _| {
+                let user = User {
+                    id: user_id + 1,
+                    ..Default::default()
+                };
+                Ok(Some(user))
+            });
+        let result = validate_referral_code(state.db(), state.helpers(), user_id, referral_code);
+        assert!(result.await.is_ok());
+    }
+
+    #[test]
+    fn test_validate_special_referral_code() {
+        let user = User {
+            login_scheme: LoginScheme::Google,
+            ..Default::default()
+        };
+        let mut special_referral_code = SpecialReferralCode {
+            is_active: true,
+            valid_from: 10,
+            valid_till: 20,
+            ..Default::default()
+        };
+        match validate_special_referral_code(&special_referral_code, &user, 21) {
+            Err(AppError::BadRequest(e)) => {
+                assert_eq!(e, "Referral code is not valid at this moment")
+            }
+            _ => panic!(),
+        };
+        special_referral_code.target_segment = Some(ReferralTargetSegment {
+            login_scheme: Some(LoginScheme::OtpBased),
+            ..Default::default()
+        });
+        match validate_special_referral_code(&special_referral_code, &user, 15) {
+            Err(AppError::BadRequest(e)) => {
+                assert_eq!(e, "User is not eligible for this referral code")
+            }
+            _ => panic!(),
+        };
+        special_referral_code.target_segment = None;
+        let result = validate_special_referral_code(&special_referral_code, &user, 15);
+        assert!(result.is_ok());
+    }
+}
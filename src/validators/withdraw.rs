@@ -0,0 +1,87 @@
+use crate::{
+    config::AppError,
+    helpers::{check_wallet_freeze, Helpers},
+    import_double,
+    models::WalletOperation,
+};
+
+import_double!(DbClient);
+
+pub async fn validate_withdraw(
+    db: &DbClient,
+    helper: &Helpers,
+    user_id: u32,
+    amount: u64,
+) -> Result<(), AppError> {
+    let wallet = helper.wallet_helpers().get_wallet(db, user_id).await?;
+    check_wallet_freeze(wallet.as_ref(), WalletOperation::Withdraw)?;
+    let balance = wallet.map(|w| w.balance()).unwrap_or_default();
+    if amount > balance.withdrawable() {
+        let msg = format!(
+            "Insufficient withdrawable balance: {}",
+            balance.withdrawable()
+        );
+        return Err(AppError::BadRequest(msg));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::{eq, function};
+
+    use crate::{
+        config::AppState,
+        models::{Wallet, WalletFreeze, WalletFreezeScope},
+        utils::get_epoch_ts,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_validate_withdraw() {
+        let user_id = 5;
+        let mut state = AppState::mock();
+
+        // scenario withdrawals are frozen
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_wallet()
+            .once()
+            .with(function(|_: &DbClient| true), eq(user_id))
+            .returning(|_, _| {
+                let freeze = WalletFreeze {
+                    scope: WalletFreezeScope::Withdrawals,
+                    expires_ts: get_epoch_ts() + 100,
+                    ..Default::default()
+                };
+                let wallet = Wallet {
+                    freeze: Some(freeze),
+                    ..Default::default()
+                };
+                Ok(Some(wallet))
+            });
+        let result = validate_withdraw(state.db(), state.helpers(), user_id, 100);
+        match result.await {
+            Err(AppError::Forbidden(e)) => {
+                assert!(e.starts_with("Wallet is frozen for withdrawals till"))
+            }
+            _ => panic!(),
+        };
+
+        // scenario insufficient withdrawable balance
+        state
+            .get_mut_helpers()
+            .mut_wallet_helpers()
+            .expect_get_wallet()
+            .once()
+            .returning(|_, _| Ok(Some(Wallet::default())));
+        let result = validate_withdraw(state.db(), state.helpers(), user_id, 100);
+        match result.await {
+            Err(AppError::BadRequest(e)) => assert_eq!(e, "Insufficient withdrawable balance: 0"),
+            _ => panic!(),
+        };
+    }
+}